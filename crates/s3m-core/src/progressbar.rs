@@ -18,13 +18,19 @@ pub struct StreamBars {
     pub status: ProgressBar,
 }
 
+pub struct FanOutBars {
+    _multi: MultiProgress,
+    pub staging: ProgressBar,
+    pub status: Vec<ProgressBar>,
+}
+
 impl Bar {
     #[must_use]
     pub fn new(file_size: u64) -> Self {
         let pb = ProgressBar::new(file_size);
 
         let style_result = ProgressStyle::default_bar()
-            .template("[{elapsed_precise}] {bar:50.green/blue} {bytes}/{total_bytes} ({bytes_per_sec} - {eta})");
+            .template("[{elapsed_precise}] {bar:50.green/blue} {bytes}/{total_bytes} ({bytes_per_sec} - {eta}) {msg}");
 
         let style = match style_result {
             Ok(style) => style,
@@ -66,37 +72,74 @@ impl Bar {
     pub fn new_stream(buffer_size: u64) -> Option<StreamBars> {
         let multi = MultiProgress::new();
 
-        let staging = multi.add(ProgressBar::new(buffer_size));
-        let staging_style = match ProgressStyle::with_template(
-            "[{elapsed_precise}] buffering {bytes}/{total_bytes}",
-        ) {
-            Ok(style) => style,
-            Err(err) => {
-                eprintln!("Error creating staging progress style: {err}");
-                return None;
-            }
-        };
-        staging.set_style(staging_style);
+        let staging = staging_bar(&multi, buffer_size)?;
+        let status = status_bar(&multi, "[{elapsed_precise}] {msg} {spinner:.green}")?;
 
-        let status = multi.add(ProgressBar::new_spinner());
-        status.enable_steady_tick(Duration::from_millis(200));
+        Some(StreamBars {
+            _multi: multi,
+            staging,
+            status,
+        })
+    }
 
-        let uploaded_style = match ProgressStyle::default_spinner()
-            .tick_strings(PROGRES_CHARS_SPINNER)
-            .template("[{elapsed_precise}] {msg} {spinner:.green}")
-        {
-            Ok(style) => style,
-            Err(err) => {
-                eprintln!("Error creating uploaded progress style: {err}");
-                return None;
-            }
-        };
-        status.set_style(uploaded_style);
+    /// A staging bar shared by the destinations of a stream and one status
+    /// line per destination, prefixed with its label
+    #[must_use]
+    pub fn new_fanout(buffer_size: u64, labels: &[String]) -> Option<FanOutBars> {
+        let multi = MultiProgress::new();
 
-        Some(StreamBars {
+        let staging = staging_bar(&multi, buffer_size)?;
+
+        let status = labels
+            .iter()
+            .map(|label| {
+                let status = status_bar(
+                    &multi,
+                    "[{elapsed_precise}] {prefix}: {msg} {spinner:.green}",
+                )?;
+                status.set_prefix(label.clone());
+                Some(status)
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(FanOutBars {
             _multi: multi,
             staging,
             status,
         })
     }
 }
+
+fn staging_bar(multi: &MultiProgress, buffer_size: u64) -> Option<ProgressBar> {
+    let staging = multi.add(ProgressBar::new(buffer_size));
+    let staging_style =
+        match ProgressStyle::with_template("[{elapsed_precise}] buffering {bytes}/{total_bytes}") {
+            Ok(style) => style,
+            Err(err) => {
+                eprintln!("Error creating staging progress style: {err}");
+                return None;
+            }
+        };
+    staging.set_style(staging_style);
+
+    Some(staging)
+}
+
+fn status_bar(multi: &MultiProgress, template: &str) -> Option<ProgressBar> {
+    let status = multi.add(ProgressBar::new_spinner());
+    status.enable_steady_tick(Duration::from_millis(200));
+
+    let uploaded_style = match ProgressStyle::default_spinner()
+        .tick_strings(PROGRES_CHARS_SPINNER)
+        .template(template)
+    {
+        Ok(style) => style,
+        Err(err) => {
+            eprintln!("Error creating uploaded progress style: {err}");
+            return None;
+        }
+    };
+    status.set_style(uploaded_style);
+
+    Some(status)
+}
@@ -5,7 +5,7 @@
 
 use crate::s3::error::Error;
 use crate::{
-    s3::actions::{Action, response_error},
+    s3::actions::{Action, response_error, send},
     s3::responses::CompleteMultipartUploadResult,
     s3::{S3, checksum::Checksum, request, tools},
 };
@@ -93,6 +93,9 @@ impl<'a> CompleteMultipartUpload<'a> {
     // that alias would shadow `std::result::Result` and break `#[derive(Serialize)]`
     // in this file.
     pub async fn request(mut self, s3: &S3) -> Result<CompleteMultipartUploadResult, Error> {
+        // long multipart uploads can outlive temporary credentials
+        s3.refresh_credentials().await?;
+
         let body = to_string(&self)?;
 
         let digest = tools::sha256_digest(&body);
@@ -152,15 +155,18 @@ impl<'a> CompleteMultipartUpload<'a> {
             }
         }
 
+        let body = &Bytes::from(body);
+
         // sign the request
-        let (url, headers) = &self.sign(s3, digest.as_ref(), None, Some(body.len()))?;
-
-        let response = request::upload(
-            s3.client(),
-            url.clone(),
-            self.http_method()?,
-            headers,
-            Bytes::from(body),
+        let response = send(
+            &self,
+            s3,
+            digest.as_ref(),
+            None,
+            Some(body.len()),
+            |url, method, headers| async move {
+                request::upload(s3.client(), url, method, &headers, body.clone()).await
+            },
         )
         .await?;
 
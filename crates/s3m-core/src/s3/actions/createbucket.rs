@@ -1,6 +1,6 @@
 use crate::s3::error::Result;
 use crate::{
-    s3::actions::{Action, response_error},
+    s3::actions::{Action, response_error, send},
     s3::{S3, request, tools},
 };
 use bytes::Bytes;
@@ -34,13 +34,17 @@ impl<'a> CreateBucket<'a> {
             s3.region.name()
         );
 
-        let (url, headers) = &self.sign(s3, tools::sha256_digest(&xml).as_ref(), None, None)?;
-        let response = request::upload(
-            s3.client(),
-            url.clone(),
-            self.http_method()?,
-            headers,
-            Bytes::from(xml),
+        let xml = &Bytes::from(xml);
+
+        let response = send(
+            &self,
+            s3,
+            tools::sha256_digest(xml).as_ref(),
+            None,
+            None,
+            |url, method, headers| async move {
+                request::upload(s3.client(), url, method, &headers, xml.clone()).await
+            },
         )
         .await?;
 
@@ -5,9 +5,10 @@
 
 use crate::s3::error::Result;
 use crate::{
-    s3::actions::{Action, response_error},
+    s3::actions::{Action, response_error, send},
     s3::object_lock::ObjectLock,
     s3::responses::InitiateMultipartUploadResult,
+    s3::tagging::Tagging,
     s3::{S3, checksum::Checksum, request, tools},
 };
 use quick_xml::de::from_str;
@@ -21,6 +22,7 @@ pub struct CreateMultipartUpload<'a> {
     meta: Option<BTreeMap<String, String>>,
     additional_checksum: Option<Checksum>,
     object_lock: Option<ObjectLock>,
+    tagging: Option<String>,
 }
 
 impl<'a> CreateMultipartUpload<'a> {
@@ -38,23 +40,30 @@ impl<'a> CreateMultipartUpload<'a> {
             meta,
             additional_checksum,
             object_lock,
+            tagging: None,
         }
     }
 
+    /// Tag the object on completion of the upload (`x-amz-tagging`).
+    #[must_use]
+    pub fn tagging(mut self, tagging: Option<&Tagging>) -> Self {
+        self.tagging = tagging.map(Tagging::header_value);
+        self
+    }
+
     /// # Errors
     ///
     /// Will return `Err` if can not make the request
     pub async fn request(&self, s3: &S3) -> Result<InitiateMultipartUploadResult> {
-        let (url, headers) = &self.sign(s3, tools::sha256_digest("").as_ref(), None, None)?;
-
-        let response = request::request(
-            s3.client(),
-            url.clone(),
-            self.http_method()?,
-            headers,
-            None,
+        let response = send(
+            self,
+            s3,
+            tools::sha256_digest("").as_ref(),
             None,
             None,
+            |url, method, headers| async move {
+                request::request(s3.client(), url, method, &headers, None, None, None).await
+            },
         )
         .await?;
 
@@ -106,6 +115,11 @@ impl Action for CreateMultipartUpload<'_> {
             object_lock.apply(&mut map);
         }
 
+        // <https://docs.aws.amazon.com/AmazonS3/latest/userguide/object-tagging.html>
+        if let Some(tagging) = &self.tagging {
+            map.insert("x-amz-tagging", tagging);
+        }
+
         Some(map)
     }
 
@@ -223,6 +237,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_headers_tagging() {
+        let tagging: Tagging = "team=ops".parse().unwrap();
+        let action =
+            CreateMultipartUpload::new("key", None, None, None, None).tagging(Some(&tagging));
+        let headers = action.headers().unwrap();
+        assert_eq!(headers.get("x-amz-tagging"), Some(&"team=ops"));
+    }
+
     #[test]
     fn test_query_pairs() {
         let action = CreateMultipartUpload::new("key", None, None, None, None);
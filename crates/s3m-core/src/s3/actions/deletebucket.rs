@@ -1,6 +1,6 @@
 use crate::s3::error::Result;
 use crate::{
-    s3::actions::{Action, response_error},
+    s3::actions::{Action, response_error, send},
     s3::{S3, request, tools},
 };
 use reqwest::Method;
@@ -25,15 +25,15 @@ impl DeleteBucket {
     ///
     /// Will return `Err` if can not make the request
     pub async fn request(&self, s3: &S3) -> Result<String> {
-        let (url, headers) = &self.sign(s3, tools::sha256_digest("").as_ref(), None, None)?;
-        let response = request::request(
-            s3.client(),
-            url.clone(),
-            self.http_method()?,
-            headers,
-            None,
+        let response = send(
+            self,
+            s3,
+            tools::sha256_digest("").as_ref(),
             None,
             None,
+            |url, method, headers| async move {
+                request::request(s3.client(), url, method, &headers, None, None, None).await
+            },
         )
         .await?;
 
@@ -3,13 +3,20 @@
 
 use crate::s3::{
     S3,
+    clock::REQUEST_TIME_TOO_SKEWED,
     error::{ApiError, Error, Result},
+    request::{RetryPolicy, is_retryable, is_retryable_status},
     responses::ErrorResponse,
     signature::Signature,
 };
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
 use quick_xml::de::from_str;
-use reqwest::{Method, Response};
-use std::{collections::BTreeMap, fmt::Write};
+use reqwest::{
+    Method, Response, StatusCode,
+    header::{HeaderMap, RETRY_AFTER},
+};
+use std::{collections::BTreeMap, fmt::Write, time::Duration};
 use url::Url;
 
 // <https://docs.aws.amazon.com/AmazonS3/latest/API/API_ListBuckets.html>
@@ -87,6 +94,34 @@ pub use self::createbucket::CreateBucket;
 mod deletebucket;
 pub use self::deletebucket::DeleteBucket;
 
+// <https://docs.aws.amazon.com/AmazonS3/latest/API/API_CopyObject.html>
+mod copyobject;
+pub use self::copyobject::{CopyObject, CopyObjectOutput};
+
+// <https://docs.aws.amazon.com/AmazonS3/latest/API/API_UploadPartCopy.html>
+mod uploadpartcopy;
+pub use self::uploadpartcopy::UploadPartCopy;
+
+// <https://docs.aws.amazon.com/AmazonS3/latest/API/API_GetBucketVersioning.html>
+mod getbucketversioning;
+pub use self::getbucketversioning::GetBucketVersioning;
+
+// <https://docs.aws.amazon.com/AmazonS3/latest/API/API_PutBucketVersioning.html>
+mod putbucketversioning;
+pub use self::putbucketversioning::{PutBucketVersioning, VersioningStatus};
+
+// <https://docs.aws.amazon.com/AmazonS3/latest/API/API_GetBucketLifecycleConfiguration.html>
+mod getbucketlifecycleconfiguration;
+pub use self::getbucketlifecycleconfiguration::GetBucketLifecycleConfiguration;
+
+// <https://docs.aws.amazon.com/AmazonS3/latest/API/API_PutBucketLifecycleConfiguration.html>
+mod putbucketlifecycleconfiguration;
+pub use self::putbucketlifecycleconfiguration::PutBucketLifecycleConfiguration;
+
+// <https://docs.aws.amazon.com/AmazonS3/latest/API/API_DeleteBucketLifecycle.html>
+mod deletebucketlifecycle;
+pub use self::deletebucketlifecycle::DeleteBucketLifecycle;
+
 // <https://docs.aws.amazon.com/AmazonS3/latest/API/API_GetObjectLockConfiguration.html>
 mod getobjectlockconfiguration;
 pub use self::getobjectlockconfiguration::GetObjectLockConfiguration;
@@ -111,6 +146,18 @@ pub use self::getobjectlegalhold::GetObjectLegalHold;
 mod putobjectlegalhold;
 pub use self::putobjectlegalhold::PutObjectLegalHold;
 
+// <https://docs.aws.amazon.com/AmazonS3/latest/API/API_GetObjectTagging.html>
+mod getobjecttagging;
+pub use self::getobjecttagging::GetObjectTagging;
+
+// <https://docs.aws.amazon.com/AmazonS3/latest/API/API_PutObjectTagging.html>
+mod putobjecttagging;
+pub use self::putobjecttagging::PutObjectTagging;
+
+// <https://docs.aws.amazon.com/AmazonS3/latest/API/API_DeleteObjectTagging.html>
+mod deleteobjecttagging;
+pub use self::deleteobjecttagging::DeleteObjectTagging;
+
 pub trait Action {
     // headers to send in the request
     fn headers(&self) -> Option<BTreeMap<&str, &str>>;
@@ -169,6 +216,141 @@ pub trait Action {
     }
 }
 
+/// Sign the request of `action` and send it with `send`, retried with the
+/// [`RetryPolicy`] of the `S3`.
+///
+/// # Errors
+///
+/// Will return `Err` if the request can not be signed or sent, see
+/// [`send_with`]
+pub(crate) async fn send<A, F, Fut>(
+    action: &A,
+    s3: &S3,
+    hash_payload: &[u8],
+    md5: Option<&[u8]>,
+    content_length: Option<usize>,
+    send: F,
+) -> Result<Response>
+where
+    A: Action + ?Sized,
+    F: Fn(Url, Method, BTreeMap<String, String>) -> Fut,
+    Fut: Future<Output = Result<Response>>,
+{
+    send_with(
+        s3.retry_policy(),
+        action,
+        s3,
+        hash_payload,
+        md5,
+        content_length,
+        send,
+    )
+    .await
+}
+
+/// Sign the request of `action` and send it with `send`.
+///
+/// Transport errors and error responses that
+/// [`is_retryable`](crate::s3::request::is_retryable) are signed and
+/// sent again as long as `policy` allows. Every response is used to measure
+/// the clock skew to the server, see
+/// [`Clock::observe`](crate::s3::clock::Clock::observe). A `403` with a skewed
+/// `Date` or the `RequestTimeTooSkewed` code is signed again with the
+/// corrected clock and sent once more.
+///
+/// # Errors
+///
+/// Will return `Err` if the request can not be signed or sent, or the error of
+/// S3 once the retries are exhausted. Error responses that are not sent again
+/// are returned as `Ok` for the action to handle
+pub(crate) async fn send_with<A, F, Fut>(
+    policy: RetryPolicy,
+    action: &A,
+    s3: &S3,
+    hash_payload: &[u8],
+    md5: Option<&[u8]>,
+    content_length: Option<usize>,
+    send: F,
+) -> Result<Response>
+where
+    A: Action + ?Sized,
+    F: Fn(Url, Method, BTreeMap<String, String>) -> Fut,
+    Fut: Future<Output = Result<Response>>,
+{
+    let mut attempt = 1;
+    let mut corrected = false;
+
+    loop {
+        let (url, headers) = action.sign(s3, hash_payload, md5, content_length)?;
+
+        let response = match send(url, action.http_method()?, headers).await {
+            Ok(response) => response,
+            Err(e) if policy.backoff(attempt, &e).await => {
+                attempt += 1;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        let status = response.status();
+        let signed = !s3.no_sign_request;
+        let observed = signed && s3.clock().observe(response.headers());
+        let forbidden = signed && !corrected && status == StatusCode::FORBIDDEN;
+
+        if status.is_success() || !(forbidden || is_retryable_status(status)) {
+            return Ok(response);
+        }
+
+        // the body tells whether to send again, a response that is not sent
+        // again is rebuilt from it for the action
+        let headers = response.headers().clone();
+        let body = match response.bytes().await {
+            Ok(body) => body,
+            Err(e) => {
+                let e = Error::from(e);
+                if policy.backoff(attempt, &e).await {
+                    attempt += 1;
+                    continue;
+                }
+                return Err(e);
+            }
+        };
+        let error = response_error(rebuild_response(status, &headers, body.clone())).await;
+
+        if forbidden {
+            // HEAD responses have no body, the skew of the `Date` is all there is
+            let skewed = observed
+                || (error.code() == Some(REQUEST_TIME_TOO_SKEWED) && s3.clock().correct(&headers));
+
+            if skewed {
+                log::debug!(
+                    "{REQUEST_TIME_TOO_SKEWED}, signing again with a clock offset of {}s",
+                    s3.clock().offset().num_seconds()
+                );
+
+                corrected = true;
+                continue;
+            }
+        } else if policy.backoff(attempt, &error).await {
+            attempt += 1;
+            continue;
+        } else if is_retryable(&error) {
+            return Err(error);
+        }
+
+        return Ok(rebuild_response(status, &headers, body));
+    }
+}
+
+// a response with the status, headers and already read body of another one
+fn rebuild_response(status: StatusCode, headers: &HeaderMap, body: Bytes) -> Response {
+    let mut response = http::Response::new(body);
+    *response.status_mut() = status;
+    *response.headers_mut() = headers.clone();
+
+    Response::from(response)
+}
+
 /// Build a structured [`Error::Api`] from a non-2xx S3 response.
 ///
 /// Header reads are best-effort and never fail the conversion; the populated
@@ -198,6 +380,12 @@ pub async fn response_error(response: Response) -> Error {
         detail.insert("Request ID", v.to_string());
     }
 
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|h| h.to_str().ok())
+        .and_then(parse_retry_after);
+
     let mut code = None;
     let mut message = None;
 
@@ -233,5 +421,87 @@ pub async fn response_error(response: Response) -> Error {
         code,
         message,
         details,
+        retry_after,
     })
 }
+
+/// `Retry-After` is either a number of seconds or an HTTP date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    value
+        .trim()
+        .parse::<u64>()
+        .map(Duration::from_secs)
+        .ok()
+        .or_else(|| {
+            DateTime::parse_from_rfc2822(value).ok().map(|date| {
+                (date.with_timezone(&Utc) - Utc::now())
+                    .to_std()
+                    .unwrap_or_default()
+            })
+        })
+}
+
+/// Detect an error returned inside a `200 OK` response.
+///
+/// Copy operations stream whitespace to keep the connection alive while S3
+/// works, so a failure that happens after the status line was sent arrives as
+/// an `<Error>` document with a success status. Returns `None` when `body` is
+/// not an error document.
+pub(crate) fn embedded_error(status: u16, body: &str) -> Option<Error> {
+    let e = from_str::<ErrorResponse>(body).ok()?;
+
+    let details = format!(
+        "HTTP Status Code: {status}\nCode: {}\nMessage: {}\n",
+        e.code, e.message
+    );
+
+    Some(Error::Api(ApiError {
+        status,
+        code: Some(e.code),
+        message: Some(e.message),
+        details,
+        retry_after: None,
+    }))
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("3"), Some(Duration::from_secs(3)));
+        assert_eq!(parse_retry_after(" 0 "), Some(Duration::ZERO));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+
+        let later = (Utc::now() + chrono::TimeDelta::seconds(120)).to_rfc2822();
+        let delay = parse_retry_after(&later).unwrap();
+        assert!(delay > Duration::from_secs(100) && delay <= Duration::from_mins(2));
+
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[test]
+    fn test_embedded_error() {
+        let body = r"<Error><Code>InternalError</Code><Message>We encountered an internal error. Please try again.</Message><RequestId>656c76696e6727732072657175657374</RequestId></Error>";
+        let err = embedded_error(200, body).unwrap();
+        assert_eq!(err.code(), Some("InternalError"));
+        assert_eq!(err.status(), Some(200));
+    }
+
+    #[test]
+    fn test_embedded_error_success_body() {
+        let body = r#"<CopyObjectResult><ETag>"abc"</ETag></CopyObjectResult>"#;
+        assert!(embedded_error(200, body).is_none());
+    }
+}
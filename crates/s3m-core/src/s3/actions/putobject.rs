@@ -1,11 +1,12 @@
 use crate::s3::error::Result;
 use crate::s3::{
     S3,
-    actions::{Action, response_error},
+    actions::{Action, response_error, send},
     checksum::{Checksum, sha256_md5_digest},
     object_lock::ObjectLock,
     options::RequestOptions,
     request,
+    tagging::Tagging,
 };
 use reqwest::Method;
 use std::collections::BTreeMap;
@@ -22,6 +23,7 @@ pub struct PutObject<'a> {
     sender: Option<UnboundedSender<usize>>,
     additional_checksum: Option<Checksum>,
     object_lock: Option<ObjectLock>,
+    tagging: Option<String>,
 }
 
 impl<'a> PutObject<'a> {
@@ -43,16 +45,23 @@ impl<'a> PutObject<'a> {
             sender,
             additional_checksum,
             object_lock,
+            tagging: None,
         }
     }
 
+    /// Tag the object on upload (`x-amz-tagging`).
+    #[must_use]
+    pub fn tagging(mut self, tagging: Option<&Tagging>) -> Self {
+        self.tagging = tagging.map(Tagging::header_value);
+        self
+    }
+
     /// # Errors
     ///
     /// Will return `Err` if can not make the request
     pub async fn request(self, s3: &S3, globals: RequestOptions) -> Result<BTreeMap<&str, String>> {
         let (sha, md5, length) = sha256_md5_digest(self.file).await?;
 
-        let (url, headers) = &self.sign(s3, sha.as_ref(), Some(md5.as_ref()), Some(length))?;
         let progress = self.sender.clone().map(|sender| {
             let callback: request::ProgressCallback = Arc::new(move |bytes_count| {
                 if sender.send(bytes_count).is_err() {
@@ -62,14 +71,29 @@ impl<'a> PutObject<'a> {
             callback
         });
 
-        let response = request::request(
-            s3.client(),
-            url.clone(),
-            self.http_method()?,
-            headers,
-            Some(Path::new(self.file)),
-            progress,
-            globals.throttle,
+        let throttle = globals.throttle.as_ref();
+
+        let response = send(
+            &self,
+            s3,
+            sha.as_ref(),
+            Some(md5.as_ref()),
+            Some(length),
+            |url, method, headers| {
+                let (file, progress) = (self.file, progress.clone());
+                async move {
+                    request::request(
+                        s3.client(),
+                        url,
+                        method,
+                        &headers,
+                        Some(file),
+                        progress,
+                        throttle,
+                    )
+                    .await
+                }
+            },
         )
         .await?;
 
@@ -130,6 +154,11 @@ impl Action for PutObject<'_> {
             object_lock.apply(&mut map);
         }
 
+        // <https://docs.aws.amazon.com/AmazonS3/latest/userguide/object-tagging.html>
+        if let Some(tagging) = &self.tagging {
+            map.insert("x-amz-tagging", tagging);
+        }
+
         Some(map)
     }
 
@@ -231,6 +260,18 @@ mod tests {
         assert_eq!(headers.get("x-amz-object-lock-legal-hold"), Some(&"ON"));
     }
 
+    #[test]
+    fn test_headers_tagging() {
+        let tagging: Tagging = "team=ops;env=prod".parse().unwrap();
+        let action = PutObject::new("key", Path::new("/"), None, None, None, None, None)
+            .tagging(Some(&tagging));
+        let headers = action.headers().unwrap();
+        assert_eq!(headers.get("x-amz-tagging"), Some(&"env=prod&team=ops"));
+
+        let action = PutObject::new("key", Path::new("/"), None, None, None, None, None);
+        assert_eq!(action.headers().unwrap().get("x-amz-tagging"), None);
+    }
+
     #[test]
     fn test_sign() {
         let s3 = S3::new(
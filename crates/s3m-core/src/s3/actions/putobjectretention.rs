@@ -1,7 +1,7 @@
 use crate::s3::error::Error;
 use crate::s3::object_lock::ObjectLockMode;
 use crate::{
-    s3::actions::{Action, response_error},
+    s3::actions::{Action, response_error, send},
     s3::{S3, request, tools},
 };
 use bytes::Bytes;
@@ -70,15 +70,17 @@ impl<'a> PutObjectRetention<'a> {
         let sha256 = tools::sha256_digest(&body);
         let md5 = md5::compute(body.as_bytes());
 
-        let (url, headers) =
-            &self.sign(s3, sha256.as_ref(), Some(md5.as_ref()), Some(body.len()))?;
-
-        let response = request::upload(
-            s3.client(),
-            url.clone(),
-            self.http_method()?,
-            headers,
-            Bytes::from(body),
+        let body = &Bytes::from(body);
+
+        let response = send(
+            self,
+            s3,
+            sha256.as_ref(),
+            Some(md5.as_ref()),
+            Some(body.len()),
+            |url, method, headers| async move {
+                request::upload(s3.client(), url, method, &headers, body.clone()).await
+            },
         )
         .await?;
 
@@ -1,10 +1,10 @@
 use crate::s3::error::{Error, Result};
 use crate::s3::{
     S3,
-    actions::{Action, response_error},
+    actions::{Action, response_error, send_with},
     checksum::{Checksum, sha256_md5_digest_multipart},
     options::RequestOptions,
-    request,
+    request::{self, RetryPolicy},
 };
 use reqwest::Method;
 use std::{collections::BTreeMap, path::Path};
@@ -61,6 +61,9 @@ impl<'a> UploadPart<'a> {
     ///
     /// Will return `Err` if can not make the request
     pub async fn request(mut self, s3: &S3, globals: &RequestOptions) -> Result<String> {
+        // long multipart uploads can outlive temporary credentials
+        s3.refresh_credentials().await?;
+
         let (sha256, md5, length, checksum) = sha256_md5_digest_multipart(
             self.file,
             self.seek,
@@ -96,18 +99,31 @@ impl<'a> UploadPart<'a> {
             }
         }
 
-        let (url, headers) = &self.sign(s3, sha256.as_ref(), Some(md5.as_ref()), Some(length))?;
-
-        let response = request::multipart_upload(request::MultipartRequest {
-            client: s3.client(),
-            url: url.clone(),
-            method: self.http_method()?,
-            headers,
-            file: self.file,
-            seek: self.seek,
-            chunk: self.chunk,
-            throttle: globals.throttle,
-        })
+        // the caller retries the whole part
+        let response = send_with(
+            RetryPolicy::none(),
+            &self,
+            s3,
+            sha256.as_ref(),
+            Some(md5.as_ref()),
+            Some(length),
+            |url, method, headers| {
+                let (file, seek, chunk) = (self.file, self.seek, self.chunk);
+                async move {
+                    request::multipart_upload(request::MultipartRequest {
+                        client: s3.client(),
+                        url,
+                        method,
+                        headers: &headers,
+                        file,
+                        seek,
+                        chunk,
+                        throttle: globals.throttle.as_ref(),
+                    })
+                    .await
+                }
+            },
+        )
         .await?;
 
         if response.status().is_success() {
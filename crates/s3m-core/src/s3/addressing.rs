@@ -0,0 +1,128 @@
+//! Bucket addressing style.
+//!
+//! S3 accepts the bucket either in the path of the URL
+//! (`https://s3.us-east-1.amazonaws.com/bucket/key`) or as part of the host
+//! name (`https://bucket.s3.us-east-1.amazonaws.com/key`). Some providers only
+//! support one of the two.
+//!
+//! <https://docs.aws.amazon.com/AmazonS3/latest/userguide/VirtualHosting.html>
+
+use crate::s3::error::Error;
+use serde::Deserialize;
+use std::{fmt, str::FromStr};
+
+/// How the bucket is addressed in request URLs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Addressing {
+    /// `{endpoint}/{bucket}/{key}`
+    #[default]
+    Path,
+    /// `{bucket}.{endpoint}/{key}`
+    Virtual,
+    /// Virtual-hosted style on AWS endpoints when the bucket name is a valid
+    /// DNS label, path style otherwise.
+    Auto,
+}
+
+impl Addressing {
+    /// Whether `bucket` goes into the host name, `host` is the endpoint
+    /// without scheme.
+    #[must_use]
+    pub fn is_virtual(self, host: &str, bucket: &str) -> bool {
+        match self {
+            Self::Path => false,
+            Self::Virtual => true,
+            Self::Auto => is_aws_host(host) && is_dns_compatible(bucket),
+        }
+    }
+}
+
+impl FromStr for Addressing {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "path" => Ok(Self::Path),
+            "virtual" => Ok(Self::Virtual),
+            "auto" => Ok(Self::Auto),
+            _ => Err(Error::Other(format!(
+                "invalid addressing {s:?}, expected path, virtual or auto"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Addressing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Path => "path",
+            Self::Virtual => "virtual",
+            Self::Auto => "auto",
+        })
+    }
+}
+
+fn is_aws_host(host: &str) -> bool {
+    let host = host.split(':').next().unwrap_or(host);
+
+    host.ends_with(".amazonaws.com") || host.ends_with(".amazonaws.com.cn")
+}
+
+/// Bucket names that can be used as a host label with TLS: 3-63 lowercase
+/// letters, digits and hyphens, starting and ending with a letter or digit.
+/// Names with dots are valid buckets but break the wildcard certificate.
+#[must_use]
+pub fn is_dns_compatible(bucket: &str) -> bool {
+    (3..=63).contains(&bucket.len())
+        && bucket
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+        && !bucket.starts_with('-')
+        && !bucket.ends_with('-')
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("path".parse::<Addressing>().unwrap(), Addressing::Path);
+        assert_eq!(
+            "virtual".parse::<Addressing>().unwrap(),
+            Addressing::Virtual
+        );
+        assert_eq!("auto".parse::<Addressing>().unwrap(), Addressing::Auto);
+        assert!("vhost".parse::<Addressing>().is_err());
+        assert_eq!(Addressing::default(), Addressing::Path);
+    }
+
+    #[test]
+    fn test_is_dns_compatible() {
+        assert!(is_dns_compatible("my-bucket"));
+        assert!(is_dns_compatible("bucket1"));
+        assert!(!is_dns_compatible("my.bucket"));
+        assert!(!is_dns_compatible("My-Bucket"));
+        assert!(!is_dns_compatible("ab"));
+        assert!(!is_dns_compatible("-bucket"));
+        assert!(!is_dns_compatible("bucket_name"));
+    }
+
+    #[test]
+    fn test_is_virtual() {
+        let aws = "s3.us-west-1.amazonaws.com";
+        assert!(!Addressing::Path.is_virtual(aws, "bucket"));
+        assert!(Addressing::Virtual.is_virtual("minio.local:9000", "my.bucket"));
+        assert!(Addressing::Auto.is_virtual(aws, "bucket"));
+        assert!(!Addressing::Auto.is_virtual(aws, "my.bucket"));
+        assert!(!Addressing::Auto.is_virtual("localhost:9000", "bucket"));
+    }
+}
@@ -0,0 +1,430 @@
+//! Bandwidth limit shared by every transfer of the process.
+//!
+//! A token bucket refilled at the configured rate: each chunk of an upload,
+//! download or stream part reserves its share of the bandwidth before being
+//! sent, so the total stays at the limit no matter how many requests run in
+//! parallel. Clones share the same bucket.
+//!
+//! The limit can follow a [`BandwidthSchedule`], re-evaluated while the
+//! transfers are running so a long upload slows down or speeds up when it
+//! crosses from one time window to the next.
+
+use crate::s3::Error;
+use chrono::{Local, NaiveTime};
+use futures::stream::{Stream, StreamExt};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    str::FromStr,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+use tokio::time::{Instant, sleep_until};
+
+/// How often the schedule is checked against the local time.
+const SCHEDULE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Bandwidth limits by time of day, in kilobytes per second (0 for no limit):
+///
+/// ```yaml
+/// bandwidth_schedule:
+///   "08:00-18:00": 20480
+///   default: 0
+/// ```
+///
+/// Windows are in local time, end excluded, and may wrap around midnight
+/// (`"22:00-06:00"`). The first window containing the time applies, else
+/// `default`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "BTreeMap<String, usize>")]
+pub struct BandwidthSchedule {
+    windows: Vec<Window>,
+    default: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Window {
+    start: NaiveTime,
+    end: NaiveTime,
+    kilobytes: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Bandwidth {
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug, Default)]
+struct Bucket {
+    // 0 for no limit
+    bytes_per_second: u64,
+    // when the bandwidth reserved so far has been used
+    next: Option<Instant>,
+    schedule: Option<BandwidthSchedule>,
+    // last time the schedule was checked
+    checked: Option<Instant>,
+}
+
+impl BandwidthSchedule {
+    /// The limit in kilobytes per second at `time`, 0 for no limit.
+    #[must_use]
+    pub fn limit_at(&self, time: NaiveTime) -> usize {
+        self.windows
+            .iter()
+            .find(|window| window.contains(time))
+            .map_or(self.default, |window| window.kilobytes)
+    }
+}
+
+impl TryFrom<BTreeMap<String, usize>> for BandwidthSchedule {
+    type Error = Error;
+
+    fn try_from(map: BTreeMap<String, usize>) -> Result<Self, Self::Error> {
+        let mut schedule = Self::default();
+
+        for (key, kilobytes) in map {
+            if key == "default" {
+                schedule.default = kilobytes;
+            } else {
+                let (start, end) = key.split_once('-').ok_or_else(|| invalid_window(&key))?;
+
+                let window = Window {
+                    start: parse_time(start).ok_or_else(|| invalid_window(&key))?,
+                    end: parse_time(end).ok_or_else(|| invalid_window(&key))?,
+                    kilobytes,
+                };
+
+                if window.start == window.end {
+                    return Err(invalid_window(&key));
+                }
+
+                schedule.windows.push(window);
+            }
+        }
+
+        Ok(schedule)
+    }
+}
+
+impl FromStr for BandwidthSchedule {
+    type Err = Error;
+
+    /// Parse `08:00-18:00=20480,default=0`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(|entry| {
+                let (key, kilobytes) = entry
+                    .split_once('=')
+                    .ok_or_else(|| Error::Other(format!("invalid bandwidth schedule: {s}")))?;
+                let kilobytes = kilobytes
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| Error::Other(format!("invalid bandwidth schedule: {s}")))?;
+                Ok((key.trim().to_string(), kilobytes))
+            })
+            .collect::<Result<BTreeMap<_, _>, Error>>()
+            .and_then(Self::try_from)
+    }
+}
+
+impl fmt::Display for BandwidthSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for window in &self.windows {
+            write!(
+                f,
+                "{}-{}={},",
+                window.start.format("%H:%M"),
+                window.end.format("%H:%M"),
+                window.kilobytes
+            )?;
+        }
+        write!(f, "default={}", self.default)
+    }
+}
+
+impl Window {
+    fn contains(self, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            self.start <= time && time < self.end
+        } else {
+            // wraps around midnight
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+fn parse_time(time: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M").ok()
+}
+
+fn invalid_window(key: &str) -> Error {
+    Error::Other(format!(
+        "invalid bandwidth schedule window: {key}, expected HH:MM-HH:MM"
+    ))
+}
+
+impl Bandwidth {
+    /// Limit to `kilobytes` per second, 0 for no limit.
+    #[must_use]
+    pub fn new(kilobytes: usize) -> Self {
+        let bandwidth = Self::default();
+        bandwidth.set_limit(kilobytes);
+        bandwidth
+    }
+
+    /// Follow `schedule`, checked against the local time while transferring.
+    #[must_use]
+    pub fn with_schedule(schedule: BandwidthSchedule) -> Self {
+        let bandwidth = Self::default();
+        bandwidth.bucket().schedule = Some(schedule);
+        bandwidth
+    }
+
+    /// The limit in kilobytes per second, `None` if there is no limit.
+    #[must_use]
+    pub fn limit(&self) -> Option<usize> {
+        let bytes_per_second = {
+            let mut bucket = self.bucket();
+            bucket.refresh(Instant::now());
+            bucket.bytes_per_second
+        };
+
+        (bytes_per_second > 0)
+            .then(|| usize::try_from(bytes_per_second / 1024).unwrap_or(usize::MAX))
+    }
+
+    /// Change the limit of every transfer sharing the bucket, 0 for no limit.
+    /// Replaces the schedule, if any.
+    pub fn set_limit(&self, kilobytes: usize) {
+        let mut bucket = self.bucket();
+
+        bucket.schedule = None;
+        bucket.set_limit(kilobytes);
+    }
+
+    /// Wait until `bytes` can be sent.
+    pub async fn consume(&self, bytes: usize) {
+        if let Some(until) = self.reserve(bytes) {
+            sleep_until(until).await;
+        }
+    }
+
+    /// Limit the chunks of a request body or of a response.
+    pub fn throttle<S, B, E>(&self, stream: S) -> impl Stream<Item = Result<B, E>> + use<S, B, E>
+    where
+        S: Stream<Item = Result<B, E>>,
+        B: AsRef<[u8]>,
+    {
+        let bandwidth = self.clone();
+
+        stream.then(move |chunk| {
+            let bandwidth = bandwidth.clone();
+            async move {
+                if let Ok(bytes) = &chunk {
+                    bandwidth.consume(bytes.as_ref().len()).await;
+                }
+                chunk
+            }
+        })
+    }
+
+    // reserve the time to send `bytes`, returns when they can be sent
+    fn reserve(&self, bytes: usize) -> Option<Instant> {
+        let mut bucket = self.bucket();
+        let now = Instant::now();
+
+        bucket.refresh(now);
+
+        if bucket.bytes_per_second == 0 {
+            return None;
+        }
+
+        let start = bucket.next.map_or(now, |next| next.max(now));
+
+        let nanos = u128::from(u64::try_from(bytes).unwrap_or(u64::MAX)) * 1_000_000_000
+            / u128::from(bucket.bytes_per_second);
+
+        bucket.next = Some(start + Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX)));
+
+        (start > now).then_some(start)
+    }
+
+    fn bucket(&self) -> MutexGuard<'_, Bucket> {
+        self.bucket.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Bucket {
+    fn set_limit(&mut self, kilobytes: usize) {
+        self.bytes_per_second = u64::try_from(kilobytes)
+            .unwrap_or(u64::MAX)
+            .saturating_mul(1024);
+        self.next = None;
+    }
+
+    // apply the limit of the schedule for the current time
+    fn refresh(&mut self, now: Instant) {
+        if self
+            .checked
+            .is_some_and(|checked| now.duration_since(checked) < SCHEDULE_INTERVAL)
+        {
+            return;
+        }
+
+        let Some(kilobytes) = self
+            .schedule
+            .as_ref()
+            .map(|schedule| schedule.limit_at(Local::now().time()))
+        else {
+            return;
+        };
+
+        self.checked = Some(now);
+
+        if u64::try_from(kilobytes)
+            .unwrap_or(u64::MAX)
+            .saturating_mul(1024)
+            != self.bytes_per_second
+        {
+            log::info!("bandwidth schedule: {kilobytes}KB/s (0 for no limit)");
+            self.set_limit(kilobytes);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit() {
+        assert_eq!(Bandwidth::default().limit(), None);
+        assert_eq!(Bandwidth::new(0).limit(), None);
+
+        let bandwidth = Bandwidth::new(1024);
+        assert_eq!(bandwidth.limit(), Some(1024));
+
+        // clones share the limit
+        let clone = bandwidth.clone();
+        clone.set_limit(10);
+        assert_eq!(bandwidth.limit(), Some(10));
+        clone.set_limit(0);
+        assert_eq!(bandwidth.limit(), None);
+    }
+
+    fn time(time: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(time, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_schedule() {
+        let schedule: BandwidthSchedule = "08:00-18:00=20480, 22:00-06:00=1024, default=0"
+            .parse()
+            .unwrap();
+
+        assert_eq!(schedule.limit_at(time("07:59")), 0);
+        assert_eq!(schedule.limit_at(time("08:00")), 20480);
+        assert_eq!(schedule.limit_at(time("17:59")), 20480);
+        assert_eq!(schedule.limit_at(time("18:00")), 0);
+        // wraps around midnight
+        assert_eq!(schedule.limit_at(time("23:00")), 1024);
+        assert_eq!(schedule.limit_at(time("05:59")), 1024);
+        assert_eq!(schedule.limit_at(time("06:00")), 0);
+
+        assert_eq!(
+            schedule.to_string(),
+            "08:00-18:00=20480,22:00-06:00=1024,default=0"
+        );
+
+        for invalid in [
+            "08:00=1",
+            "08:00-25:00=1",
+            "8-18=1",
+            "08:00-08:00=1",
+            "default",
+        ] {
+            assert!(invalid.parse::<BandwidthSchedule>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_schedule_deserialize() {
+        let schedule: BandwidthSchedule =
+            serde_yaml_ng::from_str("\"08:00-18:00\": 20480\ndefault: 10\n").unwrap();
+        assert_eq!(schedule.limit_at(time("12:00")), 20480);
+        assert_eq!(schedule.limit_at(time("20:00")), 10);
+
+        assert!(serde_yaml_ng::from_str::<BandwidthSchedule>("office: 1\n").is_err());
+    }
+
+    #[test]
+    fn test_with_schedule() {
+        // the same limit all day long
+        let schedule: BandwidthSchedule = "00:00-12:00=100,12:00-00:00=100".parse().unwrap();
+        let bandwidth = Bandwidth::with_schedule(schedule);
+        assert_eq!(bandwidth.limit(), Some(100));
+
+        let unlimited = Bandwidth::with_schedule("default=0".parse().unwrap());
+        assert_eq!(unlimited.limit(), None);
+
+        // an explicit limit replaces the schedule
+        bandwidth.set_limit(10);
+        assert_eq!(bandwidth.limit(), Some(10));
+    }
+
+    #[tokio::test]
+    async fn test_consume_unlimited() {
+        let bandwidth = Bandwidth::default();
+        let start = Instant::now();
+        bandwidth.consume(usize::MAX).await;
+        assert!(start.elapsed() < Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn test_consume_shared() {
+        // 4 transfers of 128 KiB at 512 KB/s in total
+        let bandwidth = Bandwidth::new(512);
+        let start = Instant::now();
+
+        let mut tasks = tokio::task::JoinSet::new();
+
+        for _ in 0..4 {
+            let bandwidth = bandwidth.clone();
+            tasks.spawn(async move {
+                for _ in 0..4 {
+                    bandwidth.consume(32 * 1024).await;
+                }
+            });
+        }
+
+        tasks.join_all().await;
+
+        // the first chunk is sent right away
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(900), "{elapsed:?}");
+        assert!(elapsed < Duration::from_secs(3), "{elapsed:?}");
+    }
+
+    #[tokio::test]
+    async fn test_throttle_stream() {
+        let bandwidth = Bandwidth::new(64);
+        let chunks: Vec<Result<Vec<u8>, ()>> = vec![Ok(vec![0; 32 * 1024]); 3];
+        let start = Instant::now();
+
+        let throttled: Vec<_> = bandwidth
+            .throttle(futures::stream::iter(chunks))
+            .collect()
+            .await;
+
+        assert_eq!(throttled.len(), 3);
+        assert!(start.elapsed() >= Duration::from_millis(900));
+    }
+}
@@ -0,0 +1,145 @@
+//! Clock offset to the S3 server.
+//!
+//! Signatures are only valid within 15 minutes of the server time, a host
+//! with a drifting clock gets `RequestTimeTooSkewed` for every request. The
+//! offset measured from the `Date` header of the responses is added to the
+//! local time when signing.
+//!
+//! <https://docs.aws.amazon.com/AmazonS3/latest/API/sig-v4-authenticating-requests.html>
+
+use chrono::{DateTime, TimeDelta, Utc};
+use reqwest::header::{DATE, HeaderMap};
+use std::sync::atomic::{AtomicI64, Ordering};
+
+/// Offsets below this are left alone, the `Date` header has a resolution of
+/// one second and includes the network latency.
+pub const CLOCK_SKEW_THRESHOLD_SECONDS: i64 = 60;
+
+/// Error code of a request signed with a time too far from the server time.
+pub const REQUEST_TIME_TOO_SKEWED: &str = "RequestTimeTooSkewed";
+
+#[derive(Debug, Default)]
+pub struct Clock {
+    // server time minus local time
+    offset_ms: AtomicI64,
+}
+
+impl Clock {
+    /// The local time corrected by the offset to the server.
+    #[must_use]
+    pub fn now(&self) -> DateTime<Utc> {
+        Utc::now() + self.offset()
+    }
+
+    #[must_use]
+    pub fn offset(&self) -> TimeDelta {
+        TimeDelta::milliseconds(self.offset_ms.load(Ordering::Relaxed))
+    }
+
+    /// Measure the skew from the `Date` header of a response and keep it if it
+    /// differs from the current offset by more than
+    /// [`CLOCK_SKEW_THRESHOLD_SECONDS`]. Returns `true` if the offset changed.
+    pub fn observe(&self, headers: &HeaderMap) -> bool {
+        let Some(server) = server_date(headers) else {
+            return false;
+        };
+
+        let skew = server - Utc::now();
+
+        log::trace!("clock skew: {:.3}s", seconds(skew));
+
+        if (skew - self.offset()).abs() > TimeDelta::seconds(CLOCK_SKEW_THRESHOLD_SECONDS) {
+            self.set(skew);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Set the offset from the `Date` header of a response rejected with
+    /// `RequestTimeTooSkewed`. Returns `false` if there is no `Date`.
+    pub fn correct(&self, headers: &HeaderMap) -> bool {
+        server_date(headers).is_some_and(|server| {
+            self.set(server - Utc::now());
+            true
+        })
+    }
+
+    fn set(&self, skew: TimeDelta) {
+        log::debug!(
+            "clock skew of {:.3}s to the server, correcting the signing time",
+            seconds(skew)
+        );
+
+        self.offset_ms
+            .store(skew.num_milliseconds(), Ordering::Relaxed);
+    }
+}
+
+fn server_date(headers: &HeaderMap) -> Option<DateTime<Utc>> {
+    headers
+        .get(DATE)
+        .and_then(|date| date.to_str().ok())
+        .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+        .map(|date| date.with_timezone(&Utc))
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn seconds(delta: TimeDelta) -> f64 {
+    delta.num_milliseconds() as f64 / 1000.0
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn date(offset: TimeDelta) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            DATE,
+            HeaderValue::from_str(&(Utc::now() + offset).to_rfc2822()).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_observe() {
+        let clock = Clock::default();
+        assert_eq!(clock.offset(), TimeDelta::zero());
+
+        // within the threshold
+        assert!(!clock.observe(&date(TimeDelta::seconds(5))));
+        assert_eq!(clock.offset(), TimeDelta::zero());
+
+        assert!(clock.observe(&date(TimeDelta::minutes(-20))));
+        let offset = clock.offset();
+        assert!((offset + TimeDelta::minutes(20)).abs() < TimeDelta::seconds(2));
+        assert!((clock.now() - Utc::now() - offset).abs() < TimeDelta::seconds(1));
+
+        // same skew, nothing to do
+        assert!(!clock.observe(&date(TimeDelta::minutes(-20))));
+
+        // the local clock got fixed
+        assert!(clock.observe(&date(TimeDelta::zero())));
+        assert!(clock.offset().abs() < TimeDelta::seconds(2));
+    }
+
+    #[test]
+    fn test_correct() {
+        let clock = Clock::default();
+        assert!(!clock.correct(&HeaderMap::new()));
+        assert!(!clock.observe(&HeaderMap::new()));
+
+        // corrected even below the threshold
+        assert!(clock.correct(&date(TimeDelta::seconds(30))));
+        assert!(clock.offset() > TimeDelta::seconds(28));
+    }
+}
@@ -0,0 +1,215 @@
+//! Server-side copy settings.
+//!
+//! S3 can copy an object inside the service without the data ever leaving it:
+//! [`CopyObject`](crate::s3::actions::CopyObject) for objects up to 5 GB and
+//! [`UploadPartCopy`](crate::s3::actions::UploadPartCopy) for the parts of a
+//! multipart upload when the source is larger than that.
+//!
+//! This module carries the pieces shared by both actions: the
+//! `x-amz-copy-source` location, the `x-amz-metadata-directive`, and the
+//! `x-amz-copy-source-if-*` conditional headers.
+//!
+//! <https://docs.aws.amazon.com/AmazonS3/latest/userguide/copy-object.html>
+
+use crate::s3::error::Error;
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+use std::{collections::BTreeMap, str::FromStr};
+
+// Same unreserved set used for the canonical URI: keep `/` so the key reads
+// as a path, encode everything else that is not alphanumeric or `-._~`.
+const URLENCODE_COPY_SOURCE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'/')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// The object to copy from, `x-amz-copy-source`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CopySource {
+    pub bucket: String,
+    pub key: String,
+    pub version_id: Option<String>,
+}
+
+impl CopySource {
+    #[must_use]
+    pub fn new(bucket: &str, key: &str) -> Self {
+        Self {
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+            version_id: None,
+        }
+    }
+
+    /// Copy a specific version of the source object.
+    #[must_use]
+    pub fn version_id(mut self, version_id: Option<String>) -> Self {
+        self.version_id = version_id;
+        self
+    }
+
+    /// The URL-encoded `x-amz-copy-source` header value,
+    /// `/bucket/key[?versionId=id]`.
+    #[must_use]
+    pub fn header_value(&self) -> String {
+        let path = utf8_percent_encode(
+            &format!("/{}/{}", self.bucket, self.key),
+            URLENCODE_COPY_SOURCE,
+        )
+        .to_string();
+
+        match &self.version_id {
+            Some(vid) => format!(
+                "{path}?versionId={}",
+                utf8_percent_encode(vid, URLENCODE_COPY_SOURCE)
+            ),
+            None => path,
+        }
+    }
+}
+
+/// Whether the destination keeps the source metadata or gets new metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataDirective {
+    /// Keep the metadata of the source object (S3 default).
+    Copy,
+    /// Replace the metadata with the values sent in the request.
+    Replace,
+}
+
+impl MetadataDirective {
+    /// The value S3 expects in the `x-amz-metadata-directive` header.
+    #[must_use]
+    pub const fn as_amz(self) -> &'static str {
+        match self {
+            Self::Copy => "COPY",
+            Self::Replace => "REPLACE",
+        }
+    }
+}
+
+impl FromStr for MetadataDirective {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "COPY" => Ok(Self::Copy),
+            "REPLACE" => Ok(Self::Replace),
+            other => Err(Error::Other(format!(
+                "invalid metadata directive {other:?}, expected COPY or REPLACE"
+            ))),
+        }
+    }
+}
+
+/// Preconditions evaluated against the source object; S3 answers
+/// `412 Precondition Failed` (or `304` for `if_none_match`) when they fail.
+///
+/// Dates are HTTP dates, e.g. `Wed, 21 Oct 2015 07:28:00 GMT`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CopyConditions {
+    pub if_match: Option<String>,
+    pub if_none_match: Option<String>,
+    pub if_modified_since: Option<String>,
+    pub if_unmodified_since: Option<String>,
+}
+
+impl CopyConditions {
+    /// `true` when no precondition is set.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.if_match.is_none()
+            && self.if_none_match.is_none()
+            && self.if_modified_since.is_none()
+            && self.if_unmodified_since.is_none()
+    }
+
+    /// Insert the `x-amz-copy-source-if-*` headers implied by this config into `map`.
+    pub fn apply<'a>(&'a self, map: &mut BTreeMap<&'a str, &'a str>) {
+        if let Some(etag) = &self.if_match {
+            map.insert("x-amz-copy-source-if-match", etag);
+        }
+
+        if let Some(etag) = &self.if_none_match {
+            map.insert("x-amz-copy-source-if-none-match", etag);
+        }
+
+        if let Some(date) = &self.if_modified_since {
+            map.insert("x-amz-copy-source-if-modified-since", date);
+        }
+
+        if let Some(date) = &self.if_unmodified_since {
+            map.insert("x-amz-copy-source-if-unmodified-since", date);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_value() {
+        let source = CopySource::new("bucket", "path/to/file.txt");
+        assert_eq!(source.header_value(), "/bucket/path/to/file.txt");
+    }
+
+    #[test]
+    fn test_header_value_encoded() {
+        let source = CopySource::new("bucket", "dir/a b+c.txt");
+        assert_eq!(source.header_value(), "/bucket/dir/a%20b%2Bc.txt");
+    }
+
+    #[test]
+    fn test_header_value_version() {
+        let source = CopySource::new("bucket", "key").version_id(Some("v1".to_string()));
+        assert_eq!(source.header_value(), "/bucket/key?versionId=v1");
+    }
+
+    #[test]
+    fn test_metadata_directive() {
+        assert_eq!(MetadataDirective::Copy.as_amz(), "COPY");
+        assert_eq!(MetadataDirective::Replace.as_amz(), "REPLACE");
+        assert_eq!(
+            "replace".parse::<MetadataDirective>().unwrap(),
+            MetadataDirective::Replace
+        );
+        assert!("bogus".parse::<MetadataDirective>().is_err());
+    }
+
+    #[test]
+    fn test_conditions_apply() {
+        let conditions = CopyConditions {
+            if_match: Some("\"abc\"".to_string()),
+            if_unmodified_since: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
+            ..Default::default()
+        };
+        assert!(!conditions.is_empty());
+
+        let mut map = BTreeMap::new();
+        conditions.apply(&mut map);
+        assert_eq!(map.get("x-amz-copy-source-if-match"), Some(&"\"abc\""));
+        assert_eq!(
+            map.get("x-amz-copy-source-if-unmodified-since"),
+            Some(&"Wed, 21 Oct 2015 07:28:00 GMT")
+        );
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn test_conditions_empty() {
+        let conditions = CopyConditions::default();
+        assert!(conditions.is_empty());
+        let mut map = BTreeMap::new();
+        conditions.apply(&mut map);
+        assert!(map.is_empty());
+    }
+}
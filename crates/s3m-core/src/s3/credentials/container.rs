@@ -0,0 +1,227 @@
+//! Container credentials endpoint of ECS tasks and EKS Pod Identity.
+//!
+//! <https://docs.aws.amazon.com/sdkref/latest/guide/feature-container-credentials.html>
+
+use crate::s3::{
+    actions::response_error,
+    credentials::{Credentials, provider::JsonCredentials, provider::ProvideCredentials},
+    error::{Error, Result},
+};
+use futures::future::{BoxFuture, FutureExt};
+use reqwest::{Client, header::AUTHORIZATION};
+use std::{env, path::PathBuf, time::Duration};
+
+/// Host of `AWS_CONTAINER_CREDENTIALS_RELATIVE_URI`.
+pub const ECS_CONTAINER_HOST: &str = "http://169.254.170.2";
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone)]
+pub struct ContainerProvider {
+    uri: Option<String>,
+    authorization_token: Option<String>,
+    authorization_token_file: Option<PathBuf>,
+    client: Client,
+}
+
+impl ContainerProvider {
+    #[must_use]
+    pub fn new(uri: &str) -> Self {
+        Self {
+            uri: Some(uri.to_string()),
+            authorization_token: None,
+            authorization_token_file: None,
+            client: client(),
+        }
+    }
+
+    /// Endpoint and authorization from `AWS_CONTAINER_CREDENTIALS_FULL_URI` or
+    /// `AWS_CONTAINER_CREDENTIALS_RELATIVE_URI`, and
+    /// `AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE` or
+    /// `AWS_CONTAINER_AUTHORIZATION_TOKEN`.
+    #[must_use]
+    pub fn from_env() -> Self {
+        let var = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+
+        let uri = var("AWS_CONTAINER_CREDENTIALS_FULL_URI").or_else(|| {
+            var("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI")
+                .map(|path| format!("{ECS_CONTAINER_HOST}{path}"))
+        });
+
+        Self {
+            uri,
+            authorization_token: var("AWS_CONTAINER_AUTHORIZATION_TOKEN"),
+            authorization_token_file: var("AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE")
+                .map(PathBuf::from),
+            client: client(),
+        }
+    }
+
+    #[must_use]
+    pub fn authorization_token(mut self, token: Option<String>) -> Self {
+        self.authorization_token = token;
+        self
+    }
+
+    /// File with the `Authorization` header value, read on every call. Takes
+    /// precedence over [`Self::authorization_token`].
+    #[must_use]
+    pub fn authorization_token_file(mut self, path: Option<PathBuf>) -> Self {
+        self.authorization_token_file = path;
+        self
+    }
+
+    async fn authorization(&self) -> Result<Option<String>> {
+        match &self.authorization_token_file {
+            Some(path) => tokio::fs::read_to_string(path)
+                .await
+                .map(|token| Some(token.trim().to_string()))
+                .map_err(|e| {
+                    Error::Other(format!(
+                        "could not read container authorization token {}: {e}",
+                        path.display()
+                    ))
+                }),
+            None => Ok(self.authorization_token.clone()),
+        }
+    }
+
+    async fn fetch(&self) -> Result<Credentials> {
+        let uri = self.uri.as_ref().ok_or_else(|| {
+            Error::Other(
+                "AWS_CONTAINER_CREDENTIALS_FULL_URI or AWS_CONTAINER_CREDENTIALS_RELATIVE_URI is not set"
+                    .to_string(),
+            )
+        })?;
+
+        let mut request = self.client.get(uri);
+
+        if let Some(token) = self.authorization().await? {
+            request = request.header(AUTHORIZATION, token);
+        }
+
+        let response = request.send().await?;
+
+        if !response.status().is_success() {
+            return Err(response_error(response).await);
+        }
+
+        JsonCredentials::parse(&response.text().await?)
+    }
+}
+
+impl ProvideCredentials for ContainerProvider {
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials>> {
+        self.fetch().boxed()
+    }
+}
+
+fn client() -> Client {
+    // the endpoint is link-local, fail fast when it is not there
+    Client::builder()
+        .timeout(TIMEOUT)
+        .build()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    const RESPONSE: &str = r#"{"AccessKeyId": "ASIACONTAINER", "SecretAccessKey": "container-secret", "Token": "container-token", "Expiration": "2030-01-01T00:00:00Z", "RoleArn": "arn:aws:iam::123456789012:role/s3m"}"#;
+
+    #[tokio::test]
+    async fn test_container_provider() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/v2/credentials")
+            .match_header("authorization", "secret-auth")
+            .with_status(200)
+            .with_body(RESPONSE)
+            .create_async()
+            .await;
+
+        let provider = ContainerProvider::new(&format!("{}/v2/credentials", server.url()))
+            .authorization_token(Some("secret-auth".to_string()));
+
+        let credentials = provider.credentials().await.unwrap();
+        assert_eq!(credentials.aws_access_key_id(), "ASIACONTAINER");
+        assert_eq!(credentials.aws_session_token(), Some("container-token"));
+        assert!(credentials.expiration().is_some());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_container_provider_token_file() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/pod-identity")
+            .match_header("authorization", "from-file")
+            .with_status(200)
+            .with_body(RESPONSE)
+            .create_async()
+            .await;
+
+        let mut token = NamedTempFile::new().unwrap();
+        token.write_all(b"from-file\n").unwrap();
+
+        let provider = ContainerProvider::new(&format!("{}/pod-identity", server.url()))
+            .authorization_token(Some("ignored".to_string()))
+            .authorization_token_file(Some(token.path().to_path_buf()));
+
+        assert!(provider.credentials().await.is_ok());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_container_provider_error() {
+        let mut server = Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/v2/credentials")
+            .with_status(401)
+            .create_async()
+            .await;
+
+        let provider = ContainerProvider::new(&format!("{}/v2/credentials", server.url()));
+        let err = provider.credentials().await.unwrap_err();
+        assert_eq!(err.status(), Some(401));
+    }
+
+    #[test]
+    fn test_from_env() {
+        temp_env::with_vars(
+            [
+                ("AWS_CONTAINER_CREDENTIALS_FULL_URI", None),
+                ("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI", Some("/v2/creds")),
+                ("AWS_CONTAINER_AUTHORIZATION_TOKEN", Some("token")),
+                ("AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE", None),
+            ],
+            || {
+                let provider = ContainerProvider::from_env();
+                assert_eq!(
+                    provider.uri.as_deref(),
+                    Some("http://169.254.170.2/v2/creds")
+                );
+                assert_eq!(provider.authorization_token.as_deref(), Some("token"));
+            },
+        );
+
+        temp_env::with_vars_unset(
+            [
+                "AWS_CONTAINER_CREDENTIALS_FULL_URI",
+                "AWS_CONTAINER_CREDENTIALS_RELATIVE_URI",
+            ],
+            || assert!(ContainerProvider::from_env().uri.is_none()),
+        );
+    }
+}
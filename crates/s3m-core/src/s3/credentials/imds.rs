@@ -0,0 +1,193 @@
+//! EC2 instance metadata service (`IMDSv2`), credentials of the instance
+//! profile.
+//!
+//! <https://docs.aws.amazon.com/AWSEC2/latest/UserGuide/instance-metadata-security-credentials.html>
+
+use crate::s3::{
+    actions::response_error,
+    credentials::{Credentials, provider::JsonCredentials, provider::ProvideCredentials},
+    error::{Error, Result},
+};
+use futures::future::{BoxFuture, FutureExt};
+use reqwest::{Client, Response};
+use std::{env, time::Duration};
+
+/// Default IMDS endpoint.
+pub const IMDS_ENDPOINT: &str = "http://169.254.169.254";
+
+const TOKEN_PATH: &str = "/latest/api/token";
+const CREDENTIALS_PATH: &str = "/latest/meta-data/iam/security-credentials/";
+const TOKEN_HEADER: &str = "x-aws-ec2-metadata-token";
+const TOKEN_TTL_HEADER: &str = "x-aws-ec2-metadata-token-ttl-seconds";
+const TOKEN_TTL_SECONDS: &str = "21600";
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone)]
+pub struct ImdsProvider {
+    endpoint: String,
+    client: Client,
+}
+
+impl ImdsProvider {
+    #[must_use]
+    pub fn new(endpoint: &str) -> Self {
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            // the endpoint is link-local, fail fast when it is not there
+            client: Client::builder()
+                .timeout(TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Endpoint from `AWS_EC2_METADATA_SERVICE_ENDPOINT`, or [`IMDS_ENDPOINT`].
+    #[must_use]
+    pub fn from_env() -> Self {
+        let endpoint = env::var("AWS_EC2_METADATA_SERVICE_ENDPOINT")
+            .ok()
+            .filter(|v| !v.is_empty());
+
+        Self::new(endpoint.as_deref().unwrap_or(IMDS_ENDPOINT))
+    }
+
+    async fn fetch(&self) -> Result<Credentials> {
+        // IMDSv2: every request needs a session token
+        let token = text(
+            self.client
+                .put(format!("{}{TOKEN_PATH}", self.endpoint))
+                .header(TOKEN_TTL_HEADER, TOKEN_TTL_SECONDS)
+                .send()
+                .await?,
+        )
+        .await?;
+
+        let roles = text(
+            self.client
+                .get(format!("{}{CREDENTIALS_PATH}", self.endpoint))
+                .header(TOKEN_HEADER, &token)
+                .send()
+                .await?,
+        )
+        .await?;
+
+        let role = roles
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .ok_or_else(|| Error::Other("no IAM role attached to the instance".to_string()))?;
+
+        let credentials = text(
+            self.client
+                .get(format!("{}{CREDENTIALS_PATH}{role}", self.endpoint))
+                .header(TOKEN_HEADER, &token)
+                .send()
+                .await?,
+        )
+        .await?;
+
+        JsonCredentials::parse(&credentials)
+    }
+}
+
+impl ProvideCredentials for ImdsProvider {
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials>> {
+        self.fetch().boxed()
+    }
+}
+
+async fn text(response: Response) -> Result<String> {
+    if response.status().is_success() {
+        Ok(response.text().await?)
+    } else {
+        Err(response_error(response).await)
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    #[tokio::test]
+    async fn test_imds_provider() {
+        let mut server = Server::new_async().await;
+        let token = server
+            .mock("PUT", "/latest/api/token")
+            .match_header("x-aws-ec2-metadata-token-ttl-seconds", "21600")
+            .with_status(200)
+            .with_body("imds-token")
+            .create_async()
+            .await;
+        let credentials = server
+            .mock("GET", "/latest/meta-data/iam/security-credentials/s3m-role")
+            .match_header("x-aws-ec2-metadata-token", "imds-token")
+            .with_status(200)
+            .with_body(
+                r#"{"Code": "Success", "Type": "AWS-HMAC", "AccessKeyId": "ASIAIMDS", "SecretAccessKey": "imds-secret", "Token": "imds-session", "Expiration": "2030-01-01T00:00:00Z"}"#,
+            )
+            .create_async()
+            .await;
+        let role = server
+            .mock("GET", "/latest/meta-data/iam/security-credentials/")
+            .match_header("x-aws-ec2-metadata-token", "imds-token")
+            .with_status(200)
+            .with_body("s3m-role\n")
+            .create_async()
+            .await;
+
+        let provider = ImdsProvider::new(&server.url());
+        let rs = provider.credentials().await.unwrap();
+
+        assert_eq!(rs.aws_access_key_id(), "ASIAIMDS");
+        assert_eq!(rs.aws_secret_access_key(), "imds-secret");
+        assert_eq!(rs.aws_session_token(), Some("imds-session"));
+        assert!(rs.expiration().is_some());
+
+        token.assert_async().await;
+        role.assert_async().await;
+        credentials.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_imds_provider_without_role() {
+        let mut server = Server::new_async().await;
+        let _token = server
+            .mock("PUT", "/latest/api/token")
+            .with_status(200)
+            .with_body("imds-token")
+            .create_async()
+            .await;
+        let _role = server
+            .mock("GET", "/latest/meta-data/iam/security-credentials/")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let provider = ImdsProvider::new(&server.url());
+        let err = provider.credentials().await.unwrap_err();
+        assert_eq!(err.status(), Some(404));
+    }
+
+    #[test]
+    fn test_from_env() {
+        temp_env::with_var(
+            "AWS_EC2_METADATA_SERVICE_ENDPOINT",
+            Some("http://[fd00:ec2::254]/"),
+            || {
+                assert_eq!(ImdsProvider::from_env().endpoint, "http://[fd00:ec2::254]");
+            },
+        );
+
+        temp_env::with_var_unset("AWS_EC2_METADATA_SERVICE_ENDPOINT", || {
+            assert_eq!(ImdsProvider::from_env().endpoint, IMDS_ENDPOINT);
+        });
+    }
+}
@@ -1,17 +1,41 @@
+use chrono::{DateTime, Duration, Utc};
 use secrecy::{ExposeSecret, SecretString};
 use std::env;
 
+pub mod container;
+pub mod imds;
+pub mod process;
+pub mod profile;
+pub mod provider;
+pub mod web_identity;
+pub use self::{
+    container::ContainerProvider,
+    imds::ImdsProvider,
+    process::ProcessProvider,
+    profile::Profile,
+    provider::{
+        CredentialSource, CredentialsCache, EnvProvider, ProvideCredentials, ProviderConfig,
+        StaticProvider,
+    },
+    web_identity::WebIdentityProvider,
+};
+
 #[derive(Clone, Debug)]
 pub struct Credentials {
     // AWS_ACCESS_KEY_ID
     key: String,
     // AWS_SECRET_ACCESS_KEY
     secret: SecretString,
+    // AWS_SESSION_TOKEN, only for temporary credentials
+    token: Option<SecretString>,
+    // when temporary credentials stop being valid
+    expiration: Option<DateTime<Utc>>,
 }
 
 impl Credentials {
     /// Creates new credentials with the following priority:
-    /// 1. Environment variables (`AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`)
+    /// 1. Environment variables (`AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`,
+    ///    `AWS_SESSION_TOKEN`)
     /// 2. Passed parameters (`access`, `secret`)
     ///
     /// This allows environment variables to override configuration file values,
@@ -24,9 +48,46 @@ impl Credentials {
         Self {
             key: access_key,
             secret: SecretString::new(secret_key.into()),
+            token: env::var("AWS_SESSION_TOKEN")
+                .ok()
+                .map(|token| SecretString::new(token.into())),
+            expiration: None,
+        }
+    }
+
+    /// Credentials returned by a [`ProvideCredentials`] implementation, used
+    /// as they are: the environment variables are not consulted.
+    #[must_use]
+    pub fn from_parts(key: &str, secret: SecretString, token: Option<SecretString>) -> Self {
+        Self {
+            key: key.to_string(),
+            secret,
+            token,
+            expiration: None,
         }
     }
 
+    #[must_use]
+    pub const fn with_expiration(mut self, expiration: Option<DateTime<Utc>>) -> Self {
+        self.expiration = expiration;
+        self
+    }
+
+    /// Set the session token of temporary credentials (STS), usually the
+    /// `session_token` of the config file.
+    ///
+    /// Ignored when the environment defines `AWS_ACCESS_KEY_ID` or
+    /// `AWS_SESSION_TOKEN`, so a token from the config is never paired with
+    /// keys from the environment.
+    #[must_use]
+    pub fn with_session_token(mut self, token: Option<SecretString>) -> Self {
+        if env::var_os("AWS_ACCESS_KEY_ID").is_none() && env::var_os("AWS_SESSION_TOKEN").is_none()
+        {
+            self.token = token;
+        }
+        self
+    }
+
     /// Get a reference to the access key ID.
     #[must_use]
     pub fn aws_access_key_id(&self) -> &str {
@@ -38,6 +99,25 @@ impl Credentials {
     pub fn aws_secret_access_key(&self) -> &str {
         self.secret.expose_secret()
     }
+
+    /// Get a reference to the session token, if any.
+    #[must_use]
+    pub fn aws_session_token(&self) -> Option<&str> {
+        self.token.as_ref().map(ExposeSecret::expose_secret)
+    }
+
+    /// When the credentials expire, `None` for long-term credentials.
+    #[must_use]
+    pub const fn expiration(&self) -> Option<DateTime<Utc>> {
+        self.expiration
+    }
+
+    /// Whether the credentials expire within `window` from now.
+    #[must_use]
+    pub fn expires_within(&self, window: Duration) -> bool {
+        self.expiration
+            .is_some_and(|expiration| expiration - window <= Utc::now())
+    }
 }
 
 #[cfg(test)]
@@ -64,11 +144,72 @@ mod tests {
             [
                 ("AWS_ACCESS_KEY_ID", Some("env-access")),
                 ("AWS_SECRET_ACCESS_KEY", Some("env-secret")),
+                ("AWS_SESSION_TOKEN", None),
             ],
             || {
                 let creds = Credentials::new("access", &SecretString::new("secret".into()));
                 assert_eq!(creds.aws_access_key_id(), "env-access");
                 assert_eq!(creds.aws_secret_access_key(), "env-secret");
+                assert_eq!(creds.aws_session_token(), None);
+            },
+        );
+    }
+
+    #[test]
+    fn test_credentials_expiration() {
+        let creds = Credentials::from_parts("access", SecretString::new("secret".into()), None);
+        assert!(!creds.expires_within(Duration::minutes(5)));
+
+        let creds = creds.with_expiration(Some(Utc::now() + Duration::minutes(3)));
+        assert!(creds.expires_within(Duration::minutes(5)));
+        assert!(!creds.expires_within(Duration::minutes(1)));
+    }
+
+    #[test]
+    fn test_credentials_session_token() {
+        temp_env::with_vars_unset(
+            [
+                "AWS_ACCESS_KEY_ID",
+                "AWS_SECRET_ACCESS_KEY",
+                "AWS_SESSION_TOKEN",
+            ],
+            || {
+                let creds = Credentials::new("access", &SecretString::new("secret".into()))
+                    .with_session_token(Some(SecretString::new("token".into())));
+                assert_eq!(creds.aws_session_token(), Some("token"));
+            },
+        );
+    }
+
+    #[test]
+    fn test_credentials_session_token_env() {
+        temp_env::with_vars(
+            [
+                ("AWS_ACCESS_KEY_ID", Some("env-access")),
+                ("AWS_SECRET_ACCESS_KEY", Some("env-secret")),
+                ("AWS_SESSION_TOKEN", Some("env-token")),
+            ],
+            || {
+                let creds = Credentials::new("access", &SecretString::new("secret".into()))
+                    .with_session_token(Some(SecretString::new("token".into())));
+                assert_eq!(creds.aws_session_token(), Some("env-token"));
+            },
+        );
+    }
+
+    #[test]
+    fn test_credentials_session_token_env_keys() {
+        // keys from the environment must not be paired with the config token
+        temp_env::with_vars(
+            [
+                ("AWS_ACCESS_KEY_ID", Some("env-access")),
+                ("AWS_SECRET_ACCESS_KEY", Some("env-secret")),
+                ("AWS_SESSION_TOKEN", None),
+            ],
+            || {
+                let creds = Credentials::new("access", &SecretString::new("secret".into()))
+                    .with_session_token(Some(SecretString::new("token".into())));
+                assert_eq!(creds.aws_session_token(), None);
             },
         );
     }
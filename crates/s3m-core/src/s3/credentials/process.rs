@@ -0,0 +1,106 @@
+//! `credential_process`: an external command printing the credentials.
+//!
+//! <https://docs.aws.amazon.com/cli/latest/userguide/cli-configure-sourcing-external.html>
+
+use crate::s3::{
+    credentials::{Credentials, provider::JsonCredentials, provider::ProvideCredentials},
+    error::{Error, Result},
+};
+use futures::future::{BoxFuture, FutureExt};
+use tokio::process::Command;
+
+/// Runs `command` through the shell and parses the JSON it prints on stdout:
+///
+/// ```json
+/// {"Version": 1, "AccessKeyId": "...", "SecretAccessKey": "...",
+///  "SessionToken": "...", "Expiration": "2030-01-01T00:00:00Z"}
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProcessProvider {
+    command: String,
+}
+
+impl ProcessProvider {
+    #[must_use]
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+        }
+    }
+
+    async fn run(&self) -> Result<Credentials> {
+        let output = shell(&self.command)
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| Error::Other(format!("could not run credential_process: {e}")))?;
+
+        if !output.status.success() {
+            return Err(Error::Other(format!(
+                "credential_process exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        JsonCredentials::parse(&String::from_utf8_lossy(&output.stdout))
+    }
+}
+
+impl ProvideCredentials for ProcessProvider {
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials>> {
+        self.run().boxed()
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(target_os = "windows")]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+#[cfg(test)]
+#[cfg(not(target_os = "windows"))]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_process_provider() {
+        let provider = ProcessProvider::new(
+            r#"printf '{"Version": 1, "AccessKeyId": "ASIAPROC", "SecretAccessKey": "secret", "SessionToken": "token", "Expiration": "2030-01-01T00:00:00Z"}'"#,
+        );
+        let credentials = provider.credentials().await.unwrap();
+        assert_eq!(credentials.aws_access_key_id(), "ASIAPROC");
+        assert_eq!(credentials.aws_secret_access_key(), "secret");
+        assert_eq!(credentials.aws_session_token(), Some("token"));
+        assert!(credentials.expiration().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_process_provider_failure() {
+        let provider = ProcessProvider::new("echo denied >&2; exit 3");
+        let err = provider.credentials().await.unwrap_err();
+        assert!(err.to_string().contains("denied"));
+    }
+
+    #[tokio::test]
+    async fn test_process_provider_invalid_output() {
+        let provider = ProcessProvider::new("echo not-json");
+        assert!(provider.credentials().await.is_err());
+    }
+}
@@ -0,0 +1,437 @@
+//! AWS shared credentials and config files.
+//!
+//! Profiles are read from `~/.aws/credentials` and `~/.aws/config` (or the
+//! files set in `AWS_SHARED_CREDENTIALS_FILE` / `AWS_CONFIG_FILE`), the same
+//! files used by the AWS CLI and SDKs:
+//!
+//! ```ini
+//! # ~/.aws/credentials
+//! [backup]
+//! aws_access_key_id = AKIA...
+//! aws_secret_access_key = ...
+//!
+//! # ~/.aws/config
+//! [profile backup]
+//! region = eu-west-1
+//! endpoint_url = https://s3.example.com
+//! ```
+//!
+//! <https://docs.aws.amazon.com/cli/latest/userguide/cli-configure-files.html>
+
+use crate::s3::{
+    Credentials,
+    credentials::provider::ProviderConfig,
+    error::{Error, Result},
+};
+use secrecy::SecretString;
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// Profile used when none is given.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Settings of a named profile, merged from both files. Values from the
+/// credentials file take precedence over the config file.
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    name: String,
+    aws_access_key_id: Option<String>,
+    aws_secret_access_key: Option<SecretString>,
+    aws_session_token: Option<SecretString>,
+    region: Option<String>,
+    endpoint_url: Option<String>,
+    provider: ProviderConfig,
+}
+
+impl Profile {
+    /// Load the profile `name` from the shared credentials and config files.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a file can not be read or if the profile is not
+    /// defined in any of them
+    pub fn load(name: &str) -> Result<Self> {
+        Self::from_files(name, &credentials_file(), &config_file())
+    }
+
+    /// Load the profile `name` from the given files, a missing file is
+    /// treated as empty.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a file can not be read or if the profile is not
+    /// defined in any of them
+    pub fn from_files(name: &str, credentials: &Path, config: &Path) -> Result<Self> {
+        // the config file prefixes every section but the default one with
+        // "profile ", the credentials file never does
+        let config_section = if name == DEFAULT_PROFILE {
+            name.to_string()
+        } else {
+            format!("profile {name}")
+        };
+
+        let from_credentials = read_section(credentials, name)?;
+        let from_config = read_section(config, &config_section)?;
+
+        if from_credentials.is_none() && from_config.is_none() {
+            return Err(Error::Other(format!(
+                "AWS profile {name:?} not found in {} or {}",
+                credentials.display(),
+                config.display()
+            )));
+        }
+
+        let mut values = from_config.unwrap_or_default();
+        values.extend(from_credentials.unwrap_or_default());
+
+        let mut get = |key: &str| values.remove(key).filter(|v| !v.is_empty());
+
+        Ok(Self {
+            name: name.to_string(),
+            aws_access_key_id: get("aws_access_key_id"),
+            aws_secret_access_key: get("aws_secret_access_key")
+                .map(|v| SecretString::new(v.into())),
+            aws_session_token: get("aws_session_token").map(|v| SecretString::new(v.into())),
+            region: get("region"),
+            // the S3 specific endpoint wins over the global one
+            endpoint_url: get("s3.endpoint_url").or_else(|| get("endpoint_url")),
+            provider: ProviderConfig {
+                credential_process: get("credential_process"),
+                role_arn: get("role_arn"),
+                web_identity_token_file: get("web_identity_token_file"),
+                role_session_name: get("role_session_name"),
+                sts_endpoint: get("sts.endpoint_url"),
+                credential_source: get("credential_source")
+                    .map(|source| source.parse())
+                    .transpose()?,
+            },
+        })
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    #[must_use]
+    pub fn endpoint_url(&self) -> Option<&str> {
+        self.endpoint_url.as_deref()
+    }
+
+    /// `credential_process`, `role_arn`/`web_identity_token_file` and
+    /// `credential_source` of the profile.
+    #[must_use]
+    pub const fn provider_config(&self) -> &ProviderConfig {
+        &self.provider
+    }
+
+    /// Credentials of the profile, the environment variables still take
+    /// precedence (see [`Credentials::new`]).
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the profile has no keys and the environment does
+    /// not provide them either
+    pub fn credentials(&self) -> Result<Credentials> {
+        match (&self.aws_access_key_id, &self.aws_secret_access_key) {
+            (Some(key), Some(secret)) => Ok(
+                Credentials::new(key, secret).with_session_token(self.aws_session_token.clone())
+            ),
+            _ if env::var_os("AWS_ACCESS_KEY_ID").is_some() => {
+                Ok(Credentials::new("", &SecretString::default()))
+            }
+            _ => Err(Error::Other(format!(
+                "AWS profile {:?} has no aws_access_key_id/aws_secret_access_key",
+                self.name
+            ))),
+        }
+    }
+}
+
+/// `AWS_SHARED_CREDENTIALS_FILE` or `~/.aws/credentials`
+#[must_use]
+pub fn credentials_file() -> PathBuf {
+    env::var_os("AWS_SHARED_CREDENTIALS_FILE")
+        .map_or_else(|| aws_dir().join("credentials"), PathBuf::from)
+}
+
+/// `AWS_CONFIG_FILE` or `~/.aws/config`
+#[must_use]
+pub fn config_file() -> PathBuf {
+    env::var_os("AWS_CONFIG_FILE").map_or_else(|| aws_dir().join("config"), PathBuf::from)
+}
+
+fn aws_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join(".aws")
+}
+
+fn read_section(path: &Path, section: &str) -> Result<Option<BTreeMap<String, String>>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(parse_ini(&content).remove(section)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(Error::Other(format!(
+            "could not read {}: {e}",
+            path.display()
+        ))),
+    }
+}
+
+/// Parse the INI dialect of the AWS files: `[section]` headers, `key = value`
+/// pairs, `#`/`;` comments and indented sub-properties of an empty key, stored
+/// as `parent.key`:
+///
+/// ```ini
+/// [profile minio]
+/// s3 =
+///   endpoint_url = http://localhost:9000
+/// ```
+fn parse_ini(content: &str) -> BTreeMap<String, BTreeMap<String, String>> {
+    let mut sections: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    let mut current: Option<String> = None;
+    let mut parent: Option<String> = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+            continue;
+        }
+
+        if let Some(name) = trimmed
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            // "[profile  name]" and "[profile name]" are the same section
+            let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+            sections.entry(name.clone()).or_default();
+            current = Some(name);
+            parent = None;
+            continue;
+        }
+
+        let (Some(section), Some((key, value))) = (&current, trimmed.split_once('=')) else {
+            continue;
+        };
+
+        let (key, value) = (key.trim().to_ascii_lowercase(), value.trim().to_string());
+        let indented = line.starts_with(char::is_whitespace);
+
+        let key = if let (Some(parent), true) = (&parent, indented) {
+            format!("{parent}.{key}")
+        } else {
+            parent = value.is_empty().then(|| key.clone());
+            key
+        };
+
+        sections
+            .entry(section.clone())
+            .or_default()
+            .insert(key, value);
+    }
+
+    sections
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    const CREDENTIALS: &str = r"
+[default]
+aws_access_key_id = AKIADEFAULT
+aws_secret_access_key = default-secret
+
+# temporary credentials
+[backup]
+aws_access_key_id=ASIABACKUP
+aws_secret_access_key=backup-secret
+aws_session_token=backup-token
+";
+
+    const CONFIG: &str = r"
+[default]
+region = us-east-1
+
+[profile backup]
+region = eu-west-1
+endpoint_url = https://s3.example.com
+aws_access_key_id = ignored
+
+[profile minio]
+aws_access_key_id = minio
+aws_secret_access_key = minio-secret
+endpoint_url = https://global.example.com
+s3 =
+  endpoint_url = http://localhost:9000
+";
+
+    fn files() -> (NamedTempFile, NamedTempFile) {
+        let mut credentials = NamedTempFile::new().unwrap();
+        credentials.write_all(CREDENTIALS.as_bytes()).unwrap();
+        let mut config = NamedTempFile::new().unwrap();
+        config.write_all(CONFIG.as_bytes()).unwrap();
+        (credentials, config)
+    }
+
+    fn no_env(f: impl FnOnce()) {
+        temp_env::with_vars_unset(
+            [
+                "AWS_ACCESS_KEY_ID",
+                "AWS_SECRET_ACCESS_KEY",
+                "AWS_SESSION_TOKEN",
+            ],
+            f,
+        );
+    }
+
+    #[test]
+    fn test_parse_ini() {
+        let sections = parse_ini(CONFIG);
+        assert_eq!(sections.len(), 3);
+        let minio = sections.get("profile minio").unwrap();
+        assert_eq!(
+            minio.get("s3.endpoint_url").map(String::as_str),
+            Some("http://localhost:9000")
+        );
+        assert_eq!(minio.get("s3").map(String::as_str), Some(""));
+    }
+
+    #[test]
+    fn test_default_profile() {
+        let (credentials, config) = files();
+        let profile =
+            Profile::from_files(DEFAULT_PROFILE, credentials.path(), config.path()).unwrap();
+        assert_eq!(profile.name(), "default");
+        assert_eq!(profile.region(), Some("us-east-1"));
+        assert_eq!(profile.endpoint_url(), None);
+
+        no_env(|| {
+            let creds = profile.credentials().unwrap();
+            assert_eq!(creds.aws_access_key_id(), "AKIADEFAULT");
+            assert_eq!(creds.aws_secret_access_key(), "default-secret");
+            assert_eq!(creds.aws_session_token(), None);
+        });
+    }
+
+    #[test]
+    fn test_named_profile() {
+        let (credentials, config) = files();
+        let profile = Profile::from_files("backup", credentials.path(), config.path()).unwrap();
+        assert_eq!(profile.region(), Some("eu-west-1"));
+        assert_eq!(profile.endpoint_url(), Some("https://s3.example.com"));
+
+        no_env(|| {
+            // the credentials file wins over the config file
+            let creds = profile.credentials().unwrap();
+            assert_eq!(creds.aws_access_key_id(), "ASIABACKUP");
+            assert_eq!(creds.aws_session_token(), Some("backup-token"));
+        });
+    }
+
+    #[test]
+    fn test_config_only_profile() {
+        let (credentials, config) = files();
+        let profile = Profile::from_files("minio", credentials.path(), config.path()).unwrap();
+        assert_eq!(profile.endpoint_url(), Some("http://localhost:9000"));
+        assert_eq!(profile.region(), None);
+
+        no_env(|| {
+            let creds = profile.credentials().unwrap();
+            assert_eq!(creds.aws_access_key_id(), "minio");
+        });
+    }
+
+    #[test]
+    fn test_env_wins() {
+        let (credentials, config) = files();
+        let profile = Profile::from_files("backup", credentials.path(), config.path()).unwrap();
+
+        temp_env::with_vars(
+            [
+                ("AWS_ACCESS_KEY_ID", Some("env-access")),
+                ("AWS_SECRET_ACCESS_KEY", Some("env-secret")),
+                ("AWS_SESSION_TOKEN", None),
+            ],
+            || {
+                let creds = profile.credentials().unwrap();
+                assert_eq!(creds.aws_access_key_id(), "env-access");
+                assert_eq!(creds.aws_secret_access_key(), "env-secret");
+                assert_eq!(creds.aws_session_token(), None);
+            },
+        );
+    }
+
+    #[test]
+    fn test_profile_without_keys() {
+        let (credentials, _) = files();
+        let mut config = NamedTempFile::new().unwrap();
+        config
+            .write_all(b"[profile sso]\nregion = us-east-2\n")
+            .unwrap();
+        let profile = Profile::from_files("sso", credentials.path(), config.path()).unwrap();
+
+        no_env(|| assert!(profile.credentials().is_err()));
+    }
+
+    #[test]
+    fn test_provider_profile() {
+        let (credentials, _) = files();
+        let mut config = NamedTempFile::new().unwrap();
+        config
+            .write_all(
+                b"[profile irsa]\nrole_arn = arn:aws:iam::123456789012:role/s3m\nweb_identity_token_file = /var/run/token\nsts =\n  endpoint_url = https://sts.eu-west-1.amazonaws.com\n\n[profile ec2]\ncredential_source = Ec2InstanceMetadata\n",
+            )
+            .unwrap();
+
+        let profile = Profile::from_files("irsa", credentials.path(), config.path()).unwrap();
+        let provider = profile.provider_config();
+        assert_eq!(
+            provider.role_arn.as_deref(),
+            Some("arn:aws:iam::123456789012:role/s3m")
+        );
+        assert_eq!(
+            provider.sts_endpoint.as_deref(),
+            Some("https://sts.eu-west-1.amazonaws.com")
+        );
+        assert!(provider.provider().is_some());
+
+        let profile = Profile::from_files("ec2", credentials.path(), config.path()).unwrap();
+        assert_eq!(
+            profile.provider_config().credential_source,
+            Some(crate::s3::credentials::CredentialSource::Ec2InstanceMetadata)
+        );
+    }
+
+    #[test]
+    fn test_missing_profile() {
+        let (credentials, config) = files();
+        assert!(Profile::from_files("nope", credentials.path(), config.path()).is_err());
+        assert!(
+            Profile::from_files(
+                "default",
+                Path::new("/nonexistent/credentials"),
+                Path::new("/nonexistent/config")
+            )
+            .is_err()
+        );
+    }
+}
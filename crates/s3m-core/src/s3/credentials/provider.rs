@@ -0,0 +1,471 @@
+//! Credential providers.
+//!
+//! A [`ProvideCredentials`] implementation fetches [`Credentials`] from a
+//! source that may hand out temporary credentials: a `credential_process`
+//! command, STS `AssumeRoleWithWebIdentity`, the ECS/EKS container endpoint or
+//! the EC2 instance metadata service. [`CredentialsCache`] keeps the last
+//! credentials and fetches new ones before they expire, so long multipart
+//! uploads keep signing with valid credentials.
+
+use crate::s3::{
+    credentials::{
+        Credentials, container::ContainerProvider, imds::ImdsProvider, process::ProcessProvider,
+        web_identity::WebIdentityProvider,
+    },
+    error::{Error, Result},
+};
+use chrono::{DateTime, Duration, Utc};
+use futures::future::{self, BoxFuture, FutureExt};
+use secrecy::SecretString;
+use serde::Deserialize;
+use std::{
+    env, fmt,
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+use tokio::sync::Mutex;
+
+/// Credentials expiring within this window are refreshed before signing.
+pub const REFRESH_WINDOW_SECONDS: i64 = 300;
+
+/// Source of [`Credentials`].
+pub trait ProvideCredentials: fmt::Debug + Send + Sync {
+    /// Fetch the current credentials.
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials>>;
+}
+
+/// Fixed credentials, they never expire.
+#[derive(Debug, Clone)]
+pub struct StaticProvider {
+    credentials: Credentials,
+}
+
+impl StaticProvider {
+    #[must_use]
+    pub const fn new(credentials: Credentials) -> Self {
+        Self { credentials }
+    }
+}
+
+impl ProvideCredentials for StaticProvider {
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials>> {
+        future::ready(Ok(self.credentials.clone())).boxed()
+    }
+}
+
+/// `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and `AWS_SESSION_TOKEN`, read
+/// on every call.
+#[derive(Debug, Clone, Default)]
+pub struct EnvProvider;
+
+impl ProvideCredentials for EnvProvider {
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials>> {
+        let var = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+
+        let credentials = match (var("AWS_ACCESS_KEY_ID"), var("AWS_SECRET_ACCESS_KEY")) {
+            (Some(key), Some(secret)) => Ok(Credentials::from_parts(
+                &key,
+                SecretString::new(secret.into()),
+                var("AWS_SESSION_TOKEN").map(|token| SecretString::new(token.into())),
+            )),
+            _ => Err(Error::Other(
+                "AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY are not set".to_string(),
+            )),
+        };
+
+        future::ready(credentials).boxed()
+    }
+}
+
+/// The `credential_source` of the AWS config files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum CredentialSource {
+    Environment,
+    EcsContainer,
+    Ec2InstanceMetadata,
+}
+
+impl FromStr for CredentialSource {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "Environment" => Ok(Self::Environment),
+            "EcsContainer" => Ok(Self::EcsContainer),
+            "Ec2InstanceMetadata" => Ok(Self::Ec2InstanceMetadata),
+            _ => Err(Error::Other(format!(
+                "invalid credential_source {s:?}, expected Environment, EcsContainer or Ec2InstanceMetadata"
+            ))),
+        }
+    }
+}
+
+/// Provider settings, named after the keys of the AWS config files so they
+/// read the same in `config.yml` and `~/.aws/config`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ProviderConfig {
+    /// Command printing the credentials as JSON.
+    pub credential_process: Option<String>,
+    /// Role to assume with `web_identity_token_file`.
+    pub role_arn: Option<String>,
+    pub web_identity_token_file: Option<String>,
+    pub role_session_name: Option<String>,
+    /// STS endpoint for `AssumeRoleWithWebIdentity`.
+    pub sts_endpoint: Option<String>,
+    pub credential_source: Option<CredentialSource>,
+}
+
+impl ProviderConfig {
+    /// Settings from the environment: `AWS_ROLE_ARN` with
+    /// `AWS_WEB_IDENTITY_TOKEN_FILE` (EKS IRSA) or the
+    /// `AWS_CONTAINER_CREDENTIALS_*` variables (ECS, EKS Pod Identity).
+    #[must_use]
+    pub fn from_env() -> Self {
+        let var = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+
+        let container = var("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI").is_some()
+            || var("AWS_CONTAINER_CREDENTIALS_FULL_URI").is_some();
+
+        Self {
+            credential_process: None,
+            role_arn: var("AWS_ROLE_ARN"),
+            web_identity_token_file: var("AWS_WEB_IDENTITY_TOKEN_FILE"),
+            role_session_name: var("AWS_ROLE_SESSION_NAME"),
+            sts_endpoint: var("AWS_ENDPOINT_URL_STS"),
+            credential_source: container.then_some(CredentialSource::EcsContainer),
+        }
+    }
+
+    /// The provider for these settings, in order: `credential_process`,
+    /// `role_arn` with `web_identity_token_file`, `credential_source`.
+    #[must_use]
+    pub fn provider(&self) -> Option<Arc<dyn ProvideCredentials>> {
+        if let Some(command) = &self.credential_process {
+            return Some(Arc::new(ProcessProvider::new(command)));
+        }
+
+        if let (Some(role_arn), Some(token_file)) = (&self.role_arn, &self.web_identity_token_file)
+        {
+            let provider = WebIdentityProvider::new(role_arn, token_file)
+                .session_name(self.role_session_name.clone())
+                .sts_endpoint(self.sts_endpoint.clone());
+
+            return Some(Arc::new(provider));
+        }
+
+        self.credential_source
+            .map(|source| -> Arc<dyn ProvideCredentials> {
+                match source {
+                    CredentialSource::Environment => Arc::new(EnvProvider),
+                    CredentialSource::EcsContainer => Arc::new(ContainerProvider::from_env()),
+                    CredentialSource::Ec2InstanceMetadata => Arc::new(ImdsProvider::from_env()),
+                }
+            })
+    }
+}
+
+/// JSON credentials returned by `credential_process`, the container endpoint
+/// and IMDS; the first uses `SessionToken`, the others `Token`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct JsonCredentials {
+    access_key_id: String,
+    secret_access_key: String,
+    #[serde(alias = "Token")]
+    session_token: Option<String>,
+    expiration: Option<String>,
+}
+
+impl JsonCredentials {
+    pub(crate) fn parse(json: &str) -> Result<Credentials> {
+        let parsed: Self = serde_json::from_str(json)
+            .map_err(|e| Error::Other(format!("invalid credentials JSON: {e}")))?;
+
+        let expiration = parsed
+            .expiration
+            .as_deref()
+            .map(parse_expiration)
+            .transpose()?;
+
+        Ok(Credentials::from_parts(
+            &parsed.access_key_id,
+            SecretString::new(parsed.secret_access_key.into()),
+            parsed
+                .session_token
+                .filter(|token| !token.is_empty())
+                .map(|token| SecretString::new(token.into())),
+        )
+        .with_expiration(expiration))
+    }
+}
+
+pub(crate) fn parse_expiration(value: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|date| date.with_timezone(&Utc))
+        .map_err(|e| Error::Other(format!("invalid credentials expiration {value:?}: {e}")))
+}
+
+/// The last credentials of a provider, fetched again when they are about to
+/// expire. Shared by every clone of an [`S3`](crate::s3::S3).
+#[derive(Debug)]
+pub struct CredentialsCache {
+    provider: Arc<dyn ProvideCredentials>,
+    current: RwLock<Option<Credentials>>,
+    // one refresh at a time, concurrent parts wait for it
+    refresh: Mutex<()>,
+}
+
+impl CredentialsCache {
+    #[must_use]
+    pub fn new(provider: Arc<dyn ProvideCredentials>) -> Self {
+        Self {
+            provider,
+            current: RwLock::new(None),
+            refresh: Mutex::new(()),
+        }
+    }
+
+    /// The cached credentials, `None` until the first [`Self::refresh`].
+    #[must_use]
+    pub fn current(&self) -> Option<Credentials> {
+        self.current.read().map_or_else(
+            |poisoned| poisoned.into_inner().clone(),
+            |guard| guard.clone(),
+        )
+    }
+
+    /// Fetch new credentials if there are none yet or if they expire within
+    /// [`REFRESH_WINDOW_SECONDS`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the provider fails
+    pub async fn refresh(&self) -> Result<Credentials> {
+        let window = Duration::seconds(REFRESH_WINDOW_SECONDS);
+
+        if let Some(credentials) = self.current().filter(|c| !c.expires_within(window)) {
+            return Ok(credentials);
+        }
+
+        let _guard = self.refresh.lock().await;
+
+        // another task may have refreshed while waiting for the lock
+        if let Some(credentials) = self.current().filter(|c| !c.expires_within(window)) {
+            return Ok(credentials);
+        }
+
+        let credentials = self.provider.credentials().await?;
+
+        log::info!(
+            "Fetched credentials from {:?}, expiration: {:?}",
+            self.provider,
+            credentials.expiration()
+        );
+
+        match self.current.write() {
+            Ok(mut guard) => *guard = Some(credentials.clone()),
+            Err(poisoned) => *poisoned.into_inner() = Some(credentials.clone()),
+        }
+
+        Ok(credentials)
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug)]
+    struct CountingProvider {
+        calls: AtomicUsize,
+        lifetime: Duration,
+    }
+
+    impl ProvideCredentials for CountingProvider {
+        fn credentials(&self) -> BoxFuture<'_, Result<Credentials>> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst);
+            let credentials = Credentials::from_parts(
+                &format!("key-{n}"),
+                SecretString::new("secret".into()),
+                None,
+            )
+            .with_expiration(Some(Utc::now() + self.lifetime));
+
+            future::ready(Ok(credentials)).boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_static_provider() {
+        let provider = StaticProvider::new(Credentials::from_parts(
+            "key",
+            SecretString::new("secret".into()),
+            None,
+        ));
+        let credentials = provider.credentials().await.unwrap();
+        assert_eq!(credentials.aws_access_key_id(), "key");
+    }
+
+    #[test]
+    fn test_env_provider() {
+        temp_env::with_vars(
+            [
+                ("AWS_ACCESS_KEY_ID", Some("env-key")),
+                ("AWS_SECRET_ACCESS_KEY", Some("env-secret")),
+                ("AWS_SESSION_TOKEN", Some("env-token")),
+            ],
+            || {
+                let credentials = EnvProvider.credentials().now_or_never().unwrap().unwrap();
+                assert_eq!(credentials.aws_access_key_id(), "env-key");
+                assert_eq!(credentials.aws_session_token(), Some("env-token"));
+            },
+        );
+
+        temp_env::with_vars_unset(["AWS_ACCESS_KEY_ID", "AWS_SECRET_ACCESS_KEY"], || {
+            assert!(EnvProvider.credentials().now_or_never().unwrap().is_err());
+        });
+    }
+
+    #[test]
+    fn test_json_credentials() {
+        let credentials = JsonCredentials::parse(
+            r#"{"Version": 1, "AccessKeyId": "ASIA", "SecretAccessKey": "secret", "SessionToken": "token", "Expiration": "2030-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+        assert_eq!(credentials.aws_access_key_id(), "ASIA");
+        assert_eq!(credentials.aws_session_token(), Some("token"));
+        assert_eq!(
+            credentials.expiration().unwrap().to_rfc3339(),
+            "2030-01-01T00:00:00+00:00"
+        );
+
+        // container and IMDS use Token
+        let credentials = JsonCredentials::parse(
+            r#"{"Code": "Success", "AccessKeyId": "ASIA", "SecretAccessKey": "secret", "Token": "token"}"#,
+        )
+        .unwrap();
+        assert_eq!(credentials.aws_session_token(), Some("token"));
+        assert_eq!(credentials.expiration(), None);
+
+        assert!(JsonCredentials::parse(r#"{"AccessKeyId": "ASIA"}"#).is_err());
+    }
+
+    #[test]
+    fn test_provider_config() {
+        assert!(ProviderConfig::default().provider().is_none());
+
+        let config = ProviderConfig {
+            credential_process: Some("echo".to_string()),
+            role_arn: Some("arn:aws:iam::123456789012:role/s3m".to_string()),
+            web_identity_token_file: Some("/token".to_string()),
+            ..Default::default()
+        };
+        assert!(format!("{:?}", config.provider().unwrap()).starts_with("ProcessProvider"));
+
+        let config = ProviderConfig {
+            credential_process: None,
+            ..config
+        };
+        assert!(format!("{:?}", config.provider().unwrap()).starts_with("WebIdentityProvider"));
+
+        // a role without a token file needs signed STS requests, not supported
+        let config = ProviderConfig {
+            role_arn: Some("arn:aws:iam::123456789012:role/s3m".to_string()),
+            ..Default::default()
+        };
+        assert!(config.provider().is_none());
+
+        let config = ProviderConfig {
+            credential_source: Some(CredentialSource::Ec2InstanceMetadata),
+            ..Default::default()
+        };
+        assert!(format!("{:?}", config.provider().unwrap()).starts_with("ImdsProvider"));
+    }
+
+    #[test]
+    fn test_provider_config_from_env() {
+        temp_env::with_vars(
+            [
+                ("AWS_ROLE_ARN", Some("arn:aws:iam::123456789012:role/s3m")),
+                ("AWS_WEB_IDENTITY_TOKEN_FILE", Some("/var/run/token")),
+                ("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI", None),
+                ("AWS_CONTAINER_CREDENTIALS_FULL_URI", None),
+            ],
+            || {
+                let config = ProviderConfig::from_env();
+                assert_eq!(
+                    config.web_identity_token_file.as_deref(),
+                    Some("/var/run/token")
+                );
+                assert_eq!(config.credential_source, None);
+            },
+        );
+
+        temp_env::with_vars(
+            [
+                ("AWS_ROLE_ARN", None),
+                ("AWS_WEB_IDENTITY_TOKEN_FILE", None),
+                (
+                    "AWS_CONTAINER_CREDENTIALS_RELATIVE_URI",
+                    Some("/v2/credentials"),
+                ),
+            ],
+            || {
+                let config = ProviderConfig::from_env();
+                assert_eq!(
+                    config.credential_source,
+                    Some(CredentialSource::EcsContainer)
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn test_credential_source_from_str() {
+        assert_eq!(
+            "EcsContainer".parse::<CredentialSource>().unwrap(),
+            CredentialSource::EcsContainer
+        );
+        assert!("Ec2".parse::<CredentialSource>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cache_reuses_valid_credentials() {
+        let provider = Arc::new(CountingProvider {
+            calls: AtomicUsize::new(0),
+            lifetime: Duration::hours(1),
+        });
+        let cache = CredentialsCache::new(provider.clone());
+        assert!(cache.current().is_none());
+
+        cache.refresh().await.unwrap();
+        let credentials = cache.refresh().await.unwrap();
+
+        assert_eq!(credentials.aws_access_key_id(), "key-0");
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cache_refreshes_expiring_credentials() {
+        let provider = Arc::new(CountingProvider {
+            calls: AtomicUsize::new(0),
+            lifetime: Duration::minutes(1),
+        });
+        let cache = CredentialsCache::new(provider.clone());
+
+        cache.refresh().await.unwrap();
+        let credentials = cache.refresh().await.unwrap();
+
+        assert_eq!(credentials.aws_access_key_id(), "key-1");
+        assert_eq!(cache.current().unwrap().aws_access_key_id(), "key-1");
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 2);
+    }
+}
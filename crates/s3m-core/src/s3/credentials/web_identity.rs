@@ -0,0 +1,229 @@
+//! STS `AssumeRoleWithWebIdentity`, used by EKS IAM roles for service
+//! accounts (IRSA): the OIDC token mounted in the pod is exchanged for
+//! temporary credentials of `role_arn`.
+//!
+//! <https://docs.aws.amazon.com/STS/latest/APIReference/API_AssumeRoleWithWebIdentity.html>
+
+use crate::s3::{
+    actions::response_error,
+    credentials::{Credentials, provider::ProvideCredentials, provider::parse_expiration},
+    error::{Error, Result},
+};
+use futures::future::{BoxFuture, FutureExt};
+use quick_xml::de::from_str;
+use reqwest::{Client, header::CONTENT_TYPE};
+use secrecy::SecretString;
+use serde::Deserialize;
+use std::path::PathBuf;
+
+/// Global STS endpoint, used when no other endpoint is configured.
+pub const DEFAULT_STS_ENDPOINT: &str = "https://sts.amazonaws.com";
+
+const DEFAULT_SESSION_NAME: &str = "s3m";
+
+#[derive(Debug, Clone)]
+pub struct WebIdentityProvider {
+    role_arn: String,
+    token_file: PathBuf,
+    session_name: String,
+    sts_endpoint: String,
+    client: Client,
+}
+
+impl WebIdentityProvider {
+    #[must_use]
+    pub fn new(role_arn: &str, token_file: &str) -> Self {
+        Self {
+            role_arn: role_arn.to_string(),
+            token_file: PathBuf::from(token_file),
+            session_name: DEFAULT_SESSION_NAME.to_string(),
+            sts_endpoint: DEFAULT_STS_ENDPOINT.to_string(),
+            client: Client::new(),
+        }
+    }
+
+    /// `RoleSessionName` of the assumed role, defaults to `s3m`.
+    #[must_use]
+    pub fn session_name(mut self, session_name: Option<String>) -> Self {
+        if let Some(session_name) = session_name {
+            self.session_name = session_name;
+        }
+        self
+    }
+
+    /// STS endpoint, for regional endpoints or STS compatible services.
+    #[must_use]
+    pub fn sts_endpoint(mut self, sts_endpoint: Option<String>) -> Self {
+        if let Some(sts_endpoint) = sts_endpoint {
+            self.sts_endpoint = sts_endpoint;
+        }
+        self
+    }
+
+    async fn assume_role(&self) -> Result<Credentials> {
+        // the token is rotated by the kubelet, read it on every call
+        let token = tokio::fs::read_to_string(&self.token_file)
+            .await
+            .map_err(|e| {
+                Error::Other(format!(
+                    "could not read web identity token {}: {e}",
+                    self.token_file.display()
+                ))
+            })?;
+
+        let body = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("Action", "AssumeRoleWithWebIdentity")
+            .append_pair("Version", "2011-06-15")
+            .append_pair("RoleArn", &self.role_arn)
+            .append_pair("RoleSessionName", &self.session_name)
+            .append_pair("WebIdentityToken", token.trim())
+            .finish();
+
+        // the request is authenticated by the token, it is not signed
+        let response = self
+            .client
+            .post(&self.sts_endpoint)
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(body)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(response_error(response).await);
+        }
+
+        let body = response.text().await?;
+
+        let rs: AssumeRoleWithWebIdentityResponse = from_str(&body)?;
+        let credentials = rs.result.credentials;
+
+        Ok(Credentials::from_parts(
+            &credentials.access_key_id,
+            SecretString::new(credentials.secret_access_key.into()),
+            Some(SecretString::new(credentials.session_token.into())),
+        )
+        .with_expiration(Some(parse_expiration(&credentials.expiration)?)))
+    }
+}
+
+impl ProvideCredentials for WebIdentityProvider {
+    fn credentials(&self) -> BoxFuture<'_, Result<Credentials>> {
+        self.assume_role().boxed()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AssumeRoleWithWebIdentityResponse {
+    #[serde(rename = "AssumeRoleWithWebIdentityResult")]
+    result: AssumeRoleWithWebIdentityResult,
+}
+
+#[derive(Debug, Deserialize)]
+struct AssumeRoleWithWebIdentityResult {
+    #[serde(rename = "Credentials")]
+    credentials: StsCredentials,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct StsCredentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: String,
+    expiration: String,
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    const RESPONSE: &str = r#"<AssumeRoleWithWebIdentityResponse xmlns="https://sts.amazonaws.com/doc/2011-06-15/">
+  <AssumeRoleWithWebIdentityResult>
+    <SubjectFromWebIdentityToken>system:serviceaccount:default:s3m</SubjectFromWebIdentityToken>
+    <Credentials>
+      <AccessKeyId>ASIAWEBIDENTITY</AccessKeyId>
+      <SecretAccessKey>web-secret</SecretAccessKey>
+      <SessionToken>web-token</SessionToken>
+      <Expiration>2030-01-01T00:00:00Z</Expiration>
+    </Credentials>
+  </AssumeRoleWithWebIdentityResult>
+</AssumeRoleWithWebIdentityResponse>"#;
+
+    fn token_file() -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"oidc-token\n").unwrap();
+        file
+    }
+
+    #[tokio::test]
+    async fn test_assume_role_with_web_identity() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("Action".into(), "AssumeRoleWithWebIdentity".into()),
+                Matcher::UrlEncoded(
+                    "RoleArn".into(),
+                    "arn:aws:iam::123456789012:role/s3m".into(),
+                ),
+                Matcher::UrlEncoded("RoleSessionName".into(), "backup".into()),
+                Matcher::UrlEncoded("WebIdentityToken".into(), "oidc-token".into()),
+            ]))
+            .with_status(200)
+            .with_body(RESPONSE)
+            .create_async()
+            .await;
+
+        let token = token_file();
+        let provider = WebIdentityProvider::new(
+            "arn:aws:iam::123456789012:role/s3m",
+            token.path().to_str().unwrap(),
+        )
+        .session_name(Some("backup".to_string()))
+        .sts_endpoint(Some(server.url()));
+
+        let credentials = provider.credentials().await.unwrap();
+        assert_eq!(credentials.aws_access_key_id(), "ASIAWEBIDENTITY");
+        assert_eq!(credentials.aws_secret_access_key(), "web-secret");
+        assert_eq!(credentials.aws_session_token(), Some("web-token"));
+        assert_eq!(
+            credentials.expiration().unwrap().to_rfc3339(),
+            "2030-01-01T00:00:00+00:00"
+        );
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_assume_role_with_web_identity_error() {
+        let mut server = Server::new_async().await;
+        let _mock = server
+            .mock("POST", "/")
+            .with_status(403)
+            .with_body("<ErrorResponse><Error><Code>AccessDenied</Code></Error></ErrorResponse>")
+            .create_async()
+            .await;
+
+        let token = token_file();
+        let provider = WebIdentityProvider::new("arn", token.path().to_str().unwrap())
+            .sts_endpoint(Some(server.url()));
+
+        let err = provider.credentials().await.unwrap_err();
+        assert_eq!(err.status(), Some(403));
+    }
+
+    #[tokio::test]
+    async fn test_missing_token_file() {
+        let provider = WebIdentityProvider::new("arn", "/nonexistent/token");
+        assert!(provider.credentials().await.is_err());
+    }
+}
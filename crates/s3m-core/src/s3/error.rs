@@ -10,7 +10,7 @@
 //! `?` at the binary boundary, and `anyhow::Error` produced by internal
 //! helpers converts into [`Error::Other`].
 
-use std::fmt;
+use std::{fmt, time::Duration};
 
 /// Structured representation of an error response returned by the S3 service.
 #[derive(Debug, Clone)]
@@ -23,6 +23,8 @@ pub struct ApiError {
     pub message: Option<String>,
     /// Full human-readable detail (status code, request ids, code, message).
     pub details: String,
+    /// Delay asked for by the `Retry-After` header, when present.
+    pub retry_after: Option<Duration>,
 }
 
 impl fmt::Display for ApiError {
@@ -99,6 +101,15 @@ impl Error {
         }
     }
 
+    /// The `Retry-After` delay if this is an API error that carries one.
+    #[must_use]
+    pub const fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::Api(e) => e.retry_after,
+            _ => None,
+        }
+    }
+
     /// `true` if this is a `404 Not Found` / `NoSuchKey`-style API error.
     #[must_use]
     pub fn is_not_found(&self) -> bool {
@@ -141,6 +152,7 @@ mod tests {
             code: Some("NoSuchKey".to_string()),
             message: Some("The specified key does not exist.".to_string()),
             details: "HTTP Status Code: 404 Not Found\nCode: NoSuchKey\n".to_string(),
+            retry_after: None,
         });
 
         assert_eq!(err.status(), Some(404));
@@ -154,6 +166,7 @@ mod tests {
         let err = Error::Other("boom".to_string());
         assert_eq!(err.code(), None);
         assert_eq!(err.status(), None);
+        assert_eq!(err.retry_after(), None);
         assert!(!err.is_not_found());
         assert_eq!(err.to_string(), "boom");
     }
@@ -59,6 +59,16 @@ pub const MAX_PARTS_PER_UPLOAD: usize = 10_000;
 /// **Last verified**: 2024-01-01
 pub const MIN_PART_SIZE_BYTES: u64 = 5_242_880; // 5 MB
 
+/// Maximum size of an object copied with a single `CopyObject` (5 GB as of 2024)
+///
+/// AWS limit: 5 GiB (5,368,709,120 bytes)
+///
+/// Larger objects must be copied with a multipart upload using
+/// `UploadPartCopy`.
+///
+/// **Last verified**: 2024-01-01
+pub const MAX_COPY_OBJECT_SIZE_BYTES: u64 = 5_368_709_120; // 5 GB
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -98,6 +108,7 @@ mod tests {
         assert_eq!(MAX_OBJECT_SIZE_BYTES, 5_497_558_138_880, "5 TB");
         assert_eq!(MAX_PART_SIZE_BYTES, 5_368_709_120, "5 GB");
         assert_eq!(MIN_PART_SIZE_BYTES, 5_242_880, "5 MB");
+        assert_eq!(MAX_COPY_OBJECT_SIZE_BYTES, 5_368_709_120, "5 GB");
         assert_eq!(MAX_PARTS_PER_UPLOAD, 10_000, "10,000 parts");
     }
 }
@@ -1,4 +1,7 @@
-use crate::s3::object_lock::ObjectLock;
+use crate::{
+    s3::{bandwidth::Bandwidth, object_lock::ObjectLock, request::RetryPolicy, tagging::Tagging},
+    stream::compression::{Codec, Compression},
+};
 use secrecy::SecretString;
 use std::convert::TryFrom;
 
@@ -11,13 +14,19 @@ use std::convert::TryFrom;
 /// any command-line types.
 #[derive(Debug, Clone, Default)]
 pub struct RequestOptions {
-    pub throttle: Option<usize>,
+    /// Bandwidth limit shared by every transfer using these options (and
+    /// their clones).
+    pub throttle: Option<Bandwidth>,
     pub retries: u32,
     pub compress: bool,
+    /// Codec and level used when `compress` is set.
+    pub compression: Compression,
     pub encrypt: bool,
     pub enc_key: Option<SecretString>,
     /// Object Lock (WORM) settings applied to uploads, when set.
     pub object_lock: Option<ObjectLock>,
+    /// Tags applied to uploads (`x-amz-tagging`), when set.
+    pub tagging: Option<Tagging>,
 }
 
 impl RequestOptions {
@@ -27,15 +36,29 @@ impl RequestOptions {
             throttle: None,
             retries: 3,
             compress: false,
+            compression: Compression::new(Codec::Zstd),
             encrypt: false,
             enc_key: None,
             object_lock: None,
+            tagging: None,
         }
     }
 
+    /// Limit the total bandwidth to `kilobytes` per second, 0 for no limit.
+    pub fn set_throttle(&mut self, kilobytes: usize) {
+        self.throttle = (kilobytes > 0).then(|| Bandwidth::new(kilobytes));
+    }
+
     pub fn set_retries(&mut self, retries: usize) {
         self.retries = u32::try_from(retries).unwrap_or(3);
     }
+
+    /// Policy sending a request up to `retries` times, for
+    /// [`S3::with_retry_policy`](crate::s3::S3::with_retry_policy)
+    #[must_use]
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::new(self.retries)
+    }
 }
 
 #[cfg(test)]
@@ -52,19 +75,30 @@ mod tests {
     #[test]
     fn test_request_options() {
         let mut options = RequestOptions::new();
-        assert_eq!(options.throttle, None);
+        assert!(options.throttle.is_none());
         assert_eq!(options.retries, 3);
         assert!(!options.compress);
         assert!(!options.encrypt);
+        assert!(options.tagging.is_none());
+
+        options.set_throttle(10);
+        let throttle = options.throttle.clone().unwrap();
+        assert_eq!(throttle.limit(), Some(10));
+
+        // clones share the same limit
+        options.clone().throttle.unwrap().set_limit(20);
+        assert_eq!(throttle.limit(), Some(20));
 
-        options.throttle = Some(10);
-        assert_eq!(options.throttle, Some(10));
+        options.set_throttle(0);
+        assert!(options.throttle.is_none());
 
         options.set_retries(5);
         assert_eq!(options.retries, 5);
+        assert_eq!(options.retry_policy().attempts(), 5);
 
         options.compress = true;
         assert!(options.compress);
+        assert_eq!(options.compression.codec, Codec::Zstd);
 
         options.encrypt = true;
         assert!(options.encrypt);
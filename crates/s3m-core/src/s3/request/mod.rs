@@ -1,20 +1,23 @@
 //!  S3 signature v4
 //! <https://docs.aws.amazon.com/AmazonS3/latest/API/sig-v4-header-based-auth.html>
 
-use crate::s3::error::Result;
+use crate::s3::{
+    bandwidth::Bandwidth,
+    error::{Error, Result},
+};
 use bytes::Bytes;
 use futures::stream::TryStreamExt;
+use rand::{RngExt, rng};
 use reqwest::{
     Body, Client, Response,
     header::{HeaderMap, HeaderName, HeaderValue},
 };
-use std::{collections::BTreeMap, path::Path, sync::Arc};
-use tokio::time::Duration;
+use std::{collections::BTreeMap, io::ErrorKind, path::Path, sync::Arc};
+use tokio::time::{Duration, sleep};
 use tokio::{
     fs::File,
     io::{AsyncReadExt, AsyncSeekExt, SeekFrom},
 };
-use tokio_stream::StreamExt;
 use tokio_util::codec::{BytesCodec, FramedRead};
 use url::Url;
 
@@ -24,6 +27,174 @@ static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_P
 
 pub type ProgressCallback = Arc<dyn Fn(usize) + Send + Sync + 'static>;
 
+/// Upper bound of the delay requested by a `Retry-After` header.
+pub const MAX_RETRY_AFTER: Duration = Duration::from_mins(5);
+
+/// S3 error codes worth sending the request again for.
+const RETRYABLE_CODES: [&str; 6] = [
+    "SlowDown",
+    "InternalError",
+    "RequestTimeout",
+    "ServiceUnavailable",
+    "Throttling",
+    "ThrottlingException",
+];
+
+/// S3 error codes asking the client to send fewer requests.
+const THROTTLING_CODES: [&str; 5] = [
+    "SlowDown",
+    "RequestTimeout",
+    "ServiceUnavailable",
+    "Throttling",
+    "ThrottlingException",
+];
+
+/// How often and how long to wait before a failed request is sent again,
+/// shared by every action.
+///
+/// The delay grows exponentially from `base_delay` up to `max_delay`, with
+/// jitter so parallel requests do not retry in lockstep, and a `Retry-After`
+/// from the server is honored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl RetryPolicy {
+    /// Send a request up to `attempts` times (at least once).
+    #[must_use]
+    pub fn new(attempts: u32) -> Self {
+        Self {
+            attempts: attempts.max(1),
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+
+    /// Send a request only once.
+    #[must_use]
+    pub fn none() -> Self {
+        Self::new(1)
+    }
+
+    #[must_use]
+    pub const fn with_delays(mut self, base_delay: Duration, max_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self.max_delay = max_delay;
+        self
+    }
+
+    #[must_use]
+    pub const fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// The delay after the failed `attempt` (starting at 1): half of the
+    /// exponential backoff plus a random part of the other half, or
+    /// `retry_after` if the server asked for longer.
+    #[must_use]
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(2_u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+
+        let half = backoff / 2;
+        let jitter = u64::try_from(half.as_millis()).unwrap_or(u64::MAX);
+        let delay = half + Duration::from_millis(rng().random_range(0..=jitter));
+
+        retry_after.map_or(delay, |retry_after| {
+            delay.max(retry_after.min(MAX_RETRY_AFTER))
+        })
+    }
+
+    /// Wait before the next attempt if `error` of the failed `attempt` is
+    /// retryable and attempts are left. Returns `false` when the error should
+    /// be returned instead.
+    pub async fn backoff(&self, attempt: u32, error: &Error) -> bool {
+        if attempt >= self.attempts || !is_retryable(error) {
+            return false;
+        }
+
+        let delay = self.delay(attempt, error.retry_after());
+
+        log::warn!(
+            "attempt {attempt}/{} failed, retrying in {:.1} seconds: {}",
+            self.attempts,
+            delay.as_secs_f64(),
+            error
+                .code()
+                .map_or_else(|| error.to_string(), str::to_string)
+        );
+
+        sleep(delay).await;
+
+        true
+    }
+}
+
+/// Whether sending the request again may succeed: throttling (`SlowDown`,
+/// `429`), server errors (`5xx`, `InternalError`), `RequestTimeout`, and
+/// timeouts or reset connections.
+#[must_use]
+pub fn is_retryable(error: &Error) -> bool {
+    match error {
+        Error::Api(e) => {
+            e.code
+                .as_deref()
+                .is_some_and(|code| RETRYABLE_CODES.contains(&code))
+                || e.status == 429
+                || e.status >= 500
+        }
+        Error::Http(e) => e.is_timeout() || e.is_connect() || e.is_body(),
+        Error::Io(e) => matches!(
+            e.kind(),
+            ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted
+                | ErrorKind::BrokenPipe
+                | ErrorKind::TimedOut
+                | ErrorKind::UnexpectedEof
+        ),
+        _ => false,
+    }
+}
+
+/// Whether the server asks to slow down (`SlowDown`, throttling, `503`, `429`)
+/// or a request timed out, the signal to lower the number of parallel
+/// requests.
+#[must_use]
+pub fn is_throttling(error: &Error) -> bool {
+    match error {
+        Error::Api(e) => {
+            e.code
+                .as_deref()
+                .is_some_and(|code| THROTTLING_CODES.contains(&code))
+                || e.status == 429
+                || e.status == 503
+        }
+        Error::Http(e) => e.is_timeout(),
+        Error::Io(e) => e.kind() == ErrorKind::TimedOut,
+        _ => false,
+    }
+}
+
+/// Statuses whose error is classified by [`is_retryable`], other error
+/// responses are left to the action.
+#[must_use]
+pub fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status.is_server_error()
+        || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        || status == reqwest::StatusCode::BAD_REQUEST
+}
+
 pub struct MultipartRequest<'a> {
     pub client: &'a Client,
     pub url: Url,
@@ -32,24 +203,7 @@ pub struct MultipartRequest<'a> {
     pub file: &'a Path,
     pub seek: u64,
     pub chunk: u64,
-    pub throttle: Option<usize>,
-}
-
-/// Calculate the duration per chunk to achieve the desired bandwidth
-///
-/// # Parameters
-/// - `bandwidth_kb_per_sec`: Throttle limit in KB/s
-/// - `chunk_size`: Size of the data chunk in bytes
-///
-/// # Returns
-/// Duration to wait after processing each chunk
-fn calculate_duration_per_chunk(bandwidth_kb_per_sec: usize, chunk_size: usize) -> Duration {
-    let bandwidth_bytes_per_sec = bandwidth_kb_per_sec * 1024;
-    // Calculate duration in nanoseconds using integer arithmetic
-    // duration = (chunk_size * 1_000_000_000) / bandwidth_bytes_per_sec
-    let nanos = (u128::from(chunk_size as u64) * 1_000_000_000)
-        / u128::from(bandwidth_bytes_per_sec as u64);
-    Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+    pub throttle: Option<&'a Bandwidth>,
 }
 
 /// # Errors
@@ -62,7 +216,7 @@ pub async fn request(
     headers: &BTreeMap<String, String>,
     file: Option<&Path>,
     progress: Option<ProgressCallback>,
-    throttle: Option<usize>,
+    throttle: Option<&Bandwidth>,
 ) -> Result<Response> {
     let headers = headers
         .iter()
@@ -86,34 +240,17 @@ pub async fn request(
                 })
         };
 
-        if let Some(bandwidth_kb) = throttle {
-            let duration_per_chunk =
-                calculate_duration_per_chunk(bandwidth_kb, DEFAULT_FRAMED_CHUNK_SIZE_BYTES);
-
-            log::info!(
-                "Throttling enabled: {} KB/s (duration per chunk: {:.3}s)",
-                bandwidth_kb,
-                duration_per_chunk.as_secs_f64()
-            );
-
-            let rate_limited_stream = stream.throttle(duration_per_chunk);
-
-            let body = Body::wrap_stream(rate_limited_stream);
-
-            client
-                .request(method, url)
-                .header(reqwest::header::USER_AGENT, APP_USER_AGENT)
-                .headers(headers)
-                .body(body)
+        let body = if let Some(bandwidth) = throttle {
+            Body::wrap_stream(bandwidth.throttle(stream))
         } else {
-            let body = Body::wrap_stream(stream);
+            Body::wrap_stream(stream)
+        };
 
-            client
-                .request(method, url)
-                .header(reqwest::header::USER_AGENT, APP_USER_AGENT)
-                .headers(headers)
-                .body(body)
-        }
+        client
+            .request(method, url)
+            .header(reqwest::header::USER_AGENT, APP_USER_AGENT)
+            .headers(headers)
+            .body(body)
     } else {
         client
             .request(method, url)
@@ -124,6 +261,57 @@ pub async fn request(
     Ok(request.send().await?)
 }
 
+/// Send `body` from memory, in chunks so the progress and the throttle work
+/// like for a file.
+///
+/// # Errors
+///
+/// Will return `Err` if can not make the request
+pub async fn upload_bytes(
+    client: &Client,
+    url: Url,
+    method: reqwest::Method,
+    headers: &BTreeMap<String, String>,
+    body: Bytes,
+    progress: Option<ProgressCallback>,
+    throttle: Option<&Bandwidth>,
+) -> Result<Response> {
+    let headers = headers
+        .iter()
+        .map(|(k, v)| Ok((k.parse::<HeaderName>()?, v.parse::<HeaderValue>()?)))
+        .collect::<Result<HeaderMap>>()?;
+
+    log::debug!("HTTP method: {method}, Headers: {headers:#?}");
+
+    let chunks = (0..body.len())
+        .step_by(DEFAULT_FRAMED_CHUNK_SIZE_BYTES)
+        .map(move |start| {
+            let end = body.len().min(start + DEFAULT_FRAMED_CHUNK_SIZE_BYTES);
+            Ok::<_, std::io::Error>(body.slice(start..end))
+        });
+
+    let stream = futures::stream::iter(chunks).inspect_ok(move |chunk| {
+        if let Some(callback) = &progress {
+            log::trace!("Sending {} bytes", chunk.len());
+            callback(chunk.len());
+        }
+    });
+
+    let body = if let Some(bandwidth) = throttle {
+        Body::wrap_stream(bandwidth.throttle(stream))
+    } else {
+        Body::wrap_stream(stream)
+    };
+
+    let request = client
+        .request(method, url)
+        .header(reqwest::header::USER_AGENT, APP_USER_AGENT)
+        .headers(headers)
+        .body(body);
+
+    Ok(request.send().await?)
+}
+
 /// # Errors
 ///
 /// Will return `Err` if can not make the request
@@ -148,37 +336,19 @@ pub async fn multipart_upload(request: MultipartRequest<'_>) -> Result<Response>
     let stream =
         FramedRead::with_capacity(file, BytesCodec::new(), DEFAULT_FRAMED_CHUNK_SIZE_BYTES);
 
-    let request = if let Some(bandwidth_kb) = request.throttle {
-        let duration_per_chunk =
-            calculate_duration_per_chunk(bandwidth_kb, DEFAULT_FRAMED_CHUNK_SIZE_BYTES);
-
-        log::info!(
-            "Throttling enabled: {} KB/s (duration per chunk: {:.3}s)",
-            bandwidth_kb,
-            duration_per_chunk.as_secs_f64()
-        );
-
-        let rate_limited_stream = stream.throttle(duration_per_chunk);
-
-        let body = Body::wrap_stream(rate_limited_stream);
-
-        request
-            .client
-            .request(request.method, request.url)
-            .header(reqwest::header::USER_AGENT, APP_USER_AGENT)
-            .headers(headers)
-            .body(body)
+    let body = if let Some(bandwidth) = request.throttle {
+        Body::wrap_stream(bandwidth.throttle(stream))
     } else {
-        let body = Body::wrap_stream(stream);
-
-        request
-            .client
-            .request(request.method, request.url)
-            .header(reqwest::header::USER_AGENT, APP_USER_AGENT)
-            .headers(headers)
-            .body(body)
+        Body::wrap_stream(stream)
     };
 
+    let request = request
+        .client
+        .request(request.method, request.url)
+        .header(reqwest::header::USER_AGENT, APP_USER_AGENT)
+        .headers(headers)
+        .body(body);
+
     Ok(request.send().await?)
 }
 
@@ -225,14 +395,147 @@ mod tests {
     use mockito::Server;
     use reqwest::StatusCode;
 
+    fn api_error(status: u16, code: Option<&str>) -> Error {
+        Error::Api(crate::s3::ApiError {
+            status,
+            code: code.map(str::to_string),
+            message: None,
+            details: String::new(),
+            retry_after: None,
+        })
+    }
+
     #[test]
-    fn test_calculate_duration_per_chunk() {
-        let bandwidth_kb_per_sec = 1024;
-        let chunk_size = 1024 * 128;
+    fn test_retry_policy_delay() {
+        let policy = RetryPolicy::new(5);
+        assert_eq!(policy.attempts(), 5);
+        assert_eq!(RetryPolicy::new(0).attempts(), 1);
+        assert_eq!(RetryPolicy::default(), RetryPolicy::none());
+
+        for (attempt, backoff) in [(1, 1), (2, 2), (3, 4), (4, 8), (6, 30), (40, 30)] {
+            let delay = policy.delay(attempt, None);
+            let backoff = Duration::from_secs(backoff);
+            assert!(
+                delay >= backoff / 2 && delay <= backoff,
+                "{attempt}: {delay:?}"
+            );
+        }
+
+        // Retry-After wins when it is longer, up to MAX_RETRY_AFTER
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_secs(10))),
+            Duration::from_secs(10)
+        );
+        assert!(policy.delay(5, Some(Duration::from_millis(1))) >= Duration::from_secs(8));
+        assert_eq!(
+            policy.delay(1, Some(Duration::from_hours(1))),
+            MAX_RETRY_AFTER
+        );
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(is_retryable(&api_error(503, Some("SlowDown"))));
+        assert!(is_retryable(&api_error(500, Some("InternalError"))));
+        assert!(is_retryable(&api_error(400, Some("RequestTimeout"))));
+        assert!(is_retryable(&api_error(502, None)));
+        assert!(is_retryable(&api_error(429, None)));
+        assert!(!is_retryable(&api_error(400, Some("InvalidArgument"))));
+        assert!(!is_retryable(&api_error(403, Some("AccessDenied"))));
+        assert!(!is_retryable(&api_error(404, Some("NoSuchKey"))));
+
+        assert!(is_retryable(&Error::Io(std::io::Error::from(
+            ErrorKind::ConnectionReset
+        ))));
+        assert!(!is_retryable(&Error::Io(std::io::Error::from(
+            ErrorKind::NotFound
+        ))));
+        assert!(!is_retryable(&Error::Other("boom".to_string())));
+
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn test_is_throttling() {
+        assert!(is_throttling(&api_error(503, Some("SlowDown"))));
+        assert!(is_throttling(&api_error(400, Some("RequestTimeout"))));
+        assert!(is_throttling(&api_error(503, None)));
+        assert!(is_throttling(&api_error(429, None)));
+        assert!(!is_throttling(&api_error(500, Some("InternalError"))));
+        assert!(!is_throttling(&api_error(403, Some("AccessDenied"))));
+
+        assert!(is_throttling(&Error::Io(std::io::Error::from(
+            ErrorKind::TimedOut
+        ))));
+        assert!(!is_throttling(&Error::Io(std::io::Error::from(
+            ErrorKind::ConnectionReset
+        ))));
+    }
+
+    #[tokio::test]
+    async fn test_retry_policy_backoff() {
+        let policy =
+            RetryPolicy::new(2).with_delays(Duration::from_millis(1), Duration::from_millis(1));
+        let slow_down = api_error(503, Some("SlowDown"));
+
+        assert!(policy.backoff(1, &slow_down).await);
+        // no attempts left
+        assert!(!policy.backoff(2, &slow_down).await);
+        // not retryable
+        assert!(!policy.backoff(1, &api_error(404, None)).await);
+    }
+
+    #[tokio::test]
+    async fn test_is_retryable_connection_refused() {
+        // nothing listens on the port of a dropped listener
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let err: Error = Client::new().get(url).send().await.unwrap_err().into();
+        assert!(is_retryable(&err));
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload_throttled() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("PUT", "/part")
+            .with_status(200)
+            .create_async()
+            .await;
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, &[0; 1024 * 512]).unwrap();
+
+        let client = Client::new();
+        let headers = BTreeMap::new();
+        let bandwidth = Bandwidth::new(512);
+
+        // two parts of 128 KiB sharing 512 KB/s
+        let part = |seek| {
+            multipart_upload(MultipartRequest {
+                client: &client,
+                url: format!("{}/part", server.url()).parse::<Url>().unwrap(),
+                method: reqwest::Method::PUT,
+                headers: &headers,
+                file: file.path(),
+                seek,
+                chunk: 1024 * 128,
+                throttle: Some(&bandwidth),
+            })
+        };
 
-        let duration = calculate_duration_per_chunk(bandwidth_kb_per_sec, chunk_size);
+        let start = std::time::Instant::now();
+        let (first, second) = tokio::join!(part(0), part(1024 * 128));
+        assert_eq!(first.unwrap().status(), StatusCode::OK);
+        assert_eq!(second.unwrap().status(), StatusCode::OK);
 
-        assert_eq!(duration.as_secs_f64(), 0.125);
+        // 256 KiB in total, the first chunk is sent right away
+        assert!(start.elapsed() >= Duration::from_millis(240));
+        mock.expect(2).assert_async().await;
     }
 
     #[tokio::test]
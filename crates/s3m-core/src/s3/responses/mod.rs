@@ -1,5 +1,5 @@
-use serde::Deserialize;
 use serde::de::{Deserializer, Error};
+use serde::{Deserialize, Serialize};
 
 /// # Errors
 ///
@@ -187,6 +187,40 @@ pub struct CompleteMultipartUploadResult {
     pub checksum_sha256: Option<String>,
 }
 
+/// Result of a server-side copy (`CopyObject`).
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CopyObjectResult {
+    #[serde(rename = "ETag")]
+    pub e_tag: String,
+    #[serde(rename = "LastModified")]
+    pub last_modified: Option<String>,
+    #[serde(rename = "ChecksumCRC32")]
+    pub checksum_crc32: Option<String>,
+    #[serde(rename = "ChecksumCRC32C")]
+    pub checksum_crc32c: Option<String>,
+    #[serde(rename = "ChecksumSHA1")]
+    pub checksum_sha1: Option<String>,
+    #[serde(rename = "ChecksumSHA256")]
+    pub checksum_sha256: Option<String>,
+}
+
+/// Result of copying a byte range into a multipart upload part (`UploadPartCopy`).
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CopyPartResult {
+    #[serde(rename = "ETag")]
+    pub e_tag: String,
+    #[serde(rename = "LastModified")]
+    pub last_modified: Option<String>,
+    #[serde(rename = "ChecksumCRC32")]
+    pub checksum_crc32: Option<String>,
+    #[serde(rename = "ChecksumCRC32C")]
+    pub checksum_crc32c: Option<String>,
+    #[serde(rename = "ChecksumSHA1")]
+    pub checksum_sha1: Option<String>,
+    #[serde(rename = "ChecksumSHA256")]
+    pub checksum_sha256: Option<String>,
+}
+
 #[derive(Debug, Deserialize, PartialEq, Eq)]
 #[serde(rename = "DeleteResult")]
 pub struct DeleteObjectsResult {
@@ -390,6 +424,181 @@ pub struct ObjectLegalHold {
     pub status: Option<String>,
 }
 
+/// Bucket versioning state (`GetBucketVersioning`).
+///
+/// Both fields are absent for a bucket that never had versioning enabled.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct VersioningConfiguration {
+    #[serde(rename = "Status")]
+    pub status: Option<String>,
+    #[serde(rename = "MfaDelete")]
+    pub mfa_delete: Option<String>,
+}
+
+/// Object tag set (`GetObjectTagging`).
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ObjectTagging {
+    #[serde(rename = "TagSet", default)]
+    pub tag_set: TagSet,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagSet {
+    #[serde(rename = "Tag", default)]
+    pub tags: Vec<Tag>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+    #[serde(rename = "Key")]
+    pub key: String,
+    #[serde(rename = "Value", default)]
+    pub value: String,
+}
+
+/// Bucket lifecycle rules (`GetBucketLifecycleConfiguration`).
+///
+/// The rule types also serialize, so they double as the input of
+/// [`PutBucketLifecycleConfiguration`](crate::s3::actions::PutBucketLifecycleConfiguration).
+/// Field names follow the S3 API, which makes the JSON/YAML shape used by the
+/// AWS CLI (`Rules: [{ID: .., Status: ..}]`) deserialize as well.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct LifecycleConfiguration {
+    #[serde(rename = "Rule", alias = "Rules", alias = "rules", default)]
+    pub rules: Vec<LifecycleRule>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct LifecycleRule {
+    #[serde(rename = "ID", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(rename = "Filter", skip_serializing_if = "Option::is_none")]
+    pub filter: Option<LifecycleFilter>,
+    /// Legacy rule-level prefix, superseded by `Filter`.
+    #[serde(rename = "Prefix", skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// `Enabled` or `Disabled`.
+    #[serde(rename = "Status")]
+    pub status: String,
+    #[serde(rename = "Expiration", skip_serializing_if = "Option::is_none")]
+    pub expiration: Option<LifecycleExpiration>,
+    #[serde(
+        rename = "Transition",
+        alias = "Transitions",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub transitions: Vec<LifecycleTransition>,
+    #[serde(
+        rename = "NoncurrentVersionTransition",
+        alias = "NoncurrentVersionTransitions",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub noncurrent_version_transitions: Vec<NoncurrentVersionTransition>,
+    #[serde(
+        rename = "NoncurrentVersionExpiration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub noncurrent_version_expiration: Option<NoncurrentVersionExpiration>,
+    #[serde(
+        rename = "AbortIncompleteMultipartUpload",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub abort_incomplete_multipart_upload: Option<AbortIncompleteMultipartUpload>,
+}
+
+/// Objects a rule applies to; an empty filter matches the whole bucket.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct LifecycleFilter {
+    #[serde(rename = "Prefix", skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(rename = "Tag", skip_serializing_if = "Option::is_none")]
+    pub tag: Option<Tag>,
+    #[serde(
+        rename = "ObjectSizeGreaterThan",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub object_size_greater_than: Option<u64>,
+    #[serde(rename = "ObjectSizeLessThan", skip_serializing_if = "Option::is_none")]
+    pub object_size_less_than: Option<u64>,
+    #[serde(rename = "And", skip_serializing_if = "Option::is_none")]
+    pub and: Option<LifecycleRuleAndOperator>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct LifecycleRuleAndOperator {
+    #[serde(rename = "Prefix", skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(
+        rename = "Tag",
+        alias = "Tags",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub tags: Vec<Tag>,
+    #[serde(
+        rename = "ObjectSizeGreaterThan",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub object_size_greater_than: Option<u64>,
+    #[serde(rename = "ObjectSizeLessThan", skip_serializing_if = "Option::is_none")]
+    pub object_size_less_than: Option<u64>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct LifecycleExpiration {
+    #[serde(rename = "Date", skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(rename = "Days", skip_serializing_if = "Option::is_none")]
+    pub days: Option<u32>,
+    #[serde(
+        rename = "ExpiredObjectDeleteMarker",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub expired_object_delete_marker: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct LifecycleTransition {
+    #[serde(rename = "Date", skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(rename = "Days", skip_serializing_if = "Option::is_none")]
+    pub days: Option<u32>,
+    #[serde(rename = "StorageClass")]
+    pub storage_class: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct NoncurrentVersionTransition {
+    #[serde(rename = "NoncurrentDays", skip_serializing_if = "Option::is_none")]
+    pub noncurrent_days: Option<u32>,
+    #[serde(
+        rename = "NewerNoncurrentVersions",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub newer_noncurrent_versions: Option<u32>,
+    #[serde(rename = "StorageClass")]
+    pub storage_class: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct NoncurrentVersionExpiration {
+    #[serde(rename = "NoncurrentDays", skip_serializing_if = "Option::is_none")]
+    pub noncurrent_days: Option<u32>,
+    #[serde(
+        rename = "NewerNoncurrentVersions",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub newer_noncurrent_versions: Option<u32>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct AbortIncompleteMultipartUpload {
+    #[serde(rename = "DaysAfterInitiation")]
+    pub days_after_initiation: u32,
+}
+
 #[cfg(test)]
 #[allow(
     clippy::unwrap_used,
@@ -498,4 +707,143 @@ DeleteMarker: []
         assert_eq!(parsed.versions.len(), 0);
         assert_eq!(parsed.delete_markers.len(), 0);
     }
+
+    #[test]
+    fn test_copy_object_result_deserialization() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<CopyObjectResult>
+  <LastModified>2009-10-12T17:50:30.000Z</LastModified>
+  <ETag>"9b2cf535f27731c974343645a3985328"</ETag>
+</CopyObjectResult>"#;
+        let parsed: CopyObjectResult = quick_xml::de::from_str(xml).unwrap();
+        assert_eq!(parsed.e_tag, "\"9b2cf535f27731c974343645a3985328\"");
+        assert_eq!(
+            parsed.last_modified.as_deref(),
+            Some("2009-10-12T17:50:30.000Z")
+        );
+        assert!(parsed.checksum_sha256.is_none());
+    }
+
+    #[test]
+    fn test_copy_part_result_deserialization() {
+        let xml = r#"<CopyPartResult>
+  <LastModified>2011-04-11T20:34:56.000Z</LastModified>
+  <ETag>"9b2cf535f27731c974343645a3985328"</ETag>
+</CopyPartResult>"#;
+        let parsed: CopyPartResult = quick_xml::de::from_str(xml).unwrap();
+        assert_eq!(parsed.e_tag, "\"9b2cf535f27731c974343645a3985328\"");
+    }
+
+    #[test]
+    fn test_object_tagging_deserialization() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Tagging xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <TagSet>
+    <Tag><Key>team</Key><Value>ops</Value></Tag>
+    <Tag><Key>empty</Key><Value></Value></Tag>
+  </TagSet>
+</Tagging>"#;
+        let parsed: ObjectTagging = quick_xml::de::from_str(xml).unwrap();
+        assert_eq!(parsed.tag_set.tags.len(), 2);
+        assert_eq!(parsed.tag_set.tags[0].key, "team");
+        assert_eq!(parsed.tag_set.tags[0].value, "ops");
+        assert_eq!(parsed.tag_set.tags[1].value, "");
+    }
+
+    #[test]
+    fn test_object_tagging_empty_deserialization() {
+        let xml = r"<Tagging><TagSet></TagSet></Tagging>";
+        let parsed: ObjectTagging = quick_xml::de::from_str(xml).unwrap();
+        assert!(parsed.tag_set.tags.is_empty());
+    }
+
+    #[test]
+    fn test_versioning_configuration_deserialization() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VersioningConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <Status>Enabled</Status>
+  <MfaDelete>Disabled</MfaDelete>
+</VersioningConfiguration>"#;
+        let parsed: VersioningConfiguration = quick_xml::de::from_str(xml).unwrap();
+        assert_eq!(parsed.status.as_deref(), Some("Enabled"));
+        assert_eq!(parsed.mfa_delete.as_deref(), Some("Disabled"));
+
+        let parsed: VersioningConfiguration = quick_xml::de::from_str(
+            r#"<VersioningConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/"/>"#,
+        )
+        .unwrap();
+        assert_eq!(parsed, VersioningConfiguration::default());
+    }
+
+    #[test]
+    fn test_lifecycle_configuration_deserialization() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<LifecycleConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <Rule>
+    <ID>logs</ID>
+    <Filter><Prefix>logs/</Prefix></Filter>
+    <Status>Enabled</Status>
+    <Transition><Days>30</Days><StorageClass>GLACIER</StorageClass></Transition>
+    <Expiration><Days>365</Days></Expiration>
+  </Rule>
+  <Rule>
+    <ID>mpu</ID>
+    <Filter></Filter>
+    <Status>Disabled</Status>
+    <NoncurrentVersionExpiration><NoncurrentDays>30</NoncurrentDays></NoncurrentVersionExpiration>
+    <AbortIncompleteMultipartUpload><DaysAfterInitiation>7</DaysAfterInitiation></AbortIncompleteMultipartUpload>
+  </Rule>
+</LifecycleConfiguration>"#;
+        let parsed: LifecycleConfiguration = quick_xml::de::from_str(xml).unwrap();
+        assert_eq!(parsed.rules.len(), 2);
+
+        let logs = &parsed.rules[0];
+        assert_eq!(logs.id.as_deref(), Some("logs"));
+        assert_eq!(
+            logs.filter.as_ref().and_then(|f| f.prefix.as_deref()),
+            Some("logs/")
+        );
+        assert_eq!(logs.transitions[0].storage_class, "GLACIER");
+        assert_eq!(logs.expiration.as_ref().and_then(|e| e.days), Some(365));
+
+        let mpu = &parsed.rules[1];
+        assert_eq!(mpu.status, "Disabled");
+        assert_eq!(
+            mpu.noncurrent_version_expiration
+                .as_ref()
+                .and_then(|e| e.noncurrent_days),
+            Some(30)
+        );
+        assert_eq!(
+            mpu.abort_incomplete_multipart_upload
+                .as_ref()
+                .map(|a| a.days_after_initiation),
+            Some(7)
+        );
+    }
+
+    #[test]
+    fn test_lifecycle_configuration_yaml() {
+        let yaml = r"
+Rules:
+  - ID: tmp
+    Status: Enabled
+    Filter:
+      And:
+        Prefix: tmp/
+        Tags:
+          - Key: scratch
+            Value: 'true'
+    Expiration:
+      Days: 1
+";
+        let parsed: LifecycleConfiguration = serde_yaml::from_str(yaml).unwrap();
+        let and = parsed.rules[0]
+            .filter
+            .as_ref()
+            .and_then(|f| f.and.as_ref())
+            .unwrap();
+        assert_eq!(and.prefix.as_deref(), Some("tmp/"));
+        assert_eq!(and.tags[0].key, "scratch");
+    }
 }
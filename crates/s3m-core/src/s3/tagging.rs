@@ -0,0 +1,170 @@
+//! S3 object tags.
+//!
+//! Tags are key/value pairs attached to an object, used by lifecycle rules,
+//! access policies and retention tooling. They can be set at upload time with
+//! the `x-amz-tagging` header of
+//! [`PutObject`](crate::s3::actions::PutObject) and
+//! [`CreateMultipartUpload`](crate::s3::actions::CreateMultipartUpload), or
+//! managed afterwards with the `*ObjectTagging` actions.
+//!
+//! <https://docs.aws.amazon.com/AmazonS3/latest/userguide/object-tagging.html>
+
+use crate::s3::error::Error;
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+use std::{collections::BTreeMap, str::FromStr};
+
+/// Maximum number of tags per object.
+pub const MAX_TAGS: usize = 10;
+
+/// Maximum length of a tag key, in Unicode characters.
+pub const MAX_TAG_KEY_LENGTH: usize = 128;
+
+/// Maximum length of a tag value, in Unicode characters.
+pub const MAX_TAG_VALUE_LENGTH: usize = 256;
+
+// `x-amz-tagging` is a URL query string: encode everything but the unreserved
+// characters so `=`, `&` and spaces inside keys/values survive.
+const URLENCODE_TAG: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// A validated object tag set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tagging {
+    tags: BTreeMap<String, String>,
+}
+
+impl Tagging {
+    /// # Errors
+    ///
+    /// Will return `Err` if the tags exceed the S3 limits: at most 10 tags,
+    /// non-empty keys up to 128 characters and values up to 256 characters.
+    pub fn new(tags: BTreeMap<String, String>) -> Result<Self, Error> {
+        if tags.len() > MAX_TAGS {
+            return Err(Error::Other(format!(
+                "too many tags: {}, an object can have at most {MAX_TAGS}",
+                tags.len()
+            )));
+        }
+
+        for (key, value) in &tags {
+            if key.is_empty() || key.chars().count() > MAX_TAG_KEY_LENGTH {
+                return Err(Error::Other(format!(
+                    "invalid tag key {key:?}, must be 1-{MAX_TAG_KEY_LENGTH} characters"
+                )));
+            }
+
+            if value.chars().count() > MAX_TAG_VALUE_LENGTH {
+                return Err(Error::Other(format!(
+                    "invalid value for tag {key:?}, must be at most {MAX_TAG_VALUE_LENGTH} characters"
+                )));
+            }
+        }
+
+        Ok(Self { tags })
+    }
+
+    #[must_use]
+    pub const fn tags(&self) -> &BTreeMap<String, String> {
+        &self.tags
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    /// The URL-encoded `x-amz-tagging` header value, `k1=v1&k2=v2`.
+    #[must_use]
+    pub fn header_value(&self) -> String {
+        self.tags
+            .iter()
+            .map(|(k, v)| {
+                format!(
+                    "{}={}",
+                    utf8_percent_encode(k, URLENCODE_TAG),
+                    utf8_percent_encode(v, URLENCODE_TAG)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+}
+
+/// Parse the CLI format `key1=value1;key2=value2`.
+impl FromStr for Tagging {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tags = BTreeMap::new();
+
+        for pair in s.split(';').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').ok_or_else(|| {
+                Error::Other(format!(
+                    "invalid tag {pair:?}, expected key1=value1;key2=value2"
+                ))
+            })?;
+
+            tags.insert(key.to_string(), value.to_string());
+        }
+
+        Self::new(tags)
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str() {
+        let tagging: Tagging = "team=ops;retention=30d".parse().unwrap();
+        assert_eq!(tagging.tags().len(), 2);
+        assert_eq!(
+            tagging.tags().get("retention").map(String::as_str),
+            Some("30d")
+        );
+    }
+
+    #[test]
+    fn test_from_str_invalid() {
+        assert!("team".parse::<Tagging>().is_err());
+        assert!("=value".parse::<Tagging>().is_err());
+    }
+
+    #[test]
+    fn test_empty_value() {
+        let tagging: Tagging = "flag=".parse().unwrap();
+        assert_eq!(tagging.header_value(), "flag=");
+    }
+
+    #[test]
+    fn test_header_value() {
+        let tagging: Tagging = "b=2;a=x y&z".parse().unwrap();
+        assert_eq!(tagging.header_value(), "a=x%20y%26z&b=2");
+    }
+
+    #[test]
+    fn test_limits() {
+        let too_many = (0..=MAX_TAGS)
+            .map(|i| format!("k{i}=v"))
+            .collect::<Vec<_>>()
+            .join(";");
+        assert!(too_many.parse::<Tagging>().is_err());
+
+        let long_key = format!("{}=v", "k".repeat(MAX_TAG_KEY_LENGTH + 1));
+        assert!(long_key.parse::<Tagging>().is_err());
+
+        let long_value = format!("k={}", "v".repeat(MAX_TAG_VALUE_LENGTH + 1));
+        assert!(long_value.parse::<Tagging>().is_err());
+    }
+}
@@ -4,10 +4,9 @@ use base64ct::{Base64, Encoding};
 use ring::{digest, hmac};
 use std::{
     fmt::Write,
-    io::{Error, ErrorKind, Read},
+    io::{Error, Read},
     path::Path,
 };
-use tokio::time::{Duration, sleep};
 
 /// Recommended default number of concurrent upload/download requests.
 ///
@@ -104,63 +103,6 @@ pub fn blake3(file: &Path) -> std::io::Result<String> {
     Ok(hasher.finalize().to_hex().to_string())
 }
 
-/// Throttle downloads by introducing a delay based on bandwidth and chunk size.
-///
-/// # Arguments
-/// - `bandwidth_kb`: Bandwidth in kilobytes per second (must be > 0).
-/// - `chunk_size`: Size of the data chunk in bytes (must be > 0).
-///
-/// # Errors
-/// - Returns `Err` if bandwidth is 0, chunk size is 0, bandwidth value overflows, or duration calculation is invalid.
-///
-/// # Returns
-/// - `Ok(())` if throttling is successful.
-pub async fn throttle_download(bandwidth_kb: usize, chunk_size: usize) -> std::io::Result<()> {
-    if bandwidth_kb == 0 {
-        return Err(Error::new(
-            ErrorKind::InvalidInput,
-            "Bandwidth cannot be 0 KB/s",
-        ));
-    }
-
-    if chunk_size == 0 {
-        return Err(Error::new(
-            ErrorKind::InvalidInput,
-            "Chunk size cannot be 0 bytes",
-        ));
-    }
-
-    // Calculate bandwidth in bytes per second
-    let bandwidth_bytes = bandwidth_kb
-        .checked_mul(1024)
-        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Bandwidth value too large"))?;
-
-    // Calculate duration in nanoseconds using integer arithmetic
-    // duration = (chunk_size * 1_000_000_000) / bandwidth_bytes
-    let nanos = u64::try_from(chunk_size)
-        .ok()
-        .and_then(|cs| cs.checked_mul(1_000_000_000))
-        .and_then(|n| {
-            u64::try_from(bandwidth_bytes)
-                .ok()
-                .and_then(|bb| n.checked_div(bb))
-        })
-        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Duration calculation overflow"))?;
-
-    let duration = Duration::from_nanos(nanos);
-
-    // Introduce the delay for throttling
-    sleep(duration).await;
-
-    // Log the throttling details
-    log::debug!(
-        "Throttling download: Bandwidth = {bandwidth_kb} KB/s, Chunk size = {chunk_size} bytes, Sleep duration = {:.3} seconds",
-        duration.as_secs_f64()
-    );
-
-    Ok(())
-}
-
 #[cfg(test)]
 #[allow(
     clippy::unwrap_used,
@@ -376,54 +318,4 @@ mod tests {
         assert!(usize::from(number) <= MAX_PARTS_PER_UPLOAD);
         assert!((MAX_OBJECT_SIZE_BYTES.div_ceil(part_size)) < 10_000);
     }
-
-    #[tokio::test]
-    async fn test_throttle_download_valid() {
-        let bandwidth_kb = 1; // 1 KB/s
-        let chunk_size = 1024; // 1 KB
-
-        // Test 1: Exact division (1024 bytes / 1024 bytes/sec = 1 sec)
-        let start = std::time::Instant::now();
-        let result = throttle_download(bandwidth_kb, chunk_size).await;
-
-        assert!(result.is_ok());
-        let elapsed = start.elapsed();
-        assert!(elapsed.as_secs_f64() >= 1.0);
-
-        // Test 2: Fractional seconds (512 bytes / 1024 bytes/sec = 0.5 sec)
-        let start = std::time::Instant::now();
-        let result = throttle_download(1, 512).await;
-
-        assert!(result.is_ok());
-        let elapsed = start.elapsed();
-        assert!(elapsed.as_secs_f64() >= 0.5);
-    }
-
-    #[tokio::test]
-    async fn test_throttle_download_zero_bandwidth() {
-        let result = throttle_download(0, 1024).await;
-        assert!(result.is_err());
-        assert_eq!(
-            result.unwrap_err().to_string(),
-            "Bandwidth cannot be 0 KB/s"
-        );
-    }
-
-    #[tokio::test]
-    async fn test_throttle_download_zero_chunk_size() {
-        let result = throttle_download(1, 0).await;
-        assert!(result.is_err());
-        assert_eq!(
-            result.unwrap_err().to_string(),
-            "Chunk size cannot be 0 bytes"
-        );
-    }
-
-    #[tokio::test]
-    async fn test_throttle_download_large_bandwidth() {
-        let bandwidth_kb = usize::MAX / 1024 + 1; // Exceeds usize when multiplied by 1024
-        let result = throttle_download(bandwidth_kb, 1024).await;
-        assert!(result.is_err());
-        assert_eq!(result.unwrap_err().to_string(), "Bandwidth value too large");
-    }
 }
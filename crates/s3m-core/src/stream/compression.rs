@@ -0,0 +1,621 @@
+//! Codecs of compressed uploads.
+//!
+//! Every chunk of the input is compressed on its own, so an object is a
+//! sequence of complete frames (zstd, lz4) or members/streams (gzip, xz) that
+//! the usual command line tools decode as a single file. The codec is also
+//! stored in the [`METADATA_HEADER`] of the object.
+//!
+//! zstd splits chunks larger than [`ZSTD_JOB_SIZE`] into jobs compressed by
+//! several threads.
+//!
+//! A [`Decompressor`] restores an object while it is downloaded: the codec
+//! reads the compressed bytes on a blocking thread and writes the output to
+//! its sink, so memory stays bounded whatever the ratio.
+
+use anyhow::{Context, Result, anyhow};
+use bytes::{Buf, Bytes};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, Read, Write},
+    ops::RangeInclusive,
+    str::FromStr,
+    thread::available_parallelism,
+};
+use tokio::{
+    sync::mpsc::{Receiver, Sender, channel},
+    task::{JoinHandle, spawn_blocking},
+};
+
+/// Object metadata recording the codec of a compressed upload
+pub const METADATA_HEADER: &str = "x-amz-meta-s3m-compression";
+
+/// Bytes compressed by every zstd thread, 512 KiB, the zstd minimum
+pub const ZSTD_JOB_SIZE: u32 = 512 * 1_024;
+
+// chunks of compressed input waiting for the decompressor thread
+const DECOMPRESS_QUEUE: usize = 16;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Codec {
+    #[default]
+    Zstd,
+    Gzip,
+    Lz4,
+    Xz,
+}
+
+impl Codec {
+    /// Extension added to the key of the object
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Zstd => "zst",
+            Self::Gzip => "gz",
+            Self::Lz4 => "lz4",
+            Self::Xz => "xz",
+        }
+    }
+
+    /// Content-Type of the object when it is not encrypted
+    #[must_use]
+    pub const fn content_type(self) -> &'static str {
+        match self {
+            Self::Zstd => "application/zstd",
+            Self::Gzip => "application/gzip",
+            Self::Lz4 => "application/x-lz4",
+            Self::Xz => "application/x-xz",
+        }
+    }
+
+    /// Codec of the extension of `key`, `None` when it has none
+    #[must_use]
+    pub fn from_key(key: &str) -> Option<Self> {
+        [Self::Zstd, Self::Gzip, Self::Lz4, Self::Xz]
+            .into_iter()
+            .find(|codec| {
+                key.strip_suffix(codec.extension())
+                    .is_some_and(|stem| stem.len() > 1 && stem.ends_with('.'))
+            })
+    }
+
+    /// Levels accepted by the codec
+    #[must_use]
+    pub const fn levels(self) -> RangeInclusive<i32> {
+        match self {
+            Self::Zstd => 1..=22,
+            Self::Gzip | Self::Xz => 0..=9,
+            Self::Lz4 => 1..=12,
+        }
+    }
+
+    /// Decompress every frame of `data`, at most `max_output` bytes
+    ///
+    /// # Errors
+    /// Will return `Err` if the data is corrupt or the output exceeds `max_output`
+    pub fn decompress(self, data: &[u8], max_output: u64) -> Result<Vec<u8>> {
+        let mut output = Vec::new();
+        decompress(self, data, &mut output, max_output)?;
+
+        Ok(output)
+    }
+}
+
+impl FromStr for Codec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "zstd" | "zst" => Ok(Self::Zstd),
+            "gzip" | "gz" => Ok(Self::Gzip),
+            "lz4" => Ok(Self::Lz4),
+            "xz" => Ok(Self::Xz),
+            _ => Err(anyhow!(
+                "invalid codec {s:?}, expected zstd, gzip, lz4 or xz"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Zstd => "zstd",
+            Self::Gzip => "gzip",
+            Self::Lz4 => "lz4",
+            Self::Xz => "xz",
+        })
+    }
+}
+
+/// Codec and level of a compressed upload, the default level of the codec
+/// when `level` is `None`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Compression {
+    pub codec: Codec,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<i32>,
+}
+
+impl Compression {
+    #[must_use]
+    pub const fn new(codec: Codec) -> Self {
+        Self { codec, level: None }
+    }
+
+    /// Compress with `level` instead of the default level of the codec
+    ///
+    /// # Errors
+    /// Will return `Err` if the codec does not support the level
+    pub fn with_level(self, level: i32) -> Result<Self> {
+        Self {
+            level: Some(level),
+            ..self
+        }
+        .validate()
+    }
+
+    /// Check the level against the levels of the codec
+    ///
+    /// # Errors
+    /// Will return `Err` if the codec does not support the level
+    pub fn validate(self) -> Result<Self> {
+        let Some(level) = self.level else {
+            return Ok(self);
+        };
+
+        let levels = self.codec.levels();
+        if levels.contains(&level) {
+            Ok(self)
+        } else {
+            Err(anyhow!(
+                "invalid {} level {level}, expected {}-{}",
+                self.codec,
+                levels.start(),
+                levels.end()
+            ))
+        }
+    }
+
+    /// Compress `data` into one frame of the codec
+    ///
+    /// # Errors
+    /// Will return `Err` if the level is invalid or compression fails
+    pub fn compress(self, data: &[u8]) -> Result<Vec<u8>> {
+        self.validate()?;
+
+        match self.codec {
+            Codec::Zstd => zstd_compress(data, self.level.unwrap_or(0)),
+            Codec::Gzip => {
+                let level = u32::try_from(self.level.unwrap_or(6))?;
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::new(level));
+                encoder.write_all(data)?;
+                encoder.finish().context("failed to compress with gzip")
+            }
+            Codec::Lz4 => {
+                let level = u32::try_from(self.level.unwrap_or(0))?;
+                let mut encoder = lz4::EncoderBuilder::new()
+                    .level(level)
+                    .build(Vec::new())
+                    .context("failed to initialize lz4 encoder")?;
+                encoder.write_all(data)?;
+                let (data, result) = encoder.finish();
+                result.context("failed to compress with lz4")?;
+                Ok(data)
+            }
+            Codec::Xz => {
+                let level = u32::try_from(self.level.unwrap_or(6))?;
+                let mut encoder = liblzma::write::XzEncoder::new(Vec::new(), level);
+                encoder.write_all(data)?;
+                encoder.finish().context("failed to compress with xz")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.level {
+            Some(level) => write!(f, "{}:{level}", self.codec),
+            None => write!(f, "{}", self.codec),
+        }
+    }
+}
+
+/// Streaming decompression of an object to `output`
+///
+/// The compressed bytes given to [`write`](Self::write) are decoded on a
+/// blocking thread, concatenated frames included, and the decompressed bytes
+/// are written to the output as they come. Decoding stops with an error when
+/// the output would exceed `max_output` bytes.
+pub struct Decompressor {
+    input: Option<Sender<Bytes>>,
+    task: Option<JoinHandle<Result<u64>>>,
+}
+
+impl Decompressor {
+    #[must_use]
+    pub fn new<W: Write + Send + 'static>(codec: Codec, mut output: W, max_output: u64) -> Self {
+        let (tx, rx) = channel(DECOMPRESS_QUEUE);
+
+        let task = spawn_blocking(move || {
+            let input = BufReader::new(ChannelReader {
+                rx,
+                chunk: Bytes::new(),
+            });
+            let written = decompress(codec, input, &mut output, max_output)?;
+            output.flush()?;
+            Ok(written)
+        });
+
+        Self {
+            input: Some(tx),
+            task: Some(task),
+        }
+    }
+
+    /// Decompress the next bytes of the object
+    ///
+    /// # Errors
+    /// Will return `Err` if decoding failed or the output exceeds the limit
+    pub async fn write(&mut self, data: Bytes) -> Result<()> {
+        let input = self
+            .input
+            .as_ref()
+            .ok_or_else(|| anyhow!("decompressor already finished"))?;
+
+        if input.send(data).await.is_err() {
+            // the thread stopped before the end of the input, report why
+            self.input = None;
+            self.join().await?;
+            return Err(anyhow!("decompression stopped before the end of the input"));
+        }
+
+        Ok(())
+    }
+
+    /// Wait for the end of the output, returns the decompressed bytes
+    ///
+    /// # Errors
+    /// Will return `Err` if the input is truncated or corrupt
+    pub async fn finish(mut self) -> Result<u64> {
+        self.input = None;
+        self.join().await
+    }
+
+    async fn join(&mut self) -> Result<u64> {
+        let task = self
+            .task
+            .take()
+            .ok_or_else(|| anyhow!("decompressor already finished"))?;
+
+        task.await
+            .context("decompression task panicked or was cancelled")?
+    }
+}
+
+// the compressed input of a Decompressor, EOF once the sender is dropped
+struct ChannelReader {
+    rx: Receiver<Bytes>,
+    chunk: Bytes,
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.chunk.is_empty() {
+            match self.rx.blocking_recv() {
+                Some(chunk) => self.chunk = chunk,
+                None => return Ok(0),
+            }
+        }
+
+        let len = buf.len().min(self.chunk.len());
+        if let Some(buf) = buf.get_mut(..len) {
+            self.chunk.copy_to_slice(buf);
+        }
+
+        Ok(len)
+    }
+}
+
+// decode every frame of `input` into `output`, returns the decompressed bytes
+fn decompress<R: BufRead, W: Write>(
+    codec: Codec,
+    mut input: R,
+    output: W,
+    max_output: u64,
+) -> Result<u64> {
+    let mut output = LimitedWriter {
+        output,
+        written: 0,
+        limit: max_output,
+    };
+
+    match codec {
+        Codec::Zstd => {
+            let mut decoder = zstd::stream::read::Decoder::with_buffer(input)
+                .context("failed to initialize zstd decoder")?;
+            io::copy(&mut decoder, &mut output).context("failed to decompress with zstd")?;
+        }
+        Codec::Gzip => {
+            let mut decoder = flate2::bufread::MultiGzDecoder::new(input);
+            io::copy(&mut decoder, &mut output).context("failed to decompress with gzip")?;
+        }
+        Codec::Lz4 => {
+            // the decoder stops at the end of every frame, finish tells
+            // whether the frame was complete
+            while !input.fill_buf()?.is_empty() {
+                let mut decoder =
+                    lz4::Decoder::new(&mut input).context("failed to initialize lz4 decoder")?;
+                io::copy(&mut decoder, &mut output).context("failed to decompress with lz4")?;
+                decoder
+                    .finish()
+                    .1
+                    .context("failed to decompress with lz4")?;
+            }
+        }
+        Codec::Xz => {
+            let mut decoder = liblzma::bufread::XzDecoder::new_multi_decoder(input);
+            io::copy(&mut decoder, &mut output).context("failed to decompress with xz")?;
+        }
+    }
+
+    Ok(output.written)
+}
+
+// fails once more than `limit` bytes are written
+struct LimitedWriter<W> {
+    output: W,
+    written: u64,
+    limit: u64,
+}
+
+impl<W: Write> Write for LimitedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.written.saturating_add(buf.len() as u64);
+        if written > self.limit {
+            return Err(io::Error::other(format!(
+                "decompressed output exceeds the {}-byte limit",
+                self.limit
+            )));
+        }
+
+        self.output.write_all(buf)?;
+        self.written = written;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+// level 0 is the default zstd level, chunks of several jobs use one thread
+// per job
+fn zstd_compress(data: &[u8], level: i32) -> Result<Vec<u8>> {
+    let mut encoder = zstd::stream::write::Encoder::new(Vec::new(), level)
+        .context("failed to initialize zstd encoder")?;
+
+    let workers = zstd_workers(data.len());
+    if workers > 1 {
+        encoder
+            .multithread(workers)
+            .context("failed to start zstd workers")?;
+        encoder
+            .set_parameter(zstd::stream::raw::CParameter::JobSize(ZSTD_JOB_SIZE))
+            .context("failed to set the zstd job size")?;
+    }
+
+    encoder
+        .write_all(data)
+        .context("failed to compress with zstd")?;
+    encoder.finish().context("failed to compress with zstd")
+}
+
+// one worker per job, at most one per CPU
+fn zstd_workers(len: usize) -> u32 {
+    let cpus = available_parallelism().map_or(1, std::num::NonZero::get);
+    let jobs = len.div_ceil(ZSTD_JOB_SIZE as usize);
+
+    u32::try_from(cpus.min(jobs)).unwrap_or(1)
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex, PoisonError};
+
+    // output of a Decompressor that the test can read back
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Shared {
+        fn take(&self) -> Vec<u8> {
+            std::mem::take(&mut *self.0.lock().unwrap_or_else(PoisonError::into_inner))
+        }
+    }
+
+    async fn decompress_all(codec: Codec, data: &[u8], max_output: u64) -> Result<Vec<u8>> {
+        let output = Shared::default();
+        let mut decompressor = Decompressor::new(codec, output.clone(), max_output);
+
+        // small writes split frames across chunks
+        for chunk in data.chunks(1_000) {
+            decompressor.write(Bytes::copy_from_slice(chunk)).await?;
+        }
+        let written = decompressor.finish().await?;
+
+        let out = output.take();
+        assert_eq!(written, out.len() as u64);
+        Ok(out)
+    }
+
+    fn decompress(codec: Codec, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        match codec {
+            Codec::Zstd => {
+                out = zstd::stream::decode_all(data).unwrap();
+            }
+            Codec::Gzip => {
+                flate2::read::MultiGzDecoder::new(data)
+                    .read_to_end(&mut out)
+                    .unwrap();
+            }
+            Codec::Lz4 => {
+                // the decoder stops at the end of every frame
+                let mut rest = data;
+                while !rest.is_empty() {
+                    let mut decoder = lz4::Decoder::new(rest).unwrap();
+                    decoder.read_to_end(&mut out).unwrap();
+                    let (inner, result) = decoder.finish();
+                    result.unwrap();
+                    rest = inner;
+                }
+            }
+            Codec::Xz => {
+                liblzma::read::XzDecoder::new_multi_decoder(data)
+                    .read_to_end(&mut out)
+                    .unwrap();
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_codec_from_str() {
+        assert_eq!("zstd".parse::<Codec>().unwrap(), Codec::Zstd);
+        assert_eq!("gz".parse::<Codec>().unwrap(), Codec::Gzip);
+        assert_eq!("lz4".parse::<Codec>().unwrap(), Codec::Lz4);
+        assert_eq!("xz".parse::<Codec>().unwrap(), Codec::Xz);
+        assert!("brotli".parse::<Codec>().is_err());
+        assert_eq!(Codec::Gzip.to_string(), "gzip");
+        assert_eq!(Codec::Xz.extension(), "xz");
+    }
+
+    #[test]
+    fn test_compression_levels() {
+        let zstd = Compression::new(Codec::Zstd);
+        assert_eq!(zstd.with_level(19).unwrap().level, Some(19));
+        assert!(zstd.with_level(23).is_err());
+        assert!(Compression::new(Codec::Gzip).with_level(10).is_err());
+        assert!(Compression::new(Codec::Lz4).with_level(13).is_err());
+        assert!(Compression::new(Codec::Lz4).validate().is_ok());
+    }
+
+    #[test]
+    fn test_compression_roundtrip_concatenated() {
+        let first = b"Hello, world! ".repeat(1_000);
+        let second = b"s3m ".repeat(5_000);
+
+        for codec in [Codec::Zstd, Codec::Gzip, Codec::Lz4, Codec::Xz] {
+            let compression = Compression::new(codec);
+            let mut data = compression.compress(&first).unwrap();
+            data.extend(compression.compress(&second).unwrap());
+            assert!(data.len() < first.len() + second.len(), "{codec}");
+
+            let mut expected = first.clone();
+            expected.extend_from_slice(&second);
+            assert_eq!(decompress(codec, &data), expected, "{codec}");
+        }
+    }
+
+    #[test]
+    fn test_zstd_multithreaded() {
+        // several jobs, compressed by as many threads as there are CPUs
+        let data: Vec<u8> = (0..3 * ZSTD_JOB_SIZE).map(|i| (i % 251) as u8).collect();
+        assert!(zstd_workers(data.len()) >= 1);
+        assert_eq!(zstd_workers(1), 1);
+
+        let compressed = Compression::new(Codec::Zstd)
+            .with_level(3)
+            .unwrap()
+            .compress(&data)
+            .unwrap();
+        assert_eq!(decompress(Codec::Zstd, &compressed), data);
+    }
+
+    #[tokio::test]
+    async fn test_decompressor_concatenated_frames() {
+        let first = b"Hello, world! ".repeat(10_000);
+        let second = b"s3m ".repeat(50_000);
+        let mut expected = first.clone();
+        expected.extend_from_slice(&second);
+
+        for codec in [Codec::Zstd, Codec::Gzip, Codec::Lz4, Codec::Xz] {
+            let compression = Compression::new(codec);
+            let mut data = compression.compress(&first).unwrap();
+            data.extend(compression.compress(&second).unwrap());
+
+            let out = decompress_all(codec, &data, u64::MAX).await.unwrap();
+            assert_eq!(out, expected, "{codec}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decompressor_limit_and_truncation() {
+        let original = vec![0u8; 1 << 20];
+
+        for codec in [Codec::Zstd, Codec::Gzip, Codec::Lz4, Codec::Xz] {
+            let data = Compression::new(codec).compress(&original).unwrap();
+
+            let err = decompress_all(codec, &data, 1_000).await.unwrap_err();
+            assert!(
+                format!("{err:#}").contains("exceeds the 1000-byte limit"),
+                "{codec}: {err:#}"
+            );
+
+            let truncated = data.get(..data.len() - 4).unwrap();
+            assert!(
+                decompress_all(codec, truncated, u64::MAX).await.is_err(),
+                "{codec}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_codec_from_key() {
+        assert_eq!(Codec::from_key("db.sql.zst"), Some(Codec::Zstd));
+        assert_eq!(Codec::from_key("db.sql.gz"), Some(Codec::Gzip));
+        assert_eq!(Codec::from_key("db.lz4"), Some(Codec::Lz4));
+        assert_eq!(Codec::from_key("backups/db.xz"), Some(Codec::Xz));
+        assert_eq!(Codec::from_key("db.sql"), None);
+        assert_eq!(Codec::from_key("dbxz"), None);
+        assert_eq!(Codec::from_key(".gz"), None);
+    }
+
+    #[test]
+    fn test_compression_serde() {
+        let compression: Compression = serde_yaml_ng::from_str("codec: xz\nlevel: 9").unwrap();
+        assert_eq!(
+            compression,
+            Compression::new(Codec::Xz).with_level(9).unwrap()
+        );
+
+        let compression: Compression = serde_yaml_ng::from_str("codec: gzip").unwrap();
+        assert_eq!(compression, Compression::new(Codec::Gzip));
+    }
+}
@@ -0,0 +1,193 @@
+//! Adaptive number of parallel requests.
+//!
+//! Additive increase, multiplicative decrease (AIMD): start at the requested
+//! concurrency, halve it when the server throttles (`SlowDown`, `503`, `429`)
+//! or a request times out, and add one more request after a full window of
+//! successful ones, probing up to twice the requested concurrency.
+
+use crate::s3::{Error, request::is_throttling};
+use std::sync::{Mutex, PoisonError};
+
+#[derive(Debug)]
+pub struct Concurrency {
+    min: usize,
+    max: usize,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    current: usize,
+    // successes since the last change
+    successes: usize,
+    // incremented on every decrease
+    epoch: u64,
+}
+
+impl Concurrency {
+    /// Start at `requests` parallel requests, probing up to twice as many.
+    #[must_use]
+    pub fn new(requests: usize) -> Self {
+        let requests = requests.max(1);
+        Self::with_limits(requests, 1, requests.saturating_mul(2))
+    }
+
+    /// Start at `requests`, never going below `min` nor above `max`.
+    #[must_use]
+    pub fn with_limits(requests: usize, min: usize, max: usize) -> Self {
+        let min = min.max(1);
+        let max = max.max(min);
+
+        Self {
+            min,
+            max,
+            state: Mutex::new(State {
+                current: requests.clamp(min, max),
+                successes: 0,
+                epoch: 0,
+            }),
+        }
+    }
+
+    /// The number of requests allowed in flight.
+    #[must_use]
+    pub fn current(&self) -> usize {
+        self.state().current
+    }
+
+    /// Taken before sending a request and passed to [`Self::throttled`], so
+    /// the requests that were already in flight when the concurrency was
+    /// lowered do not lower it again.
+    #[must_use]
+    pub fn epoch(&self) -> u64 {
+        self.state().epoch
+    }
+
+    /// A request succeeded, returns the new concurrency if it grew.
+    pub fn success(&self) -> Option<usize> {
+        let mut state = self.state();
+
+        state.successes += 1;
+
+        if state.successes < state.current || state.current >= self.max {
+            return None;
+        }
+
+        state.successes = 0;
+        state.current += 1;
+
+        log::info!("Concurrent requests: {} (probing)", state.current);
+
+        Some(state.current)
+    }
+
+    /// The server throttled a request sent at `epoch`, returns the new
+    /// concurrency if it shrank.
+    pub fn throttled(&self, epoch: u64) -> Option<usize> {
+        let mut state = self.state();
+
+        if epoch != state.epoch || state.current <= self.min {
+            return None;
+        }
+
+        state.successes = 0;
+        state.epoch += 1;
+        state.current = (state.current / 2).max(self.min);
+
+        log::info!("Concurrent requests: {} (throttled)", state.current);
+
+        Some(state.current)
+    }
+
+    /// Feed a failed request, only throttling errors lower the concurrency.
+    pub fn failure(&self, epoch: u64, error: &Error) -> Option<usize> {
+        if is_throttling(error) {
+            self.throttled(epoch)
+        } else {
+            None
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+mod tests {
+    use super::*;
+    use crate::s3::ApiError;
+
+    fn slow_down() -> Error {
+        Error::Api(ApiError {
+            status: 503,
+            code: Some("SlowDown".to_string()),
+            message: None,
+            details: String::new(),
+            retry_after: None,
+        })
+    }
+
+    #[test]
+    fn test_new() {
+        let concurrency = Concurrency::new(8);
+        assert_eq!(concurrency.current(), 8);
+        assert_eq!(Concurrency::new(0).current(), 1);
+        assert_eq!(Concurrency::with_limits(10, 2, 4).current(), 4);
+    }
+
+    #[test]
+    fn test_decrease() {
+        let concurrency = Concurrency::new(16);
+        let epoch = concurrency.epoch();
+
+        assert_eq!(concurrency.failure(epoch, &slow_down()), Some(8));
+
+        // requests in flight before the decrease are ignored
+        assert_eq!(concurrency.failure(epoch, &slow_down()), None);
+        assert_eq!(concurrency.current(), 8);
+
+        // not a throttling error
+        let denied = Error::Other("AccessDenied".to_string());
+        assert_eq!(concurrency.failure(concurrency.epoch(), &denied), None);
+
+        for expected in [4, 2, 1] {
+            assert_eq!(concurrency.throttled(concurrency.epoch()), Some(expected));
+        }
+
+        // never below the minimum
+        assert_eq!(concurrency.throttled(concurrency.epoch()), None);
+        assert_eq!(concurrency.current(), 1);
+    }
+
+    #[test]
+    fn test_increase() {
+        let concurrency = Concurrency::new(2);
+
+        // one more request after a window of successes
+        assert_eq!(concurrency.success(), None);
+        assert_eq!(concurrency.success(), Some(3));
+        assert_eq!(concurrency.success(), None);
+        assert_eq!(concurrency.success(), None);
+        assert_eq!(concurrency.success(), Some(4));
+
+        // never above twice the requested concurrency
+        for _ in 0..10 {
+            assert_eq!(concurrency.success(), None);
+        }
+        assert_eq!(concurrency.current(), 4);
+
+        // a decrease restarts the window
+        concurrency.success();
+        assert_eq!(concurrency.throttled(concurrency.epoch()), Some(2));
+        assert_eq!(concurrency.success(), None);
+        assert_eq!(concurrency.success(), Some(3));
+    }
+}
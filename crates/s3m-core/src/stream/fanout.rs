@@ -0,0 +1,764 @@
+//! One input streamed to several destinations.
+//!
+//! The input is read, compressed and encrypted once, then every chunk is
+//! staged for each destination: each one has its own multipart upload,
+//! `ETag`s, retries and progress line. A destination that fails is dropped
+//! while the others keep going, [`Require`] decides when the upload fails.
+//! The multipart uploads that can not complete are aborted, except the
+//! spooled ones kept for `streams resume`. The staging budgets are shared by
+//! the destinations.
+
+use crate::{
+    progressbar::Bar,
+    s3::{S3, actions},
+    stream::{
+        InitialStreamParams, RequestOptions, StdinStreamUpload, Stream, StreamProgressEvent,
+        complete_stream, create_initial_stream, flush_stream, get_key, initiate_multipart_upload,
+        maybe_upload_part,
+        part_size::PartSize,
+        pipeline::Pipeline,
+        setup_stream_progress, spawn_stream_progress_task,
+        spool::{Spool, SpoolOptions},
+        staging_limits, write_to_stream,
+    },
+};
+use anyhow::{Error, Result, anyhow};
+use futures::future::join_all;
+use std::{collections::BTreeMap, mem::take, str::FromStr};
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
+
+/// Destinations that must succeed for the upload to succeed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Require {
+    /// Every destination, the first failure stops the upload
+    #[default]
+    All,
+    /// At least one destination, the others may fail
+    Any,
+}
+
+impl FromStr for Require {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "all" => Ok(Self::All),
+            "any" => Ok(Self::Any),
+            _ => Err(anyhow!("invalid require policy: {s}, expected all or any")),
+        }
+    }
+}
+
+pub struct Destination<'a> {
+    pub s3: &'a S3,
+    pub object_key: &'a str,
+    /// Shown in the progress line and the errors, `host/bucket/key`
+    pub label: String,
+    /// Keep the staged parts to resume the upload with `streams resume`
+    pub spool: Option<SpoolOptions>,
+}
+
+enum Target<'a> {
+    Streaming(Box<Stream<'a>>),
+    Failed(Error),
+}
+
+// a multipart upload waiting for its stream
+struct Started<'a> {
+    upload: Upload<'a>,
+    spool: Option<Spool>,
+}
+
+// a multipart upload to abort when its destination can not complete
+struct Upload<'a> {
+    s3: &'a S3,
+    key: String,
+    id: String,
+    // host/bucket/key of the upload, for `s3m rm --abort`
+    path: String,
+}
+
+pub struct FanOut<'a> {
+    labels: Vec<String>,
+    targets: Vec<Target<'a>>,
+    // by destination, `None` once completed, aborted or kept by a spool
+    uploads: Vec<Option<Upload<'a>>>,
+    require: Require,
+}
+
+impl<'a> FanOut<'a> {
+    /// Start the multipart upload of every destination, `header` is staged
+    /// first in each of them
+    ///
+    /// # Errors
+    /// Will return `Err` if the destinations required by [`Require`] can not
+    /// be started
+    pub async fn start(request: StdinStreamUpload<'a>, header: Option<&[u8]>) -> Result<Self> {
+        let StdinStreamUpload {
+            destinations,
+            require,
+            acl,
+            meta,
+            quiet,
+            tmp_dir,
+            globals,
+            size_hint,
+            max_requests,
+            max_staging,
+            max_memory,
+        } = request;
+
+        let count = u64::try_from(destinations.len())?;
+        if count == 0 {
+            return Err(anyhow!("at least one destination is required"));
+        }
+
+        let (part_size, max_staging) = staging_limits(
+            PartSize::from_hint(size_hint)?,
+            max_staging / count,
+            max_memory.map(|max_memory| max_memory / count),
+        )?;
+
+        let mut fan_out = Self {
+            labels: destinations.iter().map(|d| d.label.clone()).collect(),
+            targets: Vec::with_capacity(destinations.len()),
+            uploads: Vec::with_capacity(destinations.len()),
+            require,
+        };
+
+        let mut started = Vec::with_capacity(destinations.len());
+        for (index, destination) in destinations.iter().enumerate() {
+            let meta = meta.clone().unwrap_or_default();
+
+            match initiate(destination, acl.clone(), meta, &globals).await {
+                Ok(upload) => {
+                    fan_out.uploads.push(upload.abortable());
+                    started.push(Ok(upload));
+                }
+                Err(e) => {
+                    fan_out.uploads.push(None);
+                    started.push(Err(fan_out.fail(index, e).await?));
+                }
+            }
+        }
+
+        let senders = setup_fanout_progress(quiet, part_size.size(1), &fan_out.labels).await;
+
+        for (index, (upload, sender)) in started.into_iter().zip(senders).enumerate() {
+            let stream = upload.and_then(|upload| {
+                create_initial_stream(InitialStreamParams {
+                    upload_id: &upload.upload.id,
+                    tmp_dir: &tmp_dir,
+                    key: &upload.upload.key,
+                    s3: upload.upload.s3,
+                    progress_sender: sender,
+                    globals: &globals,
+                    header_data: header,
+                    spool: upload.spool,
+                    part_size,
+                    pipeline: Pipeline::new(max_requests, max_staging),
+                    memory: max_memory.is_some(),
+                    part_number: 1,
+                    source: None,
+                })
+            });
+
+            match stream {
+                Ok(stream) => fan_out.targets.push(Target::Streaming(Box::new(stream))),
+                Err(e) => {
+                    let failed = fan_out.fail(index, e).await?;
+                    fan_out.targets.push(Target::Failed(failed));
+                }
+            }
+        }
+
+        fan_out.ensure_streaming()?;
+
+        Ok(fan_out)
+    }
+
+    /// Stage `data` for every destination still streaming and send the parts
+    /// that are full
+    ///
+    /// # Errors
+    /// Will return `Err` if a destination required by [`Require`] fails
+    pub async fn write(&mut self, data: &[u8]) -> Result<()> {
+        for index in 0..self.targets.len() {
+            let Some(Target::Streaming(stream)) = self.targets.get_mut(index) else {
+                continue;
+            };
+
+            let result = match write_to_stream(stream, data) {
+                Ok(()) => maybe_upload_part(stream).await.map_err(Error::from),
+                Err(e) => Err(anyhow!("Error writing chunk to stream: {e}")),
+            };
+
+            if let Err(e) = result {
+                let failed = self.fail(index, e).await?;
+                self.set_failed(index, failed);
+
+                self.ensure_streaming()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Send the final parts and complete the uploads, the results follow the
+    /// order of the destinations
+    ///
+    /// # Errors
+    /// Will return `Err` if the destinations required by [`Require`] failed
+    pub async fn finish(mut self) -> Result<Vec<Result<String>>> {
+        // every destination sends its last parts before any upload completes,
+        // so a failure can still abort all of them
+        let flushed = join_all(
+            take(&mut self.targets)
+                .into_iter()
+                .map(|target| async move {
+                    match target {
+                        Target::Streaming(mut stream) => {
+                            let etags = flush_stream(&mut stream).await?;
+                            Ok((stream, etags))
+                        }
+                        Target::Failed(e) => Err(e),
+                    }
+                }),
+        )
+        .await;
+
+        if !self.succeeded(flushed.iter().filter(|result| result.is_err()).count()) {
+            let error = self.error(
+                flushed
+                    .into_iter()
+                    .map(|result| result.map(|_| String::new()))
+                    .collect(),
+            );
+
+            return Err(abort_uploads(self.take_uploads(None), error).await);
+        }
+
+        let completed = join_all(flushed.into_iter().map(|result| async move {
+            let (stream, etags) = result?;
+            complete_stream(&stream, etags).await
+        }))
+        .await;
+
+        let mut results = Vec::with_capacity(completed.len());
+        for (index, result) in completed.into_iter().enumerate() {
+            let uploads = self.take_uploads(Some(index));
+
+            results.push(match result {
+                Ok(etag) => Ok(etag),
+                Err(e) => Err(abort_uploads(uploads, e).await),
+            });
+        }
+
+        if self.succeeded(results.iter().filter(|result| result.is_err()).count()) {
+            Ok(results)
+        } else {
+            Err(self.error(results))
+        }
+    }
+
+    fn succeeded(&self, failed: usize) -> bool {
+        match self.require {
+            Require::All => failed == 0,
+            Require::Any => failed < self.labels.len(),
+        }
+    }
+
+    // the error of a failed upload, from the results of every destination
+    fn error(&self, results: Vec<Result<String>>) -> Error {
+        let mut errors: Vec<(&str, Error)> = self
+            .labels
+            .iter()
+            .zip(results)
+            .filter_map(|(label, result)| result.err().map(|e| (label.as_str(), e)))
+            .collect();
+
+        // a single destination keeps its own error
+        if self.labels.len() == 1
+            && let Some((_, e)) = errors.pop()
+        {
+            return e;
+        }
+
+        failures_error(
+            errors.iter().map(|(label, e)| (*label, e)),
+            self.labels.len(),
+        )
+    }
+
+    /// Destination `index` failed with `error`: its upload is aborted, and
+    /// every other one when the policy fails the fan-out. Returns the error
+    /// to keep when the other destinations may go on.
+    async fn fail(&mut self, index: usize, error: Error) -> Result<Error> {
+        match self.failed(index, error) {
+            Ok(error) => {
+                // dropping the stream stops its parts before the abort
+                self.set_failed(index, anyhow!("aborted"));
+                Ok(abort_uploads(self.take_uploads(Some(index)), error).await)
+            }
+            Err(error) => {
+                self.targets.clear();
+                Err(abort_uploads(self.take_uploads(None), error).await)
+            }
+        }
+    }
+
+    fn set_failed(&mut self, index: usize, error: Error) {
+        if let Some(target) = self.targets.get_mut(index) {
+            *target = Target::Failed(error);
+        }
+    }
+
+    // the uploads of destination `index`, or of all of them
+    fn take_uploads(&mut self, index: Option<usize>) -> Vec<Upload<'a>> {
+        match index {
+            Some(index) => self
+                .uploads
+                .get_mut(index)
+                .and_then(Option::take)
+                .into_iter()
+                .collect(),
+            None => self.uploads.iter_mut().filter_map(Option::take).collect(),
+        }
+    }
+
+    /// Apply the policy to destination `index` that failed with `error`,
+    /// returning the error to keep when the other destinations may go on
+    fn failed(&self, index: usize, error: Error) -> Result<Error> {
+        // a single destination keeps its own error
+        if self.labels.len() == 1 {
+            return Err(error);
+        }
+
+        let label = self.labels.get(index).map_or("", String::as_str);
+
+        match self.require {
+            Require::All => Err(anyhow!("{label}: {error}")),
+            Require::Any => {
+                log::warn!("{label}: {error}");
+                Ok(error)
+            }
+        }
+    }
+
+    // with `Require::Any`, fail once no destination is left
+    fn ensure_streaming(&self) -> Result<()> {
+        if self
+            .targets
+            .iter()
+            .any(|target| matches!(target, Target::Streaming(_)))
+        {
+            return Ok(());
+        }
+
+        let failures = self
+            .labels
+            .iter()
+            .zip(&self.targets)
+            .filter_map(|(label, target)| match target {
+                Target::Failed(e) => Some((label.as_str(), e)),
+                Target::Streaming(_) => None,
+            });
+
+        Err(failures_error(failures, self.labels.len()))
+    }
+}
+
+impl<'a> Started<'a> {
+    // a spooled upload is kept for `streams resume`
+    fn abortable(&self) -> Option<Upload<'a>> {
+        self.spool.is_none().then(|| Upload {
+            s3: self.upload.s3,
+            key: self.upload.key.clone(),
+            id: self.upload.id.clone(),
+            path: self.upload.path.clone(),
+        })
+    }
+}
+
+/// Abort the multipart uploads of destinations that can not complete, so
+/// their parts do not stay billed. The ones that fail are added to `error`
+/// with the command to abort them.
+async fn abort_uploads(uploads: Vec<Upload<'_>>, error: Error) -> Error {
+    let results = join_all(uploads.iter().map(|upload| async move {
+        log::warn!("Aborting upload {} of {}", upload.id, upload.path);
+
+        actions::AbortMultipartUpload::new(&upload.key, &upload.id)
+            .request(upload.s3)
+            .await
+    }))
+    .await;
+
+    let hints: Vec<String> = uploads
+        .iter()
+        .zip(results)
+        .filter_map(|(upload, result)| {
+            result.err().map(|e| {
+                log::error!("Could not abort upload {}: {e}", upload.id);
+
+                format!(
+                    "could not abort upload {} ({e}), clean it up with: s3m rm {} --abort {}",
+                    upload.id, upload.path, upload.id
+                )
+            })
+        })
+        .collect();
+
+    if hints.is_empty() {
+        error
+    } else {
+        anyhow!("{error}\n{}", hints.join("\n"))
+    }
+}
+
+/// Create the multipart upload of a destination
+async fn initiate<'a>(
+    destination: &Destination<'a>,
+    acl: Option<String>,
+    meta: BTreeMap<String, String>,
+    globals: &RequestOptions,
+) -> Result<Started<'a>> {
+    let key = get_key(
+        destination.object_key,
+        globals.compress.then_some(globals.compression.codec),
+        globals.encrypt,
+    );
+
+    let upload_id = initiate_multipart_upload(
+        destination.s3,
+        &key,
+        acl,
+        meta,
+        globals.object_lock.clone(),
+        globals.tagging.as_ref(),
+    )
+    .await?;
+
+    // persist the upload id before sending any part
+    let spool = destination
+        .spool
+        .as_ref()
+        .map(|options| Spool::create(options, destination.s3, &key, &upload_id, globals))
+        .transpose()?;
+
+    // the label ends with the object key, without the codec and .enc suffix
+    let path = destination
+        .label
+        .strip_suffix(destination.object_key)
+        .map_or_else(|| key.clone(), |prefix| format!("{prefix}{key}"));
+
+    Ok(Started {
+        upload: Upload {
+            s3: destination.s3,
+            key,
+            id: upload_id,
+            path,
+        },
+        spool,
+    })
+}
+
+/// One progress line per destination, a single destination keeps the stream
+/// progress
+async fn setup_fanout_progress(
+    quiet: bool,
+    part_size: u64,
+    labels: &[String],
+) -> Vec<Option<UnboundedSender<StreamProgressEvent>>> {
+    if labels.len() == 1 {
+        return vec![setup_stream_progress(quiet, part_size).await];
+    }
+
+    let bars = if quiet {
+        None
+    } else {
+        Bar::new_fanout(part_size, labels)
+    };
+
+    let Some(bars) = bars else {
+        return vec![None; labels.len()];
+    };
+
+    bars.status
+        .into_iter()
+        .map(|status| {
+            let (sender, receiver) = unbounded_channel::<StreamProgressEvent>();
+            spawn_stream_progress_task(receiver, bars.staging.clone(), status);
+            Some(sender)
+        })
+        .collect()
+}
+
+fn failures_error<'e>(failures: impl Iterator<Item = (&'e str, &'e Error)>, total: usize) -> Error {
+    let failures: Vec<String> = failures
+        .map(|(label, e)| format!("  {label}: {e}"))
+        .collect();
+
+    anyhow!(
+        "{} of {total} destinations failed:\n{}",
+        failures.len(),
+        failures.join("\n")
+    )
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+mod tests {
+    use super::*;
+    use crate::s3::{Credentials, Region};
+    use mockito::{Matcher, Mock, Server, ServerGuard};
+    use secrecy::SecretString;
+    use std::path::Path;
+
+    fn create_mock_s3(endpoint: String, bucket: &str) -> S3 {
+        S3::new(
+            &Credentials::new(
+                "minioadmin",
+                &SecretString::new("minioadmin".to_string().into()),
+            ),
+            &Region::custom("us-east-1", endpoint),
+            Some(bucket.to_string()),
+            false,
+        )
+    }
+
+    // a multipart upload of `key` in `bucket`, its parts answered with `part_status`
+    async fn mock_upload(server: &mut ServerGuard, bucket: &str, part_status: usize) -> Vec<Mock> {
+        let path = format!("/{bucket}/key");
+
+        vec![
+            server
+                .mock("POST", path.as_str())
+                .match_query(Matcher::Regex("uploads".into()))
+                .with_status(200)
+                .with_body(format!(
+                    "<InitiateMultipartUploadResult><Bucket>{bucket}</Bucket><Key>key</Key><UploadId>id-{bucket}</UploadId></InitiateMultipartUploadResult>"
+                ))
+                .create_async()
+                .await,
+            server
+                .mock("PUT", path.as_str())
+                .match_query(Matcher::UrlEncoded("uploadId".into(), format!("id-{bucket}")))
+                .match_body("data")
+                .with_status(part_status)
+                .with_header("ETag", &format!("\"part-{bucket}\""))
+                .create_async()
+                .await,
+            server
+                .mock("POST", path.as_str())
+                .match_query(Matcher::UrlEncoded("uploadId".into(), format!("id-{bucket}")))
+                .with_status(200)
+                .with_body(format!(
+                    "<CompleteMultipartUploadResult><Location>l</Location><Bucket>{bucket}</Bucket><Key>key</Key><ETag>\"{bucket}\"</ETag></CompleteMultipartUploadResult>"
+                ))
+                .create_async()
+                .await,
+        ]
+    }
+
+    // AbortMultipartUpload of the upload of `bucket`, expected once
+    async fn mock_abort(server: &mut ServerGuard, bucket: &str, status: usize) -> Mock {
+        server
+            .mock("DELETE", format!("/{bucket}/key").as_str())
+            .match_query(Matcher::UrlEncoded(
+                "uploadId".into(),
+                format!("id-{bucket}"),
+            ))
+            .with_status(status)
+            .expect(1)
+            .create_async()
+            .await
+    }
+
+    fn request<'a>(s3s: &'a [S3], tmp_dir: &Path, require: Require) -> StdinStreamUpload<'a> {
+        StdinStreamUpload {
+            destinations: s3s
+                .iter()
+                .map(|s3| Destination {
+                    s3,
+                    object_key: "key",
+                    label: format!("host/{}/key", s3.bucket().unwrap()),
+                    spool: None,
+                })
+                .collect(),
+            require,
+            acl: None,
+            meta: None,
+            quiet: true,
+            tmp_dir: tmp_dir.to_path_buf(),
+            globals: RequestOptions {
+                retries: 1,
+                ..RequestOptions::new()
+            },
+            size_hint: None,
+            max_requests: 1,
+            max_staging: u64::MAX,
+            max_memory: None,
+        }
+    }
+
+    #[test]
+    fn test_require_from_str() {
+        assert_eq!("all".parse::<Require>().unwrap(), Require::All);
+        assert_eq!("any".parse::<Require>().unwrap(), Require::Any);
+        assert_eq!(Require::default(), Require::All);
+        assert!("some".parse::<Require>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_every_destination() {
+        let mut server = Server::new_async().await;
+        let _a = mock_upload(&mut server, "bucket-a", 200).await;
+        let _b = mock_upload(&mut server, "bucket-b", 200).await;
+
+        let s3s = [
+            create_mock_s3(server.url(), "bucket-a"),
+            create_mock_s3(server.url(), "bucket-b"),
+        ];
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut fan_out = FanOut::start(request(&s3s, dir.path(), Require::All), None)
+            .await
+            .unwrap();
+        fan_out.write(b"da").await.unwrap();
+        fan_out.write(b"ta").await.unwrap();
+
+        let etags: Vec<String> = fan_out
+            .finish()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(etags, vec!["\"bucket-a\"", "\"bucket-b\""]);
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_require_any() {
+        let mut server = Server::new_async().await;
+        let _a = mock_upload(&mut server, "bucket-a", 200).await;
+        let _b = server
+            .mock("POST", "/bucket-b/key")
+            .match_query(Matcher::Any)
+            .with_status(403)
+            .create_async()
+            .await;
+
+        let s3s = [
+            create_mock_s3(server.url(), "bucket-a"),
+            create_mock_s3(server.url(), "bucket-b"),
+        ];
+        let dir = tempfile::tempdir().unwrap();
+
+        // bucket-b can not be started, bucket-a keeps going
+        let mut fan_out = FanOut::start(request(&s3s, dir.path(), Require::Any), None)
+            .await
+            .unwrap();
+        fan_out.write(b"data").await.unwrap();
+
+        let results = fan_out.finish().await.unwrap();
+        assert_eq!(results[0].as_ref().unwrap(), "\"bucket-a\"");
+        assert!(results[1].is_err());
+
+        // every destination is required
+        assert!(
+            FanOut::start(request(&s3s, dir.path(), Require::All), None)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_require_all() {
+        let mut server = Server::new_async().await;
+        let a = mock_upload(&mut server, "bucket-a", 200).await;
+        let _b = mock_upload(&mut server, "bucket-b", 500).await;
+        let abort_a = mock_abort(&mut server, "bucket-a", 204).await;
+        let abort_b = mock_abort(&mut server, "bucket-b", 204).await;
+
+        let s3s = [
+            create_mock_s3(server.url(), "bucket-a"),
+            create_mock_s3(server.url(), "bucket-b"),
+        ];
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut fan_out = FanOut::start(request(&s3s, dir.path(), Require::All), None)
+            .await
+            .unwrap();
+        fan_out.write(b"data").await.unwrap();
+
+        let err = fan_out.finish().await.unwrap_err().to_string();
+        assert!(err.contains("1 of 2 destinations failed"), "{err}");
+        assert!(err.contains("host/bucket-b/key"), "{err}");
+        assert!(!err.contains("bucket-a"), "{err}");
+
+        // bucket-a sent its part but is aborted instead of completed
+        a[1].assert_async().await;
+        abort_a.assert_async().await;
+        abort_b.assert_async().await;
+        assert!(!a[2].matched_async().await);
+
+        // a single destination keeps its own error
+        let mut fan_out = FanOut::start(request(&s3s[1..], dir.path(), Require::Any), None)
+            .await
+            .unwrap();
+        fan_out.write(b"data").await.unwrap();
+        let err = fan_out.finish().await.unwrap_err().to_string();
+        assert!(!err.contains("destinations failed"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_aborts_started_uploads() {
+        let mut server = Server::new_async().await;
+        let _a = mock_upload(&mut server, "bucket-a", 200).await;
+        let _c = mock_upload(&mut server, "bucket-c", 200).await;
+        let _b = server
+            .mock("POST", "/bucket-b/key")
+            .match_query(Matcher::Any)
+            .with_status(403)
+            .create_async()
+            .await;
+        let abort_a = mock_abort(&mut server, "bucket-a", 204).await;
+        let abort_c = mock_abort(&mut server, "bucket-c", 403).await;
+
+        let s3s = [
+            create_mock_s3(server.url(), "bucket-a"),
+            create_mock_s3(server.url(), "bucket-b"),
+            create_mock_s3(server.url(), "bucket-c"),
+        ];
+        let dir = tempfile::tempdir().unwrap();
+
+        // bucket-b can not be started, the upload of bucket-a is aborted
+        let err = FanOut::start(request(&s3s[..2], dir.path(), Require::All), None)
+            .await
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("host/bucket-b/key"), "{err}");
+        assert!(!err.contains("--abort"), "{err}");
+        abort_a.assert_async().await;
+
+        // an upload that can not be aborted is left to the user
+        let s3s = [s3s[2].clone(), s3s[1].clone()];
+        let err = FanOut::start(request(&s3s, dir.path(), Require::All), None)
+            .await
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(
+            err.contains("clean it up with: s3m rm host/bucket-c/key --abort id-bucket-c"),
+            "{err}"
+        );
+        abort_c.assert_async().await;
+    }
+}
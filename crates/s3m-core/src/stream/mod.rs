@@ -1,7 +1,14 @@
+pub mod compression;
+pub mod concurrency;
 pub mod db;
+pub mod fanout;
 pub mod iterator;
 pub mod part;
+pub mod part_size;
+pub mod pipeline;
+pub mod spool;
 pub mod state;
+pub mod transform;
 pub mod upload_compressed;
 pub mod upload_compressed_encrypted;
 pub mod upload_default;
@@ -15,11 +22,26 @@ pub mod upload_stdin_encrypted;
 use crate::{
     progressbar::Bar,
     s3::RequestOptions,
-    s3::{S3, actions, object_lock::ObjectLock, request::ProgressCallback},
+    s3::{
+        Bandwidth, S3, actions,
+        limits::{MAX_PARTS_PER_UPLOAD, MIN_PART_SIZE_BYTES},
+        object_lock::ObjectLock,
+        request::ProgressCallback,
+        tagging::Tagging,
+    },
+    stream::{
+        compression::{Codec, Compression},
+        concurrency::Concurrency,
+        fanout::{Destination, Require},
+        part_size::PartSize,
+        pipeline::Pipeline,
+        spool::Spool,
+        transform::{Checkpoint, CheckpointOptions},
+    },
 };
-use aead_stream::{DecryptorBE32, EncryptorBE32};
+use aead_stream::{DecryptorBE32, EncryptorBE32, StreamBE32, StreamPrimitive};
 use anyhow::{Context as _, Result, anyhow};
-use bytes::BytesMut;
+use bytes::{Bytes, BytesMut};
 use bytesize::ByteSize;
 use chacha20poly1305::{ChaCha20Poly1305, Key, aead::KeyInit};
 use indicatif::ProgressBar;
@@ -29,46 +51,93 @@ use secrecy::ExposeSecret;
 use std::{
     cmp::min,
     collections::BTreeMap,
-    io::{Cursor, Read, Write},
+    io::Write,
     path::{Path, PathBuf},
     sync::Arc,
 };
 use tempfile::{Builder, NamedTempFile};
-use tokio::time::{Duration, sleep};
+use tokio::time::sleep;
 use tokio::{
     io::Error,
     sync::mpsc::{UnboundedSender, unbounded_channel},
     task,
 };
-use zstd::stream::encode_all;
-
-// 512MB  to upload 5TB (the current max object size)
-const STDIN_BUFFER_SIZE: usize = 1_024 * 1_024 * 512;
 
 pub struct Stream<'a> {
-    tmp_file: NamedTempFile,
+    staging: Staging,
     count: usize,
-    etags: Vec<String>,
-    key: &'a str,
+    part_size: PartSize,
+    pipeline: Pipeline,
+    key: String,
     part_number: u16,
     s3: &'a S3,
-    upload_id: &'a str,
+    upload_id: String,
     sha: ring::digest::Context,
     md5: md5::Context,
     channel: Option<UnboundedSender<StreamProgressEvent>>,
     tmp_dir: PathBuf,
-    throttle: Option<usize>,
+    throttle: Option<Bandwidth>,
     retries: u32,
+    spool: Option<Arc<Spool>>,
+    source: Option<Source>,
+}
+
+/// Input of a stream read from a file, every part is checkpointed with the
+/// range of the file it holds
+struct Source {
+    checkpoint: Arc<Checkpoint>,
+    // file offsets of the staged part and of the input staged so far
+    start: u64,
+    end: u64,
+}
+
+/// Where the current part is staged, a temporary file unless the stream is
+/// staged in memory
+enum Staging {
+    File(NamedTempFile),
+    Memory(BytesMut),
+}
+
+// the staged part handed to an upload task
+enum PartBody {
+    File(NamedTempFile),
+    Memory(Bytes),
+}
+
+/// A staged part, owned by the task sending it
+struct StagedPart {
+    s3: S3,
+    key: String,
+    upload_id: String,
+    part_number: u16,
+    body: PartBody,
+    count: usize,
+    digest_sha: ring::digest::Digest,
+    digest_md5: md5::Digest,
+    channel: Option<UnboundedSender<StreamProgressEvent>>,
+    globals: RequestOptions,
+    concurrency: Arc<Concurrency>,
+    checkpoint: Option<Arc<Checkpoint>>,
 }
 
-struct InitialStreamParams<'a> {
-    upload_id: &'a str,
-    tmp_dir: &'a Path,
-    key: &'a str,
+// the stream only borrows `s3`, the other references may be dropped once it is
+// created
+struct InitialStreamParams<'a, 'b> {
+    upload_id: &'b str,
+    tmp_dir: &'b Path,
+    key: &'b str,
     s3: &'a S3,
     progress_sender: Option<UnboundedSender<StreamProgressEvent>>,
-    globals: &'a RequestOptions,
-    header_data: Option<&'a [u8]>,
+    globals: &'b RequestOptions,
+    header_data: Option<&'b [u8]>,
+    spool: Option<Spool>,
+    part_size: PartSize,
+    pipeline: Pipeline,
+    // stage in memory instead of tmp_dir
+    memory: bool,
+    // first part of the stream, greater than 1 when resuming a checkpoint
+    part_number: u16,
+    source: Option<Source>,
 }
 
 pub struct FileStreamUpload<'a> {
@@ -80,101 +149,89 @@ pub struct FileStreamUpload<'a> {
     pub tmp_dir: PathBuf,
     pub globals: RequestOptions,
     pub file_path: &'a Path,
+    /// Parts sent in parallel
+    pub max_requests: u8,
+    /// Staged bytes waiting for S3
+    pub max_staging: u64,
+    /// Stage the parts in memory instead of `tmp_dir`, within this many bytes
+    pub max_memory: Option<u64>,
+    /// Keep the state of the upload to resume it, see [`transform`]
+    pub checkpoint: Option<CheckpointOptions>,
+}
+
+pub struct StdinStreamUpload<'a> {
+    /// Each destination gets its own multipart upload of the input
+    pub destinations: Vec<Destination<'a>>,
+    /// Destinations that must succeed
+    pub require: Require,
+    pub acl: Option<String>,
+    pub meta: Option<BTreeMap<String, String>>,
+    pub quiet: bool,
+    pub tmp_dir: PathBuf,
+    pub globals: RequestOptions,
+    /// Approximate size of the input, to start with larger parts
+    pub size_hint: Option<u64>,
+    /// Parts sent in parallel
+    pub max_requests: u8,
+    /// Staged bytes waiting for S3
+    pub max_staging: u64,
+    /// Stage the parts in memory instead of `tmp_dir`, within this many bytes
+    pub max_memory: Option<u64>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum StreamProgressEvent {
     Staged(u64),
     SendingStarted { part_number: u16, total_bytes: u64 },
-    SendingProgress(u64),
-    SendingStopped,
-    Confirmed(u64),
-    ResetStaging,
+    SendingProgress { part_number: u16, bytes: u64 },
+    SendingStopped { part_number: u16 },
+    Confirmed { part_number: u16, bytes: u64 },
+    ResetStaging { part_size: u64 },
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 struct StreamProgressState {
     staged_current: u64,
     staged_total: u64,
     confirmed_total: u64,
-    sending_current: u64,
-    sending_total: u64,
-    sending_part: Option<u16>,
+    // sent and total bytes of the parts being sent
+    sending: BTreeMap<u16, (u64, u64)>,
 }
 
-// return the key with the .zst, .enc, or .zst.enc extension based on the flags
-fn get_key(key: &str, compress: bool, encrypt: bool) -> String {
-    let path = Path::new(key);
-    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-
-    match (compress, encrypt) {
-        (true, true) => {
-            // If extension is already "zst.enc" add ".zst.enc" only if not present
-            if ext == "zst.enc" {
-                key.to_string()
-            } else {
-                format!("{key}.zst.enc")
-            }
-        }
-
-        (true, false) => {
-            // Only compress: add ".zst" if not already "zst"
-            if ext == "zst" {
-                key.to_string()
-            } else {
-                format!("{key}.zst")
-            }
-        }
-
-        (false, true) => {
-            // Only encrypt: add ".enc" if not already "enc"
-            if ext == "enc" {
-                key.to_string()
-            } else {
-                format!("{key}.enc")
-            }
-        }
+impl StreamProgressState {
+    fn sending_current(&self) -> u64 {
+        self.sending.values().map(|(current, _)| current).sum()
+    }
 
-        (false, false) => key.to_string(),
+    fn sending_total(&self) -> u64 {
+        self.sending.values().map(|(_, total)| total).sum()
     }
 }
 
-async fn try_stream_part(part: &Stream<'_>) -> Result<String> {
-    let mut etag = String::new();
-
-    let digest_sha = part.sha.clone().finish();
-    let digest_md5 = part.md5.clone().finalize();
-
-    // Create globals only to pass the throttle
-    let globals = RequestOptions {
-        throttle: part.throttle,
-        retries: part.retries,
-        compress: false,
-        encrypt: false,
-        enc_key: None,
-        object_lock: None,
-    };
+// return the key with the extension of the codec, .enc, or both, unless the
+// key already ends with it
+fn get_key(key: &str, compress: Option<Codec>, encrypt: bool) -> String {
+    let mut suffix = compress
+        .map(|codec| format!(".{}", codec.extension()))
+        .unwrap_or_default();
 
-    for attempt in 1..=part.retries {
-        // Exponential backoff: 1s, 2s, 4s, 8s, 16s, capped at 30s
-        const MAX_BACKOFF_SECS: u64 = 30;
-        let backoff_time = std::cmp::min(2u64.pow(attempt - 1), MAX_BACKOFF_SECS);
+    if encrypt {
+        suffix.push_str(".enc");
+    }
 
-        if attempt > 1 {
-            // Add jitter (0-1000ms) to prevent thundering herd
-            let jitter_ms = rng().random_range(0_u64..1000);
-            let total_backoff =
-                Duration::from_secs(backoff_time) + Duration::from_millis(jitter_ms);
+    if key.ends_with(&suffix) {
+        key.to_string()
+    } else {
+        format!("{key}{suffix}")
+    }
+}
 
-            log::warn!(
-                "Error streaming part number {}, retrying in {:.1} seconds",
-                part.part_number,
-                total_backoff.as_secs_f64()
-            );
+async fn try_stream_part(part: &StagedPart) -> Result<String> {
+    let mut etag = String::new();
 
-            sleep(total_backoff).await;
-        }
+    let policy = part.globals.retry_policy();
 
+    for attempt in 1..=policy.attempts() {
         emit_stream_progress(
             part.channel.as_ref(),
             StreamProgressEvent::SendingStarted {
@@ -183,22 +240,41 @@ async fn try_stream_part(part: &Stream<'_>) -> Result<String> {
             },
         );
 
-        let action = actions::StreamPart::new(
-            part.key,
-            part.tmp_file.path(),
-            part.part_number,
-            part.upload_id,
-            part.count,
-            (digest_sha.as_ref(), digest_md5.as_ref()),
-            stream_sending_progress_callback(part.channel.as_ref()),
-        );
+        let digest = (part.digest_sha.as_ref(), part.digest_md5.as_ref());
+        let progress = stream_sending_progress_callback(part.channel.as_ref(), part.part_number);
+
+        let action = match &part.body {
+            PartBody::File(tmp_file) => actions::StreamPart::new(
+                &part.key,
+                tmp_file.path(),
+                part.part_number,
+                &part.upload_id,
+                part.count,
+                digest,
+                progress,
+            ),
+            PartBody::Memory(bytes) => actions::StreamPart::from_bytes(
+                &part.key,
+                bytes.clone(),
+                part.part_number,
+                &part.upload_id,
+                digest,
+                progress,
+            ),
+        };
+
+        let epoch = part.concurrency.epoch();
 
-        match action.request(part.s3, &globals).await {
+        match action.request(&part.s3, &part.globals).await {
             Ok(e) => {
                 etag = e;
+                part.concurrency.success();
                 emit_stream_progress(
                     part.channel.as_ref(),
-                    StreamProgressEvent::Confirmed(part.count as u64),
+                    StreamProgressEvent::Confirmed {
+                        part_number: part.part_number,
+                        bytes: part.count as u64,
+                    },
                 );
 
                 log::info!("Uploaded part: {}, etag: {}", part.part_number, etag);
@@ -207,23 +283,40 @@ async fn try_stream_part(part: &Stream<'_>) -> Result<String> {
             }
 
             Err(e) => {
-                emit_stream_progress(part.channel.as_ref(), StreamProgressEvent::SendingStopped);
+                part.concurrency.failure(epoch, &e);
+                emit_stream_progress(
+                    part.channel.as_ref(),
+                    StreamProgressEvent::SendingStopped {
+                        part_number: part.part_number,
+                    },
+                );
 
                 log::error!(
                     "Error uploading part number {}, attempt {}/{} failed: {}",
                     part.part_number,
                     attempt,
-                    part.retries,
+                    policy.attempts(),
                     e
                 );
 
-                if attempt == part.retries {
+                if attempt == policy.attempts() {
                     return Err(anyhow::anyhow!(
                         "Error uploading part number {}, {}",
                         part.part_number,
                         e
                     ));
                 }
+
+                // exponential backoff with jitter, or the Retry-After of SlowDown
+                let delay = policy.delay(attempt, e.retry_after());
+
+                log::warn!(
+                    "Error streaming part number {}, retrying in {:.1} seconds",
+                    part.part_number,
+                    delay.as_secs_f64()
+                );
+
+                sleep(delay).await;
             }
         }
     }
@@ -231,25 +324,44 @@ async fn try_stream_part(part: &Stream<'_>) -> Result<String> {
     Ok(etag)
 }
 
-/// Compresses a chunk of bytes using Zstandard (zstd), offloading the work to a blocking thread.
+/// Send a staged part, with a spool or a checkpoint it is confirmed once S3
+/// accepted it
+async fn upload_staged_part(part: StagedPart, spool: Option<Arc<Spool>>) -> Result<String> {
+    let etag = try_stream_part(&part).await?;
+
+    if let Some(spool) = spool {
+        spool
+            .confirm(part.part_number, &etag)
+            .context("Error updating spool")?;
+    }
+
+    if let Some(checkpoint) = &part.checkpoint {
+        checkpoint
+            .confirm(part.part_number, &etag)
+            .context("Error updating checkpoint")?;
+    }
+
+    Ok(etag)
+}
+
+/// Compresses a chunk of bytes with the codec and level of `compression`,
+/// offloading the work to a blocking thread.
 ///
 /// # Errors
 /// Returns an error if compression fails or the thread panics.
-pub async fn compress_chunk(bytes: BytesMut) -> Result<Vec<u8>> {
+pub async fn compress_chunk(bytes: BytesMut, compression: Compression) -> Result<Vec<u8>> {
     let input = bytes.freeze(); // Safe to send across threads
 
-    task::spawn_blocking(move || {
-        encode_all(Cursor::new(input), 0).context("failed to compress with zstd")
-    })
-    .await
-    .context("compression task panicked or was cancelled")?
+    task::spawn_blocking(move || compression.compress(&input))
+        .await
+        .context("compression task panicked or was cancelled")?
 }
 
-/// Decompresses Zstandard (zstd) data, offloading the work to a blocking thread.
+/// Decompresses data of `codec`, offloading the work to a blocking thread.
 ///
 /// Counterpart of [`compress_chunk`]. Decodes the entire input, including
-/// multiple concatenated zstd frames (as produced by compressing a stream chunk
-/// by chunk), so it can restore a whole compressed object in one call.
+/// multiple concatenated frames (as produced by compressing a stream chunk by
+/// chunk), so it can restore a whole compressed object in one call.
 ///
 /// The output is **bounded to `max_output` bytes**: decompression stops and
 /// returns an error if the decompressed size would exceed it. This caps memory
@@ -258,31 +370,12 @@ pub async fn compress_chunk(bytes: BytesMut) -> Result<Vec<u8>> {
 /// # Errors
 /// Returns an error if decompression fails, the output would exceed
 /// `max_output`, or the thread panics.
-pub async fn decompress_chunk(data: Vec<u8>, max_output: usize) -> Result<Vec<u8>> {
-    task::spawn_blocking(move || {
-        let decoder = zstd::stream::read::Decoder::new(Cursor::new(data))
-            .context("failed to initialize zstd decoder")?;
-
-        // Read at most max_output + 1 bytes; getting the extra byte means the
-        // decompressed stream is larger than allowed.
-        let limit = u64::try_from(max_output)
-            .unwrap_or(u64::MAX)
-            .saturating_add(1);
-        let mut out = Vec::new();
-        decoder
-            .take(limit)
-            .read_to_end(&mut out)
-            .context("failed to decompress with zstd")?;
-
-        if out.len() > max_output {
-            return Err(anyhow!(
-                "decompressed output exceeds the {max_output}-byte limit"
-            ));
-        }
-        Ok(out)
-    })
-    .await
-    .context("decompression task panicked or was cancelled")?
+pub async fn decompress_chunk(data: Vec<u8>, codec: Codec, max_output: usize) -> Result<Vec<u8>> {
+    let limit = u64::try_from(max_output).unwrap_or(u64::MAX);
+
+    task::spawn_blocking(move || codec.decompress(&data, limit))
+        .await
+        .context("decompression task panicked or was cancelled")?
 }
 
 /// Build a `ChaCha20Poly1305` cipher from a 32-byte encryption key.
@@ -332,8 +425,10 @@ async fn initiate_multipart_upload(
     acl: Option<String>,
     meta: BTreeMap<String, String>,
     object_lock: Option<ObjectLock>,
+    tagging: Option<&Tagging>,
 ) -> Result<String> {
-    let action = actions::CreateMultipartUpload::new(key, acl, Some(meta), None, object_lock);
+    let action = actions::CreateMultipartUpload::new(key, acl, Some(meta), None, object_lock)
+        .tagging(tagging);
     let response = action.request(s3).await?;
     Ok(response.upload_id)
 }
@@ -353,14 +448,17 @@ async fn setup_progress(quiet: bool, size: u64) -> Option<UnboundedSender<usize>
     None
 }
 
-async fn setup_stream_progress(quiet: bool) -> Option<UnboundedSender<StreamProgressEvent>> {
+async fn setup_stream_progress(
+    quiet: bool,
+    part_size: u64,
+) -> Option<UnboundedSender<StreamProgressEvent>> {
     if quiet {
         return None;
     }
 
     let (sender, receiver) = unbounded_channel::<StreamProgressEvent>();
 
-    if let Some(bars) = Bar::new_stream(STDIN_BUFFER_SIZE as u64) {
+    if let Some(bars) = Bar::new_stream(part_size) {
         spawn_stream_progress_task(receiver, bars.staging, bars.status);
         return Some(sender);
     }
@@ -403,33 +501,33 @@ fn spawn_stream_progress_task(
                 StreamProgressEvent::Staged(bytes_count) => {
                     state.staged_current += bytes_count;
                     state.staged_total += bytes_count;
-                    staging_pb.set_position(min(state.staged_current, STDIN_BUFFER_SIZE as u64));
+                    staging_pb.set_position(
+                        staging_pb
+                            .length()
+                            .map_or(state.staged_current, |len| min(state.staged_current, len)),
+                    );
                 }
                 StreamProgressEvent::SendingStarted {
                     part_number,
                     total_bytes,
                 } => {
-                    state.sending_part = Some(part_number);
-                    state.sending_current = 0;
-                    state.sending_total = total_bytes;
+                    state.sending.insert(part_number, (0, total_bytes));
                 }
-                StreamProgressEvent::SendingProgress(bytes_count) => {
-                    state.sending_current =
-                        min(state.sending_current + bytes_count, state.sending_total);
+                StreamProgressEvent::SendingProgress { part_number, bytes } => {
+                    if let Some((current, total)) = state.sending.get_mut(&part_number) {
+                        *current = min(*current + bytes, *total);
+                    }
                 }
-                StreamProgressEvent::SendingStopped => {
-                    state.sending_part = None;
-                    state.sending_current = 0;
-                    state.sending_total = 0;
+                StreamProgressEvent::SendingStopped { part_number } => {
+                    state.sending.remove(&part_number);
                 }
-                StreamProgressEvent::Confirmed(bytes_count) => {
-                    state.confirmed_total += bytes_count;
-                    state.sending_part = None;
-                    state.sending_current = 0;
-                    state.sending_total = 0;
+                StreamProgressEvent::Confirmed { part_number, bytes } => {
+                    state.confirmed_total += bytes;
+                    state.sending.remove(&part_number);
                 }
-                StreamProgressEvent::ResetStaging => {
+                StreamProgressEvent::ResetStaging { part_size } => {
                     state.staged_current = 0;
+                    staging_pb.set_length(part_size);
                     staging_pb.set_position(0);
                 }
             }
@@ -438,13 +536,13 @@ fn spawn_stream_progress_task(
         }
 
         log::debug!(
-            "Stream progress channel closed - staged_current: {}, staged_total: {}, confirmed_total: {}, sending_current: {}, sending_total: {}, sending_part: {:?}",
+            "Stream progress channel closed - staged_current: {}, staged_total: {}, confirmed_total: {}, sending_current: {}, sending_total: {}, sending_parts: {:?}",
             state.staged_current,
             state.staged_total,
             state.confirmed_total,
-            state.sending_current,
-            state.sending_total,
-            state.sending_part
+            state.sending_current(),
+            state.sending_total(),
+            state.sending.keys()
         );
 
         staging_pb.finish();
@@ -454,15 +552,23 @@ fn spawn_stream_progress_task(
 }
 
 fn update_stream_status_message(pb: &ProgressBar, state: &StreamProgressState) {
-    let message = if let Some(part_number) = state.sending_part {
-        format!(
+    let parts: Vec<String> = state.sending.keys().map(u16::to_string).collect();
+
+    let message = match parts.as_slice() {
+        [] => format!("confirmed {}", ByteSize(state.confirmed_total)),
+        [part_number] => format!(
             "sending part {part_number} {}/{} | confirmed {}",
-            ByteSize(state.sending_current),
-            ByteSize(state.sending_total),
+            ByteSize(state.sending_current()),
+            ByteSize(state.sending_total()),
             ByteSize(state.confirmed_total)
-        )
-    } else {
-        format!("confirmed {}", ByteSize(state.confirmed_total))
+        ),
+        _ => format!(
+            "sending parts {} {}/{} | confirmed {}",
+            parts.join(", "),
+            ByteSize(state.sending_current()),
+            ByteSize(state.sending_total()),
+            ByteSize(state.confirmed_total)
+        ),
     };
 
     pb.set_message(message);
@@ -481,11 +587,15 @@ fn emit_stream_progress(
 
 fn stream_sending_progress_callback(
     channel: Option<&UnboundedSender<StreamProgressEvent>>,
+    part_number: u16,
 ) -> Option<ProgressCallback> {
     let sender = channel.cloned()?;
     let callback: ProgressCallback = Arc::new(move |bytes_count| {
         if sender
-            .send(StreamProgressEvent::SendingProgress(bytes_count as u64))
+            .send(StreamProgressEvent::SendingProgress {
+                part_number,
+                bytes: bytes_count as u64,
+            })
             .is_err()
         {
             log::trace!("Progress receiver dropped");
@@ -494,27 +604,108 @@ fn stream_sending_progress_callback(
     Some(callback)
 }
 
+/// Part sizes and staging budget of a stream. With `max_memory` parts are
+/// staged in memory: half of it holds the part being staged and half the
+/// parts being sent, so parts are also limited to half of it.
+///
+/// # Errors
+/// Returns `Err` if `max_memory` can not hold two parts of the minimum size
+fn staging_limits(
+    part_size: PartSize,
+    max_staging: u64,
+    max_memory: Option<u64>,
+) -> Result<(PartSize, u64)> {
+    let Some(max_memory) = max_memory else {
+        return Ok((part_size, max_staging));
+    };
+
+    let half = max_memory / 2;
+
+    if half < MIN_PART_SIZE_BYTES {
+        return Err(anyhow!(
+            "max memory must be at least {} bytes, two parts of the minimum part size",
+            MIN_PART_SIZE_BYTES * 2
+        ));
+    }
+
+    let part_size = part_size.limit(half);
+
+    log::info!(
+        "Staging in memory, max part size: {}, max object size: {}",
+        ByteSize(part_size.size(u16::MAX)),
+        ByteSize(part_size.capacity())
+    );
+
+    Ok((part_size, max_staging.min(half)))
+}
+
+impl Staging {
+    fn new(memory: bool, prefix: &str, tmp_dir: &Path, part_size: u64) -> Result<Self> {
+        if memory {
+            return Ok(Self::Memory(BytesMut::with_capacity(
+                usize::try_from(part_size).unwrap_or(usize::MAX),
+            )));
+        }
+
+        Ok(Self::File(
+            Builder::new()
+                .prefix(prefix)
+                .suffix(".s3m")
+                .tempfile_in(tmp_dir)?,
+        ))
+    }
+
+    fn write(&mut self, data: &[u8]) -> std::io::Result<()> {
+        match self {
+            Self::File(tmp_file) => tmp_file.write_all(data),
+            Self::Memory(buffer) => {
+                buffer.extend_from_slice(data);
+                Ok(())
+            }
+        }
+    }
+
+    const fn is_memory(&self) -> bool {
+        matches!(self, Self::Memory(_))
+    }
+}
+
 /// Create the initial stream with nonce header
-fn create_initial_stream(params: InitialStreamParams<'_>) -> Result<Stream<'_>> {
-    let tmp_file = Builder::new()
-        .prefix(params.upload_id)
-        .suffix(".s3m")
-        .tempfile_in(params.tmp_dir)?;
+fn create_initial_stream<'a>(params: InitialStreamParams<'a, '_>) -> Result<Stream<'a>> {
+    if params.memory && params.spool.is_some() {
+        return Err(anyhow!("a spool stages the parts on disk, not in memory"));
+    }
+
+    // spooled parts are linked from the staging file, keep it in the spool
+    let tmp_dir = params
+        .spool
+        .as_ref()
+        .map_or(params.tmp_dir, |spool| spool.dir());
+
+    let staging = Staging::new(
+        params.memory,
+        params.upload_id,
+        tmp_dir,
+        params.part_size.size(params.part_number),
+    )?;
 
     let mut stream = Stream {
-        tmp_file,
+        staging,
         count: 0,
-        etags: Vec::new(),
-        key: params.key,
-        part_number: 1,
+        part_size: params.part_size,
+        pipeline: params.pipeline,
+        key: params.key.to_string(),
+        part_number: params.part_number,
         s3: params.s3,
-        upload_id: params.upload_id,
+        upload_id: params.upload_id.to_string(),
         sha: Context::new(&SHA256),
         md5: md5::Context::new(),
         channel: params.progress_sender,
-        tmp_dir: params.tmp_dir.to_path_buf(),
-        throttle: params.globals.throttle,
+        tmp_dir: tmp_dir.to_path_buf(),
+        throttle: params.globals.throttle.clone(),
         retries: params.globals.retries,
+        spool: params.spool.map(Arc::new),
+        source: params.source,
     };
 
     if let Some(header) = params.header_data {
@@ -529,7 +720,7 @@ fn create_initial_stream(params: InitialStreamParams<'_>) -> Result<Stream<'_>>
 /// # Errors
 /// Returns `Err` if writing to the temporary file fails
 pub fn write_to_stream(stream: &mut Stream<'_>, data: &[u8]) -> Result<()> {
-    stream.tmp_file.write_all(data)?;
+    stream.staging.write(data)?;
     stream.sha.update(data);
     stream.md5.consume(data);
     stream.count += data.len();
@@ -542,6 +733,13 @@ pub fn write_to_stream(stream: &mut Stream<'_>, data: &[u8]) -> Result<()> {
     Ok(())
 }
 
+/// Count `bytes` of the file as staged in the current part
+fn read_from_source(stream: &mut Stream<'_>, bytes: u64) {
+    if let Some(source) = stream.source.as_mut() {
+        source.end += bytes;
+    }
+}
+
 /// Create encryption nonce header
 #[must_use]
 pub fn create_nonce_header(nonce_bytes: &[u8; 7]) -> Vec<u8> {
@@ -581,12 +779,34 @@ pub fn encrypt_chunk(
         .encrypt_next_in_place(&[], &mut encrypted_chunk)
         .map_err(|e| anyhow!("Encryption error: {e}"))?;
 
-    // Format: [encrypted_data_length(4 bytes)][encrypted_data]
+    frame_encrypted_chunk(&encrypted_chunk)
+}
+
+/// Encrypt a chunk of data at `position` of the STREAM, the output is the same
+/// as [`encrypt_chunk`] for the chunk number `position`
+///
+/// # Errors
+/// Returns `Err` if encryption fails or if the encrypted chunk exceeds 4GB
+pub fn encrypt_chunk_at(
+    stream: &StreamBE32<ChaCha20Poly1305>,
+    position: u32,
+    chunk: &[u8],
+) -> Result<Vec<u8>> {
+    let mut encrypted_chunk = chunk.to_vec();
+    stream
+        .encrypt_in_place(position, false, &[], &mut encrypted_chunk)
+        .map_err(|e| anyhow!("Encryption error: {e}"))?;
+
+    frame_encrypted_chunk(&encrypted_chunk)
+}
+
+// Format: [encrypted_data_length(4 bytes)][encrypted_data]
+fn frame_encrypted_chunk(encrypted_chunk: &[u8]) -> Result<Vec<u8>> {
     let encrypted_len = u32::try_from(encrypted_chunk.len())
         .map_err(|_| anyhow!("Encrypted chunk size exceeds 4GB"))?;
     let mut result = Vec::with_capacity(4 + encrypted_chunk.len());
     result.extend_from_slice(&encrypted_len.to_be_bytes());
-    result.extend_from_slice(&encrypted_chunk);
+    result.extend_from_slice(encrypted_chunk);
     Ok(result)
 }
 
@@ -610,39 +830,110 @@ pub fn decrypt_chunk(
     Ok(chunk)
 }
 
-/// Check if we need to upload current part and start a new one
+/// Check if we need to upload current part and start a new one, the part is
+/// sent in the background while the next one is staged. Parts grow with the
+/// part number, see [`PartSize`].
 ///
 /// # Errors
-/// Returns `Err` if streaming the part fails or if creating a new temporary file fails
-pub async fn maybe_upload_part(stream: &mut Stream<'_>, buffer_size: usize) -> Result<(), Error> {
-    if stream.count >= buffer_size {
-        let etag = try_stream_part(stream)
+/// Returns `Err` if a previous part failed or if creating a new temporary file fails
+pub async fn maybe_upload_part(stream: &mut Stream<'_>) -> Result<(), Error> {
+    if stream.count as u64 >= stream.part_size.size(stream.part_number) {
+        let part = take_part(stream).map_err(Error::other)?;
+
+        emit_stream_progress(
+            stream.channel.as_ref(),
+            StreamProgressEvent::ResetStaging {
+                part_size: stream.part_size.size(stream.part_number),
+            },
+        );
+
+        send_part(stream, part)
             .await
             .map_err(|e| Error::other(format!("Error streaming part: {e}")))?;
+    }
+    Ok(())
+}
+
+/// Take the staged part out of the stream and start a new one, with a spool
+/// the part is persisted first
+fn take_part(stream: &mut Stream<'_>) -> Result<StagedPart> {
+    if usize::from(stream.part_number) > MAX_PARTS_PER_UPLOAD {
+        return Err(anyhow!(
+            "the stream exceeds the maximum of {MAX_PARTS_PER_UPLOAD} parts of up to {}",
+            ByteSize(stream.part_size.size(stream.part_number))
+        ));
+    }
 
-        emit_stream_progress(stream.channel.as_ref(), StreamProgressEvent::ResetStaging);
+    if let (Some(spool), Staging::File(tmp_file)) = (&stream.spool, &stream.staging) {
+        spool
+            .stage(stream.part_number, tmp_file.path(), stream.count as u64)
+            .context("Error spooling part")?;
+    }
 
-        stream.etags.push(etag);
+    if let Some(source) = stream.source.as_mut() {
+        source
+            .checkpoint
+            .stage(stream.part_number, source.start, source.end - source.start)
+            .context("Error saving checkpoint")?;
+        source.start = source.end;
+    }
 
-        log::debug!(
-            "Part {} uploaded, total bytes: {}, etag: {}",
-            stream.part_number,
-            stream.count,
-            stream.etags.last().unwrap_or(&String::new())
-        );
+    let staging = Staging::new(
+        stream.staging.is_memory(),
+        &stream.upload_id,
+        &stream.tmp_dir,
+        stream.part_size.size(stream.part_number.saturating_add(1)),
+    )?;
 
-        // Reset for next part
-        stream.tmp_file = Builder::new()
-            .prefix(stream.upload_id)
-            .suffix(".s3m")
-            .tempfile_in(&stream.tmp_dir)?;
+    let body = match std::mem::replace(&mut stream.staging, staging) {
+        Staging::File(tmp_file) => PartBody::File(tmp_file),
+        Staging::Memory(buffer) => PartBody::Memory(buffer.freeze()),
+    };
 
-        stream.count = 0;
-        stream.part_number += 1;
-        stream.sha = Context::new(&SHA256);
-        stream.md5 = md5::Context::new();
-    }
-    Ok(())
+    let part = StagedPart {
+        s3: stream.s3.clone(),
+        key: stream.key.clone(),
+        upload_id: stream.upload_id.clone(),
+        part_number: stream.part_number,
+        body,
+        count: stream.count,
+        digest_sha: std::mem::replace(&mut stream.sha, Context::new(&SHA256)).finish(),
+        digest_md5: std::mem::replace(&mut stream.md5, md5::Context::new()).finalize(),
+        channel: stream.channel.clone(),
+        globals: RequestOptions {
+            throttle: stream.throttle.clone(),
+            retries: stream.retries,
+            ..RequestOptions::new()
+        },
+        concurrency: stream.pipeline.concurrency(),
+        checkpoint: stream
+            .source
+            .as_ref()
+            .map(|source| source.checkpoint.clone()),
+    };
+
+    // Reset for next part
+    stream.count = 0;
+    stream.part_number += 1;
+
+    Ok(part)
+}
+
+/// Hand the part to the pipeline, waiting while too many parts are in flight.
+/// With a spool a failure only stops sending: the remaining input keeps being
+/// captured.
+async fn send_part(stream: &mut Stream<'_>, part: StagedPart) -> Result<()> {
+    let spool = stream.spool.clone();
+
+    stream
+        .pipeline
+        .push(
+            part.part_number,
+            part.count as u64,
+            spool.as_deref(),
+            upload_staged_part(part, spool.clone()),
+        )
+        .await
 }
 
 /// Complete multipart upload
@@ -698,44 +989,57 @@ async fn complete_multipart_upload(
     Ok(response.e_tag)
 }
 
-/// Upload the final part
-async fn upload_final_part(
-    stream: &mut Stream<'_>,
-    key: &str,
-    upload_id: &str,
-    s3: &S3,
-    globals: &RequestOptions,
-) -> Result<String> {
-    let digest_sha = stream.sha.clone().finish();
-    let digest_md5 = stream.md5.clone().finalize();
+/// Send the final part, wait for all the parts and complete the multipart
+/// upload
+async fn finish_stream(mut stream: Stream<'_>) -> Result<String> {
+    let etags = flush_stream(&mut stream).await?;
 
-    emit_stream_progress(
-        stream.channel.as_ref(),
-        StreamProgressEvent::SendingStarted {
-            part_number: stream.part_number,
-            total_bytes: stream.count as u64,
-        },
-    );
+    complete_stream(&stream, etags).await
+}
 
-    let action = actions::StreamPart::new(
-        key,
-        stream.tmp_file.path(),
-        stream.part_number,
-        upload_id,
-        stream.count,
-        (digest_sha.as_ref(), digest_md5.as_ref()),
-        stream_sending_progress_callback(stream.channel.as_ref()),
-    );
+/// Send the last part and wait for every part of the stream, returning their
+/// `ETag`s in part order
+async fn flush_stream(stream: &mut Stream<'_>) -> Result<Vec<String>> {
+    let part = take_part(stream)?;
 
-    let etag = action.request(s3, globals).await.map_err(|e| {
-        emit_stream_progress(stream.channel.as_ref(), StreamProgressEvent::SendingStopped);
-        anyhow!("Failed to upload final part: {e}")
-    })?;
+    if let Some(spool) = &stream.spool {
+        spool.captured(part.part_number)?;
+    }
 
-    emit_stream_progress(
-        stream.channel.as_ref(),
-        StreamProgressEvent::Confirmed(stream.count as u64),
-    );
+    send_part(stream, part).await?;
+
+    // Close channel if it exists, the tasks hold their own sender
+    if let Some(sender) = stream.channel.take() {
+        drop(sender);
+    }
+
+    let etags = stream.pipeline.finish(stream.spool.as_deref()).await?;
+
+    if let Some(spool) = &stream.spool
+        && let Some(error) = spool.interrupted()
+    {
+        return Err(spool.resume_error(&error));
+    }
+
+    Ok(etags)
+}
+
+/// Complete the multipart upload of a flushed stream
+async fn complete_stream(stream: &Stream<'_>, etags: Vec<String>) -> Result<String> {
+    // a resumed checkpoint also holds the parts sent before
+    let etag = match &stream.source {
+        Some(source) => {
+            source
+                .checkpoint
+                .complete(stream.s3, &stream.key, &stream.upload_id)
+                .await?
+        }
+        None => complete_multipart_upload(stream.s3, &stream.key, &stream.upload_id, etags).await?,
+    };
+
+    if let Some(spool) = &stream.spool {
+        spool.complete(&etag)?;
+    }
 
     Ok(etag)
 }
@@ -751,25 +1055,33 @@ async fn upload_final_part(
 mod tests {
     use super::*;
     use crate::s3::{Credentials, Region, S3};
+    use crate::stream::spool::SpoolOptions;
     use aead_stream::EncryptorBE32;
     use indicatif::ProgressBar;
     use mockito::{Matcher, Server};
     use secrecy::SecretString;
-    use tokio::{sync::mpsc::unbounded_channel, time::timeout};
+    use tokio::{
+        sync::mpsc::unbounded_channel,
+        time::{Duration, timeout},
+    };
 
     #[tokio::test]
     async fn test_compress_decompress_roundtrip() {
         let original = b"the quick brown fox ".repeat(64);
         let second = b"second chunk payload".to_vec();
 
-        let c1 = compress_chunk(BytesMut::from(&original[..])).await.unwrap();
-        let c2 = compress_chunk(BytesMut::from(&second[..])).await.unwrap();
+        let c1 = compress_chunk(BytesMut::from(&original[..]), Compression::default())
+            .await
+            .unwrap();
+        let c2 = compress_chunk(BytesMut::from(&second[..]), Compression::default())
+            .await
+            .unwrap();
 
         // Two independent zstd frames concatenated, as chunked compression produces.
         let mut blob = c1;
         blob.extend_from_slice(&c2);
 
-        let out = decompress_chunk(blob, 1 << 20).await.unwrap();
+        let out = decompress_chunk(blob, Codec::Zstd, 1 << 20).await.unwrap();
 
         let mut expected = original.clone();
         expected.extend_from_slice(&second);
@@ -780,16 +1092,45 @@ mod tests {
     async fn test_decompress_chunk_respects_limit() {
         // ~100 KB of highly compressible data: small compressed, big decompressed.
         let original = b"A".repeat(100_000);
-        let compressed = compress_chunk(BytesMut::from(&original[..])).await.unwrap();
+        let compressed = compress_chunk(BytesMut::from(&original[..]), Compression::default())
+            .await
+            .unwrap();
 
         // A tight limit must reject the bomb...
-        assert!(decompress_chunk(compressed.clone(), 1_000).await.is_err());
+        assert!(
+            decompress_chunk(compressed.clone(), Codec::Zstd, 1_000)
+                .await
+                .is_err()
+        );
 
         // ...while a generous limit round-trips exactly.
-        let ok = decompress_chunk(compressed, 1_000_000).await.unwrap();
+        let ok = decompress_chunk(compressed, Codec::Zstd, 1_000_000)
+            .await
+            .unwrap();
         assert_eq!(ok, original);
     }
 
+    #[tokio::test]
+    async fn test_decompress_chunk_every_codec() {
+        let original = b"the quick brown fox ".repeat(64);
+
+        for codec in [Codec::Zstd, Codec::Gzip, Codec::Lz4, Codec::Xz] {
+            let mut blob = Vec::new();
+            for _ in 0..2 {
+                let frame = compress_chunk(BytesMut::from(&original[..]), Compression::new(codec))
+                    .await
+                    .unwrap();
+                blob.extend_from_slice(&frame);
+            }
+
+            let out = decompress_chunk(blob.clone(), codec, 1 << 20)
+                .await
+                .unwrap();
+            assert_eq!(out, original.repeat(2), "{codec}");
+            assert!(decompress_chunk(blob, codec, 100).await.is_err(), "{codec}");
+        }
+    }
+
     #[test]
     fn test_encrypt_decrypt_roundtrip() {
         let key = SecretString::new("0123456789abcdef0123456789abcdef".into());
@@ -838,19 +1179,25 @@ mod tests {
 
     #[test]
     fn test_get_key() {
+        let zst = Some(Codec::Zstd);
         let test_cases = vec![
-            ("test", false, false, "test"),
-            ("test", true, false, "test.zst"),
-            ("test", false, true, "test.enc"),
-            ("test", true, true, "test.zst.enc"),
-            ("test.txt", false, false, "test.txt"),
-            ("test.txt", true, false, "test.txt.zst"),
-            ("test.txt", false, true, "test.txt.enc"),
-            ("test.txt", true, true, "test.txt.zst.enc"),
-            ("test.ZST", false, false, "test.ZST"),
-            ("test.ZST", true, false, "test.ZST.zst"),
-            ("test.ZST", false, true, "test.ZST.enc"),
-            ("test.ZST", true, true, "test.ZST.zst.enc"),
+            ("test", None, false, "test"),
+            ("test", zst, false, "test.zst"),
+            ("test", None, true, "test.enc"),
+            ("test", zst, true, "test.zst.enc"),
+            ("test.txt", None, false, "test.txt"),
+            ("test.txt", zst, false, "test.txt.zst"),
+            ("test.txt", None, true, "test.txt.enc"),
+            ("test.txt", zst, true, "test.txt.zst.enc"),
+            ("test.ZST", None, false, "test.ZST"),
+            ("test.ZST", zst, false, "test.ZST.zst"),
+            ("test.ZST", None, true, "test.ZST.enc"),
+            ("test.ZST", zst, true, "test.ZST.zst.enc"),
+            ("test.zst", zst, false, "test.zst"),
+            ("test.zst.enc", zst, true, "test.zst.enc"),
+            ("test.sql", Some(Codec::Gzip), false, "test.sql.gz"),
+            ("test.sql", Some(Codec::Lz4), true, "test.sql.lz4.enc"),
+            ("test.sql.xz", Some(Codec::Xz), false, "test.sql.xz"),
         ];
         for (key, compress, encrypt, expected) in test_cases {
             assert_eq!(get_key(key, compress, encrypt), expected);
@@ -869,22 +1216,27 @@ mod tests {
             false,
         );
 
-        let part = Stream {
-            tmp_file: NamedTempFile::new().unwrap(),
+        let mut part = Stream {
+            staging: Staging::File(NamedTempFile::new().unwrap()),
             count: 0,
-            etags: Vec::new(),
-            key: "test",
+            part_size: PartSize::new(16),
+            pipeline: Pipeline::new(1, u64::MAX),
+            key: "test".to_string(),
             part_number: 1,
             s3: &s3,
-            upload_id: "test",
+            upload_id: "test".to_string(),
             sha: ring::digest::Context::new(&ring::digest::SHA256),
             md5: md5::Context::new(),
             channel: None,
             tmp_dir: PathBuf::new(),
             throttle: None,
             retries: 1,
+            spool: None,
+            source: None,
         };
 
+        let part = take_part(&mut part).unwrap();
+
         let rt = tokio::runtime::Runtime::new().unwrap();
         let result = rt.block_on(try_stream_part(&part));
         assert!(result.is_err());
@@ -899,6 +1251,25 @@ mod tests {
         assert_eq!(&header[1..], &nonce);
     }
 
+    #[test]
+    fn test_encrypt_chunk_at() {
+        let key = secrecy::SecretString::new("0123456789abcdef0123456789abcdef".into());
+        let (cipher, nonce) = init_encryption(&key).unwrap();
+        let mut encryptor: EncryptorBE32<ChaCha20Poly1305> =
+            EncryptorBE32::from_aead(cipher_from_key(&key).unwrap(), (&nonce).into());
+        let stream: StreamBE32<ChaCha20Poly1305> =
+            aead_stream::NewStream::from_aead(cipher, (&nonce).into());
+
+        // chunk 2 encrypted directly is chunk 2 of the encryptor
+        let first = encrypt_chunk(&mut encryptor, b"one").unwrap();
+        encrypt_chunk(&mut encryptor, b"two").unwrap();
+        let third = encrypt_chunk(&mut encryptor, b"three").unwrap();
+
+        assert_eq!(encrypt_chunk_at(&stream, 0, b"one").unwrap(), first);
+        assert_eq!(encrypt_chunk_at(&stream, 2, b"three").unwrap(), third);
+        assert_ne!(encrypt_chunk_at(&stream, 1, b"three").unwrap(), third);
+    }
+
     #[test]
     fn test_encrypt_chunk() {
         let key = secrecy::SecretString::new("0123456789abcdef0123456789abcdef".into());
@@ -944,26 +1315,32 @@ mod tests {
         );
 
         let mut stream = Stream {
-            tmp_file: NamedTempFile::new().unwrap(),
+            staging: Staging::File(NamedTempFile::new().unwrap()),
             count: 0,
-            etags: Vec::new(),
-            key: "test",
+            part_size: PartSize::new(16),
+            pipeline: Pipeline::new(1, u64::MAX),
+            key: "test".to_string(),
             part_number: 1,
             s3: &s3,
-            upload_id: "test",
+            upload_id: "test".to_string(),
             sha: Context::new(&SHA256),
             md5: md5::Context::new(),
             channel: None,
             tmp_dir: PathBuf::new(),
             throttle: None,
             retries: 1,
+            spool: None,
+            source: None,
         };
 
         let data = b"Hello, world!";
         write_to_stream(&mut stream, data).unwrap();
 
         assert_eq!(stream.count, data.len());
-        assert!(stream.tmp_file.as_file().metadata().unwrap().len() > 0);
+        let Staging::File(tmp_file) = &stream.staging else {
+            panic!("staged in memory");
+        };
+        assert!(tmp_file.as_file().metadata().unwrap().len() > 0);
     }
 
     #[tokio::test]
@@ -974,19 +1351,22 @@ mod tests {
             spawn_stream_progress_task(receiver, ProgressBar::hidden(), ProgressBar::hidden());
 
         let mut stream = Stream {
-            tmp_file: NamedTempFile::new().unwrap(),
+            staging: Staging::File(NamedTempFile::new().unwrap()),
             count: 0,
-            etags: Vec::new(),
-            key: "test",
+            part_size: PartSize::new(16),
+            pipeline: Pipeline::new(1, u64::MAX),
+            key: "test".to_string(),
             part_number: 1,
             s3: &s3,
-            upload_id: "test",
+            upload_id: "test".to_string(),
             sha: Context::new(&SHA256),
             md5: md5::Context::new(),
             channel: Some(sender),
             tmp_dir: PathBuf::new(),
             throttle: None,
             retries: 1,
+            spool: None,
+            source: None,
         };
 
         let data = b"Hello, world!";
@@ -1001,9 +1381,9 @@ mod tests {
         assert_eq!(progress.staged_current, data.len() as u64);
         assert_eq!(progress.staged_total, data.len() as u64);
         assert_eq!(progress.confirmed_total, 0);
-        assert_eq!(progress.sending_current, 0);
-        assert_eq!(progress.sending_total, 0);
-        assert_eq!(progress.sending_part, None);
+        assert_eq!(progress.sending_current(), 0);
+        assert_eq!(progress.sending_total(), 0);
+        assert!(progress.sending.is_empty());
     }
 
     #[test]
@@ -1025,9 +1405,11 @@ mod tests {
             throttle: None,
             retries: 1,
             compress: false,
+            compression: Compression::default(),
             encrypt: false,
             enc_key: None,
             object_lock: None,
+            tagging: None,
         };
 
         let stream = create_initial_stream(InitialStreamParams {
@@ -1038,6 +1420,12 @@ mod tests {
             progress_sender: None,
             globals: &globals,
             header_data: None,
+            spool: None,
+            part_size: PartSize::new(16),
+            pipeline: Pipeline::new(1, u64::MAX),
+            memory: false,
+            part_number: 1,
+            source: None,
         })
         .unwrap();
 
@@ -1056,9 +1444,11 @@ mod tests {
             throttle: None,
             retries: 1,
             compress: false,
+            compression: Compression::default(),
             encrypt: true,
             enc_key: None,
             object_lock: None,
+            tagging: None,
         };
         let header = create_nonce_header(&[1, 2, 3, 4, 5, 6, 7]);
         let tmp_dir = PathBuf::new();
@@ -1074,6 +1464,12 @@ mod tests {
             progress_sender: Some(sender),
             globals: &globals,
             header_data: Some(&header),
+            spool: None,
+            part_size: PartSize::new(16),
+            pipeline: Pipeline::new(1, u64::MAX),
+            memory: false,
+            part_number: 1,
+            source: None,
         })
         .unwrap();
 
@@ -1088,9 +1484,9 @@ mod tests {
         assert_eq!(progress.staged_current, header.len() as u64);
         assert_eq!(progress.staged_total, header.len() as u64);
         assert_eq!(progress.confirmed_total, 0);
-        assert_eq!(progress.sending_current, 0);
-        assert_eq!(progress.sending_total, 0);
-        assert_eq!(progress.sending_part, None);
+        assert_eq!(progress.sending_current(), 0);
+        assert_eq!(progress.sending_total(), 0);
+        assert!(progress.sending.is_empty());
     }
 
     #[test]
@@ -1098,7 +1494,7 @@ mod tests {
         let data = BytesMut::from("Hello, world!");
         let compressed = tokio::runtime::Runtime::new()
             .unwrap()
-            .block_on(compress_chunk(data))
+            .block_on(compress_chunk(data, Compression::default()))
             .unwrap();
 
         assert!(
@@ -1131,9 +1527,11 @@ mod tests {
             throttle: None,
             retries: 1,
             compress: false,
+            compression: Compression::default(),
             encrypt: false,
             enc_key: None,
             object_lock: None,
+            tagging: None,
         };
 
         let stream = create_initial_stream(InitialStreamParams {
@@ -1144,17 +1542,24 @@ mod tests {
             progress_sender: None,
             globals: &globals,
             header_data: None,
+            spool: None,
+            part_size: PartSize::new(1024), // 1KB for testing
+            pipeline: Pipeline::new(1, u64::MAX),
+            memory: false,
+            part_number: 1,
+            source: None,
         })
         .unwrap();
 
-        let buffer_size = 1024; // 1KB for testing
         let mut stream = stream;
         let data = vec![0; 1023];
 
         write_to_stream(&mut stream, &data).unwrap();
-        let result = maybe_upload_part(&mut stream, buffer_size).await;
+        let result = maybe_upload_part(&mut stream).await;
 
         println!("Result: {result:?}");
+        assert!(result.is_ok());
+        assert_eq!(stream.part_number, 1);
     }
 
     #[tokio::test]
@@ -1189,10 +1594,20 @@ mod tests {
             })
             .unwrap();
         sender
-            .send(StreamProgressEvent::SendingProgress(4))
+            .send(StreamProgressEvent::SendingProgress {
+                part_number: 2,
+                bytes: 4,
+            })
+            .unwrap();
+        sender
+            .send(StreamProgressEvent::Confirmed {
+                part_number: 2,
+                bytes: 9,
+            })
+            .unwrap();
+        sender
+            .send(StreamProgressEvent::ResetStaging { part_size: 16 })
             .unwrap();
-        sender.send(StreamProgressEvent::Confirmed(9)).unwrap();
-        sender.send(StreamProgressEvent::ResetStaging).unwrap();
         drop(sender);
 
         let progress = timeout(Duration::from_secs(1), handle)
@@ -1203,9 +1618,9 @@ mod tests {
         assert_eq!(progress.staged_current, 0);
         assert_eq!(progress.staged_total, 9);
         assert_eq!(progress.confirmed_total, 9);
-        assert_eq!(progress.sending_current, 0);
-        assert_eq!(progress.sending_total, 0);
-        assert_eq!(progress.sending_part, None);
+        assert_eq!(progress.sending_current(), 0);
+        assert_eq!(progress.sending_total(), 0);
+        assert!(progress.sending.is_empty());
     }
 
     // Comprehensive tests for complete_multipart_upload to prevent regressions
@@ -1265,25 +1680,30 @@ mod tests {
             spawn_stream_progress_task(receiver, ProgressBar::hidden(), ProgressBar::hidden());
 
         let mut stream = Stream {
-            tmp_file: NamedTempFile::new().unwrap(),
+            staging: Staging::File(NamedTempFile::new().unwrap()),
             count: 0,
-            etags: Vec::new(),
-            key: "key",
+            part_size: PartSize::new(16),
+            pipeline: Pipeline::new(1, u64::MAX),
+            key: "key".to_string(),
             part_number: 1,
             s3: &s3,
-            upload_id: "upload-id",
+            upload_id: "upload-id".to_string(),
             sha: Context::new(&SHA256),
             md5: md5::Context::new(),
             channel: Some(sender),
             tmp_dir: PathBuf::new(),
             throttle: None,
             retries: 2,
+            spool: None,
+            source: None,
         };
 
         let data = b"retry me once";
         write_to_stream(&mut stream, data).unwrap();
 
-        let etag = try_stream_part(&stream).await.unwrap();
+        let part = take_part(&mut stream).unwrap();
+        let etag = try_stream_part(&part).await.unwrap();
+        drop(part);
         assert_eq!(etag, "\"retry-ok\"");
 
         drop(stream.channel.take());
@@ -1296,9 +1716,9 @@ mod tests {
         assert_eq!(progress.staged_current, data.len() as u64);
         assert_eq!(progress.staged_total, data.len() as u64);
         assert_eq!(progress.confirmed_total, data.len() as u64);
-        assert_eq!(progress.sending_current, 0);
-        assert_eq!(progress.sending_total, 0);
-        assert_eq!(progress.sending_part, None);
+        assert_eq!(progress.sending_current(), 0);
+        assert_eq!(progress.sending_total(), 0);
+        assert!(progress.sending.is_empty());
     }
 
     #[tokio::test]
@@ -1324,24 +1744,28 @@ mod tests {
             spawn_stream_progress_task(receiver, ProgressBar::hidden(), ProgressBar::hidden());
 
         let mut stream = Stream {
-            tmp_file: NamedTempFile::new().unwrap(),
+            staging: Staging::File(NamedTempFile::new().unwrap()),
             count: 0,
-            etags: Vec::new(),
-            key: "key",
+            part_size: PartSize::new(16),
+            pipeline: Pipeline::new(1, u64::MAX),
+            key: "key".to_string(),
             part_number: 1,
             s3: &s3,
-            upload_id: "upload-id",
+            upload_id: "upload-id".to_string(),
             sha: Context::new(&SHA256),
             md5: md5::Context::new(),
             channel: Some(sender),
             tmp_dir: PathBuf::new(),
             throttle: None,
             retries: 1,
+            spool: None,
+            source: None,
         };
 
         let data = vec![0_u8; 32];
         write_to_stream(&mut stream, &data).unwrap();
-        maybe_upload_part(&mut stream, 16).await.unwrap();
+        maybe_upload_part(&mut stream).await.unwrap();
+        let etags = stream.pipeline.finish(None).await.unwrap();
         drop(stream.channel.take());
 
         let progress = timeout(Duration::from_secs(1), handle)
@@ -1352,12 +1776,296 @@ mod tests {
         assert_eq!(progress.staged_current, 0);
         assert_eq!(progress.staged_total, data.len() as u64);
         assert_eq!(progress.confirmed_total, data.len() as u64);
-        assert_eq!(progress.sending_current, 0);
-        assert_eq!(progress.sending_total, 0);
-        assert_eq!(progress.sending_part, None);
+        assert_eq!(progress.sending_current(), 0);
+        assert_eq!(progress.sending_total(), 0);
+        assert!(progress.sending.is_empty());
         assert_eq!(stream.count, 0);
         assert_eq!(stream.part_number, 2);
-        assert_eq!(stream.etags.len(), 1);
+        assert_eq!(etags, vec!["\"part-ok\""]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_parts_sent_in_parallel() {
+        let mut server = Server::new_async().await;
+
+        // later parts are answered first
+        let mut parts = Vec::new();
+        for number in 1..=4_u64 {
+            parts.push(
+                server
+                    .mock("PUT", "/bucket/key")
+                    .match_query(Matcher::UrlEncoded("partNumber".into(), number.to_string()))
+                    .with_status(200)
+                    .with_header("ETag", &format!("\"p{number}\""))
+                    .with_chunked_body(move |w| {
+                        std::thread::sleep(Duration::from_millis((5 - number) * 30));
+                        w.write_all(b"")
+                    })
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+        let complete = server
+            .mock("POST", "/bucket/key")
+            .match_query(Matcher::UrlEncoded("uploadId".into(), "upload-id".into()))
+            .match_body(Matcher::Regex("(?s)p1.*p2.*p3.*p4".into()))
+            .with_status(200)
+            .with_body(
+                "<CompleteMultipartUploadResult><Location>l</Location><Bucket>bucket</Bucket><Key>key</Key><ETag>\"final\"</ETag></CompleteMultipartUploadResult>",
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let s3 = create_mock_s3(server.url(), Some("bucket"));
+        let dir = tempfile::tempdir().unwrap();
+        let globals = RequestOptions {
+            retries: 1,
+            ..RequestOptions::new()
+        };
+        let (sender, receiver) = unbounded_channel();
+        let handle =
+            spawn_stream_progress_task(receiver, ProgressBar::hidden(), ProgressBar::hidden());
+
+        let mut stream = create_initial_stream(InitialStreamParams {
+            upload_id: "upload-id",
+            tmp_dir: dir.path(),
+            key: "key",
+            s3: &s3,
+            progress_sender: Some(sender),
+            globals: &globals,
+            header_data: None,
+            spool: None,
+            part_size: PartSize::new(16),
+            pipeline: Pipeline::new(4, u64::MAX),
+            memory: false,
+            part_number: 1,
+            source: None,
+        })
+        .unwrap();
+
+        for _ in 0..3 {
+            write_to_stream(&mut stream, &[0_u8; 16]).unwrap();
+            maybe_upload_part(&mut stream).await.unwrap();
+        }
+        // the producer was not blocked by the parts being sent
+        assert_eq!(stream.pipeline.len(), 3);
+
+        write_to_stream(&mut stream, b"tail").unwrap();
+        assert_eq!(finish_stream(stream).await.unwrap(), "\"final\"");
+
+        for part in parts {
+            part.assert_async().await;
+        }
+        complete.assert_async().await;
+
+        let progress = timeout(Duration::from_secs(1), handle)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(progress.staged_total, 52);
+        assert_eq!(progress.confirmed_total, 52);
+        assert!(progress.sending.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stream_staged_in_memory() {
+        let mut server = Server::new_async().await;
+        let parts = server
+            .mock("PUT", "/bucket/key")
+            .match_query(Matcher::Any)
+            .match_body(Matcher::Regex("^(a{16}|tail)$".into()))
+            .with_status(200)
+            .with_header("ETag", "\"memory\"")
+            .expect(3)
+            .create_async()
+            .await;
+        let complete = server
+            .mock("POST", "/bucket/key")
+            .match_query(Matcher::UrlEncoded("uploadId".into(), "upload-id".into()))
+            .with_status(200)
+            .with_body(
+                "<CompleteMultipartUploadResult><Location>l</Location><Bucket>bucket</Bucket><Key>key</Key><ETag>\"final\"</ETag></CompleteMultipartUploadResult>",
+            )
+            .create_async()
+            .await;
+
+        let s3 = create_mock_s3(server.url(), Some("bucket"));
+        let globals = RequestOptions {
+            retries: 1,
+            ..RequestOptions::new()
+        };
+
+        // nothing is written to tmp_dir
+        let mut stream = create_initial_stream(InitialStreamParams {
+            upload_id: "upload-id",
+            tmp_dir: Path::new("/nonexistent"),
+            key: "key",
+            s3: &s3,
+            progress_sender: None,
+            globals: &globals,
+            header_data: None,
+            spool: None,
+            part_size: PartSize::new(16),
+            pipeline: Pipeline::new(2, u64::MAX),
+            memory: true,
+            part_number: 1,
+            source: None,
+        })
+        .unwrap();
+
+        for _ in 0..2 {
+            write_to_stream(&mut stream, &[b'a'; 16]).unwrap();
+            maybe_upload_part(&mut stream).await.unwrap();
+        }
+        write_to_stream(&mut stream, b"tail").unwrap();
+
+        assert_eq!(finish_stream(stream).await.unwrap(), "\"final\"");
+        parts.assert_async().await;
+        complete.assert_async().await;
+    }
+
+    #[test]
+    fn test_staging_limits() {
+        let part_size = PartSize::default();
+
+        // on disk
+        assert_eq!(
+            staging_limits(part_size, 1_024, None).unwrap(),
+            (part_size, 1_024)
+        );
+
+        // half of the memory for the staged part, half for the parts being sent
+        let (limited, max_staging) =
+            staging_limits(part_size, u64::MAX, Some(64 * 1_024 * 1_024)).unwrap();
+        assert_eq!(max_staging, 32 * 1_024 * 1_024);
+        assert_eq!(limited.size(10_000), 32 * 1_024 * 1_024);
+
+        assert!(staging_limits(part_size, u64::MAX, Some(MIN_PART_SIZE_BYTES)).is_err());
+
+        // spooled parts are linked from a file
+        let s3 = create_test_s3();
+        let dir = tempfile::tempdir().unwrap();
+        let globals = RequestOptions::new();
+        let spool = Spool::create(
+            &SpoolOptions {
+                s3m_dir: dir.path().to_path_buf(),
+                host: "s3".to_string(),
+            },
+            &s3,
+            "key",
+            "upload-id",
+            &globals,
+        )
+        .unwrap();
+        let result = create_initial_stream(InitialStreamParams {
+            upload_id: "upload-id",
+            tmp_dir: dir.path(),
+            key: "key",
+            s3: &s3,
+            progress_sender: None,
+            globals: &globals,
+            header_data: None,
+            spool: Some(spool),
+            part_size,
+            pipeline: Pipeline::new(1, u64::MAX),
+            memory: true,
+            part_number: 1,
+            source: None,
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_update_stream_status_message() {
+        let pb = ProgressBar::hidden();
+        let mut state = StreamProgressState {
+            confirmed_total: 500,
+            ..StreamProgressState::default()
+        };
+
+        update_stream_status_message(&pb, &state);
+        assert_eq!(pb.message(), "confirmed 500 B");
+
+        state.sending.insert(3, (10, 100));
+        update_stream_status_message(&pb, &state);
+        assert_eq!(pb.message(), "sending part 3 10 B/100 B | confirmed 500 B");
+
+        state.sending.insert(2, (40, 100));
+        update_stream_status_message(&pb, &state);
+        assert_eq!(
+            pb.message(),
+            "sending parts 2, 3 50 B/200 B | confirmed 500 B"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_spooled_stream_captures_input_after_failure() {
+        let mut server = Server::new_async().await;
+        let upload = server
+            .mock("PUT", "/bucket/key")
+            .match_query(Matcher::Any)
+            .with_status(500)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let s3 = create_mock_s3(server.url(), Some("bucket"));
+        let globals = RequestOptions {
+            retries: 1,
+            ..RequestOptions::new()
+        };
+        let spool = Spool::create(
+            &SpoolOptions {
+                s3m_dir: dir.path().to_path_buf(),
+                host: "s3".to_string(),
+            },
+            &s3,
+            "key",
+            "upload-id",
+            &globals,
+        )
+        .unwrap();
+
+        let mut stream = create_initial_stream(InitialStreamParams {
+            upload_id: "upload-id",
+            tmp_dir: dir.path(),
+            key: "key",
+            s3: &s3,
+            progress_sender: None,
+            globals: &globals,
+            header_data: None,
+            spool: Some(spool),
+            part_size: PartSize::new(16),
+            pipeline: Pipeline::new(1, u64::MAX),
+            memory: false,
+            part_number: 1,
+            source: None,
+        })
+        .unwrap();
+
+        // part 1 fails, part 2 and the final part are only captured
+        for _ in 0..2 {
+            write_to_stream(&mut stream, &[0_u8; 32]).unwrap();
+            maybe_upload_part(&mut stream).await.unwrap();
+        }
+        write_to_stream(&mut stream, b"tail").unwrap();
+
+        let err = finish_stream(stream).await.unwrap_err().to_string();
+        assert!(err.contains("s3m streams resume"), "{err}");
+
+        upload.assert_async().await;
+
+        let entry = state::scan_streams(dir.path()).unwrap().pop().unwrap();
+        assert_eq!(entry.parts_pending, 3);
+        assert_eq!(entry.parts_uploaded, 0);
+
+        let db = db::Db::open_existing(&entry.state_dir, entry.metadata.unwrap().db_key).unwrap();
+        assert_eq!(db.captured().unwrap(), Some(3));
+        let spool = Spool::open(entry.id, db).unwrap();
+        assert_eq!(std::fs::read(spool.part_path(3)).unwrap(), b"tail");
     }
 
     #[tokio::test]
@@ -0,0 +1,154 @@
+//! Part sizes of streams whose size is unknown.
+//!
+//! Parts start small and double every [`GROWTH_INTERVAL`] parts, so a small
+//! dump only stages a few MiB while the 10,000 parts of a multipart upload
+//! still reach the 5 TB object limit: starting at 8 MiB, the parts add up to
+//! almost 8 TiB. A size hint raises the first part size with
+//! [`calculate_part_size`], growth still covers a hint that is too low.
+
+use crate::s3::{
+    limits::{MAX_OBJECT_SIZE_BYTES, MAX_PART_SIZE_BYTES, MAX_PARTS_PER_UPLOAD},
+    tools::calculate_part_size,
+};
+use anyhow::{Result, anyhow};
+
+/// First part size of a stream, 8 MiB
+pub const STREAM_PART_SIZE: u64 = 8 * 1_024 * 1_024;
+
+/// Parts sent before the part size doubles
+pub const GROWTH_INTERVAL: u16 = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartSize {
+    initial: u64,
+    max: u64,
+}
+
+impl Default for PartSize {
+    fn default() -> Self {
+        Self::new(STREAM_PART_SIZE)
+    }
+}
+
+impl PartSize {
+    #[must_use]
+    pub const fn new(initial: u64) -> Self {
+        Self {
+            initial,
+            max: MAX_PART_SIZE_BYTES,
+        }
+    }
+
+    /// Never grow parts above `max` bytes, this also limits the object size
+    /// to [`capacity`](Self::capacity)
+    #[must_use]
+    pub fn limit(self, max: u64) -> Self {
+        Self {
+            initial: self.initial.min(max),
+            max: self.max.min(max),
+        }
+    }
+
+    /// Part sizes for a stream of about `size_hint` bytes
+    ///
+    /// # Errors
+    /// Will return `Err` if the hint exceeds the 5 TB object limit
+    pub fn from_hint(size_hint: Option<u64>) -> Result<Self> {
+        let Some(size_hint) = size_hint else {
+            return Ok(Self::default());
+        };
+
+        if size_hint > MAX_OBJECT_SIZE_BYTES {
+            return Err(anyhow!(
+                "size hint {size_hint} exceeds the max object size of 5 TB"
+            ));
+        }
+
+        Ok(Self::new(calculate_part_size(size_hint, STREAM_PART_SIZE)?))
+    }
+
+    /// Size of part `part_number`, starting at 1
+    #[must_use]
+    pub fn size(&self, part_number: u16) -> u64 {
+        let doublings = u32::from(part_number.saturating_sub(1) / GROWTH_INTERVAL);
+
+        self.initial
+            .checked_shl(doublings)
+            .unwrap_or(u64::MAX)
+            .min(self.max)
+    }
+
+    /// Bytes the parts can hold within the 10,000 parts limit
+    #[must_use]
+    pub fn capacity(&self) -> u64 {
+        (1..=u16::try_from(MAX_PARTS_PER_UPLOAD).unwrap_or(u16::MAX))
+            .map(|part_number| self.size(part_number))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_size_growth() {
+        let part_size = PartSize::default();
+        assert_eq!(part_size.size(1), STREAM_PART_SIZE);
+        assert_eq!(part_size.size(1_000), STREAM_PART_SIZE);
+        assert_eq!(part_size.size(1_001), STREAM_PART_SIZE * 2);
+        assert_eq!(part_size.size(2_001), STREAM_PART_SIZE * 4);
+        assert_eq!(part_size.size(10_000), STREAM_PART_SIZE * 512);
+        assert!(part_size.size(10_000) <= MAX_PART_SIZE_BYTES);
+
+        // never above the max part size
+        assert_eq!(
+            PartSize::new(MAX_PART_SIZE_BYTES).size(5_000),
+            MAX_PART_SIZE_BYTES
+        );
+    }
+
+    #[test]
+    fn test_part_size_limit() {
+        let part_size = PartSize::default().limit(32 * 1_024 * 1_024);
+        assert_eq!(part_size.size(1), STREAM_PART_SIZE);
+        assert_eq!(part_size.size(2_001), 32 * 1_024 * 1_024);
+        assert_eq!(part_size.size(10_000), 32 * 1_024 * 1_024);
+        assert!(part_size.capacity() < MAX_OBJECT_SIZE_BYTES);
+
+        // smaller than the first part
+        let part_size = PartSize::default().limit(6 * 1_024 * 1_024);
+        assert_eq!(part_size.size(1), 6 * 1_024 * 1_024);
+    }
+
+    #[test]
+    fn test_part_size_reaches_max_object_size() {
+        assert!(PartSize::default().capacity() >= MAX_OBJECT_SIZE_BYTES);
+    }
+
+    #[test]
+    fn test_part_size_from_hint() {
+        assert_eq!(PartSize::from_hint(None).unwrap(), PartSize::default());
+
+        // small streams keep the first part size
+        let part_size = PartSize::from_hint(Some(3 * 1_024 * 1_024)).unwrap();
+        assert_eq!(part_size.size(1), STREAM_PART_SIZE);
+
+        // 1 TiB fits in 10,000 parts of 128 MiB
+        let part_size = PartSize::from_hint(Some(1_024 * 1_024 * 1_024 * 1_024)).unwrap();
+        assert_eq!(part_size.size(1), 128 * 1_024 * 1_024);
+
+        let part_size = PartSize::from_hint(Some(MAX_OBJECT_SIZE_BYTES)).unwrap();
+        assert!(part_size.size(1) <= MAX_PART_SIZE_BYTES);
+        assert!(part_size.capacity() >= MAX_OBJECT_SIZE_BYTES);
+
+        assert!(PartSize::from_hint(Some(MAX_OBJECT_SIZE_BYTES + 1)).is_err());
+    }
+}
@@ -0,0 +1,238 @@
+//! Parts of a stream uploaded while the next one is staged.
+//!
+//! Every staged part is handed to an upload task. Tasks run up to the adaptive
+//! [`Concurrency`] started at `-n`, and only while the staged bytes waiting for
+//! S3 fit in the staging budget; otherwise the producer waits for a task to
+//! finish. `ETag`s are kept by part number, so parts may complete in any order.
+
+use crate::stream::{concurrency::Concurrency, spool::Spool};
+use anyhow::{Result, anyhow};
+use std::{collections::BTreeMap, future::Future, sync::Arc};
+use tokio::task::JoinSet;
+
+/// Default budget of staged bytes waiting for S3, 2 GiB
+pub const DEFAULT_MAX_STAGING: u64 = 2 * 1_024 * 1_024 * 1_024;
+
+type PartResult = (u16, u64, Result<String>);
+
+#[derive(Debug)]
+pub struct Pipeline {
+    uploads: JoinSet<PartResult>,
+    concurrency: Arc<Concurrency>,
+    in_flight: u64,
+    max_staging: u64,
+    etags: BTreeMap<u16, String>,
+}
+
+impl Pipeline {
+    /// Up to `max_requests` parts (adapted to throttling) and `max_staging`
+    /// bytes in flight, at least one part is always sent
+    #[must_use]
+    pub fn new(max_requests: u8, max_staging: u64) -> Self {
+        Self {
+            uploads: JoinSet::new(),
+            concurrency: Arc::new(Concurrency::new(max_requests.into())),
+            in_flight: 0,
+            max_staging,
+            etags: BTreeMap::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn concurrency(&self) -> Arc<Concurrency> {
+        self.concurrency.clone()
+    }
+
+    /// Parts being sent
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.uploads.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.uploads.is_empty()
+    }
+
+    /// Bytes of the parts being sent
+    #[must_use]
+    pub const fn in_flight(&self) -> u64 {
+        self.in_flight
+    }
+
+    fn has_room(&self, bytes: u64) -> bool {
+        self.uploads.is_empty()
+            || (self.uploads.len() < self.concurrency.current()
+                && self.in_flight.saturating_add(bytes) <= self.max_staging)
+    }
+
+    /// Wait for room and spawn the `upload` of part `number` of `bytes`.
+    ///
+    /// With a spool, a failed part interrupts it instead of returning an
+    /// error, and once interrupted parts are no longer sent.
+    ///
+    /// # Errors
+    /// Will return `Err` if a previous part failed without a spool
+    pub async fn push<F>(
+        &mut self,
+        number: u16,
+        bytes: u64,
+        spool: Option<&Spool>,
+        upload: F,
+    ) -> Result<()>
+    where
+        F: Future<Output = Result<String>> + Send + 'static,
+    {
+        while !self.has_room(bytes) {
+            self.reap(spool).await?;
+        }
+
+        if spool.is_some_and(Spool::is_interrupted) {
+            return Ok(());
+        }
+
+        self.in_flight += bytes;
+        self.uploads
+            .spawn(async move { (number, bytes, upload.await) });
+
+        Ok(())
+    }
+
+    // wait for one part
+    async fn reap(&mut self, spool: Option<&Spool>) -> Result<()> {
+        let Some(joined) = self.uploads.join_next().await else {
+            return Ok(());
+        };
+
+        let (number, bytes, result) = joined.map_err(|e| anyhow!("Upload task failed: {e}"))?;
+
+        self.in_flight = self.in_flight.saturating_sub(bytes);
+
+        match (result, spool) {
+            (Ok(etag), _) => {
+                log::debug!("Part {number} uploaded, bytes: {bytes}, etag: {etag}");
+
+                self.etags.insert(number, etag);
+            }
+            (Err(e), Some(spool)) => spool.interrupt(&e),
+            (Err(e), None) => return Err(anyhow!("Error streaming part {number}: {e}")),
+        }
+
+        Ok(())
+    }
+
+    /// Wait for the remaining parts and return the `ETag`s ordered by part
+    /// number
+    ///
+    /// # Errors
+    /// Will return `Err` if a part failed without a spool
+    pub async fn finish(&mut self, spool: Option<&Spool>) -> Result<Vec<String>> {
+        while !self.uploads.is_empty() {
+            self.reap(spool).await?;
+        }
+
+        Ok(std::mem::take(&mut self.etags).into_values().collect())
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::time::{Duration, sleep};
+
+    #[tokio::test]
+    async fn test_pipeline_orders_etags() {
+        let mut pipeline = Pipeline::new(4, u64::MAX);
+
+        // later parts finish first
+        for number in 1..=4_u16 {
+            pipeline
+                .push(number, 1, None, async move {
+                    sleep(Duration::from_millis(u64::from(5 - number) * 20)).await;
+                    Ok(format!("etag{number}"))
+                })
+                .await
+                .unwrap();
+        }
+        assert_eq!(pipeline.len(), 4);
+
+        let etags = pipeline.finish(None).await.unwrap();
+        assert_eq!(etags, vec!["etag1", "etag2", "etag3", "etag4"]);
+        assert!(pipeline.is_empty());
+        assert_eq!(pipeline.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_limits() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let upload = |running: Arc<AtomicUsize>, peak: Arc<AtomicUsize>| async move {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            sleep(Duration::from_millis(20)).await;
+            running.fetch_sub(1, Ordering::SeqCst);
+            Ok(String::new())
+        };
+
+        // two requests
+        let mut pipeline = Pipeline::new(2, u64::MAX);
+        for number in 1..=6 {
+            pipeline
+                .push(number, 10, None, upload(running.clone(), peak.clone()))
+                .await
+                .unwrap();
+            assert!(pipeline.len() <= 2);
+        }
+        pipeline.finish(None).await.unwrap();
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+
+        // a budget of 25 bytes fits two parts of 10
+        peak.store(0, Ordering::SeqCst);
+        let mut pipeline = Pipeline::new(8, 25);
+        for number in 1..=6 {
+            pipeline
+                .push(number, 10, None, upload(running.clone(), peak.clone()))
+                .await
+                .unwrap();
+            assert!(pipeline.in_flight() <= 25);
+        }
+        pipeline.finish(None).await.unwrap();
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+
+        // a part larger than the budget is still sent alone
+        let mut pipeline = Pipeline::new(8, 5);
+        pipeline
+            .push(1, 10, None, async { Ok("etag".to_string()) })
+            .await
+            .unwrap();
+        assert_eq!(pipeline.finish(None).await.unwrap(), vec!["etag"]);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_error() {
+        let mut pipeline = Pipeline::new(1, u64::MAX);
+        pipeline
+            .push(1, 1, None, async { Err(anyhow!("connection reset")) })
+            .await
+            .unwrap();
+
+        // the failure is returned while waiting for room
+        let err = pipeline
+            .push(2, 1, None, async { Ok(String::new()) })
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("part 1: connection reset"),
+            "{err}"
+        );
+    }
+}
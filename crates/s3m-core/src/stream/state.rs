@@ -1,4 +1,5 @@
 use crate::stream::{
+    compression::Compression,
     db::{DB_PARTS, DB_UPLOADED},
     part::Part,
 };
@@ -18,7 +19,11 @@ const STALE_SECONDS: u64 = 60 * 60 * 24 * 7;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum StreamMode {
+    /// Parts read from `source_path` on resume, compressed and encrypted
+    /// again when `compress` or `encrypt` is set
     FileMultipart,
+    /// `--pipe` upload, parts kept in the spool of the state directory
+    PipeSpool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +44,10 @@ pub struct StreamMetadata {
     pub updated_at: Option<u64>,
     pub pipe: bool,
     pub compress: bool,
+    /// Codec and level of a compressed upload, zstd for states written
+    /// before codecs could be chosen
+    #[serde(default)]
+    pub compression: Compression,
     pub encrypt: bool,
     pub mode: StreamMode,
 }
@@ -307,7 +316,9 @@ fn infer_db_key(db: &sled::Db) -> Option<String> {
         .keys()
         .filter_map(Result::ok)
         .filter_map(|key| String::from_utf8(key.to_vec()).ok())
-        .filter(|key| !key.starts_with("etag "))
+        .filter(|key| {
+            !key.starts_with("etag ") && !key.starts_with("captured ") && !key.starts_with("nonce ")
+        })
         .collect::<Vec<_>>();
     keys.sort();
     keys.dedup();
@@ -411,6 +422,7 @@ mod tests {
                 updated_at: Some(now_secs()),
                 pipe: false,
                 compress: false,
+                compression: Compression::default(),
                 encrypt: false,
                 mode: StreamMode::FileMultipart,
             },
@@ -458,6 +470,7 @@ mod tests {
                 updated_at: Some(now_secs()),
                 pipe: false,
                 compress: false,
+                compression: Compression::default(),
                 encrypt: false,
                 mode: StreamMode::FileMultipart,
             },
@@ -522,6 +535,7 @@ mod tests {
                 updated_at: Some(now_secs()),
                 pipe: false,
                 compress: false,
+                compression: Compression::default(),
                 encrypt: false,
                 mode: StreamMode::FileMultipart,
             },
@@ -561,6 +575,7 @@ mod tests {
                 updated_at: Some(created_at),
                 pipe: false,
                 compress: false,
+                compression: Compression::default(),
                 encrypt: false,
                 mode: StreamMode::FileMultipart,
             },
@@ -32,7 +32,8 @@ pub async fn upload(request: UploadRequest<'_>) -> Result<String> {
         progress_sender,
         request.additional_checksum,
         request.globals.object_lock.clone(),
-    );
+    )
+    .tagging(request.globals.tagging.as_ref());
 
     let response = action.request(request.s3, request.globals).await?;
     let etag = &response.get("ETag").ok_or_else(|| anyhow!("no etag"))?;
@@ -1,15 +1,14 @@
 use crate::{
     progressbar::Bar,
     s3::RequestOptions,
-    s3::{S3, actions, checksum::Checksum},
-    stream::{db::Db, iterator::PartIterator, part::Part},
+    s3::{Error, S3, actions, checksum::Checksum},
+    stream::{concurrency::Concurrency, db::Db, iterator::PartIterator, part::Part},
 };
 use anyhow::{Result, anyhow};
 use futures::stream::{FuturesUnordered, StreamExt};
-use rkyv::{from_bytes, rancor::Error as RkyvError, to_bytes};
-use sled::transaction::{TransactionError, Transactional};
+use rkyv::{from_bytes, rancor::Error as RkyvError};
 use std::{collections::BTreeMap, path::Path};
-use tokio::time::{Duration, sleep};
+use tokio::time::sleep;
 
 pub struct MultipartUploadRequest<'a> {
     pub s3: &'a S3,
@@ -26,6 +25,17 @@ pub struct MultipartUploadRequest<'a> {
     pub globals: RequestOptions,
 }
 
+// shared by the parts of an upload
+struct PartUpload<'a> {
+    s3: &'a S3,
+    key: &'a str,
+    file: &'a Path,
+    uid: &'a str,
+    db: &'a Db,
+    globals: &'a RequestOptions,
+    concurrency: &'a Concurrency,
+}
+
 struct UploadPartRequest<'a> {
     s3: &'a S3,
     key: &'a str,
@@ -77,7 +87,8 @@ pub async fn upload_multipart(request: MultipartUploadRequest<'_>) -> Result<Str
             request.meta,
             request.additional_checksum.clone(),
             request.globals.object_lock.clone(),
-        );
+        )
+        .tagging(request.globals.tagging.as_ref());
 
         let response = action.request(request.s3).await?;
 
@@ -109,10 +120,25 @@ pub async fn upload_multipart(request: MultipartUploadRequest<'_>) -> Result<Str
 
     increment_progress_bar(&pb, db_uploaded.len() as u64 * request.chunk_size, None);
 
+    // starts at max_requests, lowered on SlowDown and raised while parts succeed
+    let concurrency = Concurrency::new(request.max_requests.into());
+
+    let upload = PartUpload {
+        s3: request.s3,
+        key: request.key,
+        file: request.file,
+        uid: &upload_id,
+        db: request.sdb,
+        globals: &request.globals,
+        concurrency: &concurrency,
+    };
+
     let mut tasks = FuturesUnordered::new();
 
     log::info!("Max concurrent requests: {}", request.max_requests);
 
+    show_concurrency(&pb, &concurrency);
+
     for part in db_parts
         .iter()
         .values()
@@ -124,23 +150,9 @@ pub async fn upload_multipart(request: MultipartUploadRequest<'_>) -> Result<Str
         log::info!("Task push part: {}", part.get_number());
 
         // spawn task (upload part)
-        tasks.push(upload_part(
-            request.s3,
-            request.key,
-            request.file,
-            &upload_id,
-            request.sdb,
-            part,
-            &request.globals,
-        ));
-
-        await_tasks(
-            &mut tasks,
-            &pb,
-            request.chunk_size,
-            request.max_requests.into(),
-        )
-        .await?;
+        tasks.push(upload_part(&upload, part));
+
+        await_tasks(&mut tasks, &pb, request.chunk_size, &concurrency).await?;
     }
 
     // wait for the remaining tasks
@@ -180,18 +192,19 @@ async fn await_tasks<T>(
     tasks: &mut FuturesUnordered<T>,
     pb: &Bar,
     chunk_size: u64,
-    max_requests: usize,
+    concurrency: &Concurrency,
 ) -> Result<()>
 where
     T: std::future::Future<Output = Result<usize>> + Send,
 {
     log::debug!("Running tasks: {}", tasks.len());
 
-    // limit to available parallelism - 2 or 1 (see tools::default_concurrency)
-    while tasks.len() >= max_requests {
+    // limit to the current concurrency, it changes as parts complete
+    while tasks.len() >= concurrency.current() {
         if let Some(r) = tasks.next().await {
             r.map_err(|e| anyhow!("{e}"))?;
             increment_progress_bar(pb, chunk_size, None);
+            show_concurrency(pb, concurrency);
         }
     }
 
@@ -216,6 +229,12 @@ where
     Ok(())
 }
 
+fn show_concurrency(pb: &Bar, concurrency: &Concurrency) {
+    if let Some(pb) = pb.progress.as_ref() {
+        pb.set_message(format!("[{} parallel]", concurrency.current()));
+    }
+}
+
 fn increment_progress_bar(pb: &Bar, chunk_size: u64, finish: Option<bool>) {
     if let Some(pb) = pb.progress.as_ref() {
         pb.inc(chunk_size);
@@ -226,17 +245,16 @@ fn increment_progress_bar(pb: &Bar, chunk_size: u64, finish: Option<bool>) {
     }
 }
 
-async fn upload_part(
-    s3: &S3,
-    key: &str,
-    file: &Path,
-    uid: &str,
-    db: &Db,
-    part: Part,
-    globals: &RequestOptions,
-) -> Result<usize> {
-    let unprocessed = db.db_parts()?;
-    let processed = db.db_uploaded()?;
+async fn upload_part(upload: &PartUpload<'_>, part: Part) -> Result<usize> {
+    let PartUpload {
+        s3,
+        key,
+        file,
+        uid,
+        db,
+        globals,
+        concurrency,
+    } = *upload;
 
     let mut additional_checksum = part.get_checksum();
 
@@ -245,14 +263,10 @@ async fn upload_part(
 
     let mut etag: String = String::new();
 
-    // Retry with exponential backoff
-    for attempt in 1..=globals.retries {
-        let backoff_time = 2u64.pow(attempt - 1);
-        if attempt > 1 {
-            log::warn!("Error uploading part: {part_number}, retrying in {backoff_time} seconds");
+    let policy = globals.retry_policy();
 
-            sleep(Duration::from_secs(backoff_time)).await;
-        }
+    for attempt in 1..=policy.attempts() {
+        let epoch = concurrency.epoch();
 
         match try_upload_part(UploadPartRequest {
             s3,
@@ -270,6 +284,8 @@ async fn upload_part(
             Ok(e) => {
                 etag = e;
 
+                concurrency.success();
+
                 log::info!(
                     "Uploaded part: {}, etag: {}{}",
                     part_number,
@@ -288,15 +304,30 @@ async fn upload_part(
                     "Error uploading part: {}, attempt {}/{} failed: {}",
                     part.get_number(),
                     attempt,
-                    globals.retries,
+                    policy.attempts(),
                     e
                 );
 
-                // Increment attempt after an error
-                if attempt == globals.retries {
-                    // If it's the last attempt, return the error without incrementing attempt
+                if let Some(error) = e.downcast_ref::<Error>() {
+                    concurrency.failure(epoch, error);
+                }
+
+                if attempt == policy.attempts() {
                     return Err(e);
                 }
+
+                // exponential backoff with jitter, or the Retry-After of SlowDown
+                let delay = policy.delay(
+                    attempt,
+                    e.downcast_ref::<Error>().and_then(Error::retry_after),
+                );
+
+                log::warn!(
+                    "Error uploading part: {part_number}, retrying in {:.1} seconds",
+                    delay.as_secs_f64()
+                );
+
+                sleep(delay).await;
             }
         }
     }
@@ -304,20 +335,8 @@ async fn upload_part(
     // update part with the etag and checksum if any
     let part = part.set_etag(etag).set_checksum(additional_checksum);
 
-    let rkyv_part = to_bytes::<RkyvError>(&part)?;
-
     // move part to uploaded
-    (&unprocessed, &processed)
-        .transaction(|(unprocessed, processed)| {
-            unprocessed.remove(&part_number.to_be_bytes())?;
-            processed.insert(&part_number.to_be_bytes(), rkyv_part.as_slice())?;
-            Ok(())
-        })
-        .map_err(|err| match err {
-            TransactionError::Abort(err) | TransactionError::Storage(err) => err,
-        })?;
-
-    db.flush()
+    db.complete_part(&part)
 }
 
 async fn try_upload_part(request: UploadPartRequest<'_>) -> Result<String> {
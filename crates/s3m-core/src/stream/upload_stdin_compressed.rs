@@ -1,82 +1,47 @@
-use crate::{
-    s3::RequestOptions,
-    s3::S3,
-    stream::{
-        InitialStreamParams, STDIN_BUFFER_SIZE, Stream, complete_multipart_upload, compress_chunk,
-        create_initial_stream, get_key, initiate_multipart_upload, maybe_upload_part,
-        setup_stream_progress, upload_final_part, write_to_stream,
-    },
+use crate::stream::{
+    StdinStreamUpload, compress_chunk, compression::METADATA_HEADER, fanout::FanOut,
+    transform::read_chunk,
 };
 use anyhow::{Result, anyhow};
-use futures::stream::TryStreamExt;
-use std::{collections::BTreeMap, path::PathBuf};
 use tokio::io::stdin;
-use tokio_util::codec::{BytesCodec, FramedRead};
 
-/// Read from STDIN, compress the data using zstd, and upload in chunks
+/// Read from STDIN in chunks of [`CHUNK_SIZE`](crate::stream::transform::CHUNK_SIZE),
+/// compress them with the codec of the request, and upload to every
+/// destination
 /// # Errors
 /// Will return an error if the upload fails
 pub async fn stream_stdin_compressed(
-    s3: &S3,
-    object_key: &str,
-    acl: Option<String>,
-    meta: Option<BTreeMap<String, String>>,
-    quiet: bool,
-    tmp_dir: PathBuf,
-    globals: RequestOptions,
-) -> Result<String> {
-    // use .zst extension
-    let key = get_key(object_key, globals.compress, globals.encrypt);
-
-    let mut meta = meta.unwrap_or_default();
-    meta.insert("Content-Type".to_string(), "application/zstd".to_string());
-
-    // S3 setup
-    let upload_id =
-        initiate_multipart_upload(s3, &key, acl, meta, globals.object_lock.clone()).await?;
-
-    let progress_sender = setup_stream_progress(quiet).await;
-
-    // Create initial stream
-    let first_stream: Stream = create_initial_stream(InitialStreamParams {
-        upload_id: &upload_id,
-        tmp_dir: &tmp_dir,
-        key: &key,
-        s3,
-        progress_sender,
-        globals: &globals,
-        header_data: None,
-    })?;
-
-    let mut stream = FramedRead::new(stdin(), BytesCodec::new())
-        .map_err(|e| anyhow!("Error reading STDIN chunk: {e}"))
-        .try_fold(
-            first_stream,
-            |mut current_upload_state_acc, chunk| async move {
-                // Compress the current chunk
-                let data = compress_chunk(chunk).await?;
-
-                // Write the compressed chunk to our internal buffer/temp file
-                write_to_stream(&mut current_upload_state_acc, &data)
-                    .map_err(|e| anyhow!("Error writing compressed chunk to stream: {e}"))?;
-
-                // Check if a part needs to be uploaded to S3
-                maybe_upload_part(&mut current_upload_state_acc, STDIN_BUFFER_SIZE).await?;
-
-                Ok(current_upload_state_acc) // Return updated accumulator
-            },
-        )
-        .await?;
-
-    // Upload final part and complete multipart upload
-    let final_etag = upload_final_part(&mut stream, &key, &upload_id, s3, &globals).await?;
-
-    stream.etags.push(final_etag);
-
-    // Close channel if it exists
-    if let Some(sender) = stream.channel.take() {
-        drop(sender);
+    mut request: StdinStreamUpload<'_>,
+) -> Result<Vec<Result<String>>> {
+    let compression = request.globals.compression;
+
+    let mut meta = request.meta.take().unwrap_or_default();
+    meta.insert(
+        "Content-Type".to_string(),
+        compression.codec.content_type().to_string(),
+    );
+    meta.insert(METADATA_HEADER.to_string(), compression.codec.to_string());
+    request.meta = Some(meta);
+
+    // S3 setup, use the extension of the codec
+    let mut fan_out = FanOut::start(request, None).await?;
+    let mut input = stdin();
+
+    loop {
+        let chunk = read_chunk(&mut input)
+            .await
+            .map_err(|e| anyhow!("Error reading STDIN chunk: {e}"))?;
+        if chunk.is_empty() {
+            break;
+        }
+
+        // Compress the current chunk
+        let data = compress_chunk(chunk, compression).await?;
+
+        // Write the compressed chunk, parts are uploaded once full
+        fan_out.write(&data).await?;
     }
 
-    complete_multipart_upload(s3, &key, &upload_id, stream.etags).await
+    // Upload final parts and complete the multipart uploads
+    fan_out.finish().await
 }
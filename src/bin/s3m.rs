@@ -5,11 +5,18 @@ use s3m::cli::{actions, actions::Action, start};
 async fn main() -> Result<()> {
     let (s3, action, globals) = start()?;
 
+    // fetch the credentials of the host provider, if any
+    s3.refresh_credentials().await?;
+
     match action {
         Action::ACL { .. } => {
             actions::acl::handle(&s3, action).await?;
         }
 
+        Action::CopyObject { .. } => {
+            actions::object_copy::handle(&s3, action, globals).await?;
+        }
+
         Action::CreateBucket { .. } => {
             actions::bucket::handle(&s3, action).await?;
         }
@@ -49,6 +56,20 @@ async fn main() -> Result<()> {
         Action::ObjectLockGet { .. } | Action::ObjectLockSet(_) => {
             actions::object_lock::handle(&s3, action).await?;
         }
+
+        Action::ObjectTagGet { .. }
+        | Action::ObjectTagSet { .. }
+        | Action::ObjectTagDelete { .. } => {
+            actions::object_tag::handle(&s3, action).await?;
+        }
+
+        Action::LifecycleGet { .. } | Action::LifecycleSet { .. } | Action::LifecycleDelete => {
+            actions::lifecycle::handle(&s3, action).await?;
+        }
+
+        Action::VersioningGet { .. } | Action::VersioningSet { .. } => {
+            actions::versioning::handle(&s3, action).await?;
+        }
     }
 
     Ok(())
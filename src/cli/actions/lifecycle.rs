@@ -0,0 +1,234 @@
+use crate::{
+    cli::actions::Action,
+    s3::{
+        Error, S3,
+        actions::{
+            DeleteBucketLifecycle, GetBucketLifecycleConfiguration, PutBucketLifecycleConfiguration,
+        },
+        responses::{LifecycleFilter, LifecycleRule},
+    },
+};
+use anyhow::Result;
+use serde_json::json;
+
+/// `true` when the bucket simply has no lifecycle rules: S3 answers
+/// `GetBucketLifecycleConfiguration` with a 404 `NoSuchLifecycleConfiguration`.
+fn is_unset(e: &Error) -> bool {
+    e.is_not_found() || e.code() == Some("NoSuchLifecycleConfiguration")
+}
+
+/// # Errors
+/// Will return an error if the action fails
+pub async fn handle(s3: &S3, action: Action) -> Result<()> {
+    match action {
+        Action::LifecycleGet { json } => {
+            let rules = match GetBucketLifecycleConfiguration::new().request(s3).await {
+                Ok(config) => config.rules,
+                Err(e) if is_unset(&e) => Vec::new(),
+                Err(e) => return Err(e.into()),
+            };
+
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&json!({
+                        "kind": "lifecycle",
+                        "rules": rules,
+                    }))?
+                );
+            } else if rules.is_empty() {
+                println!("No lifecycle rules");
+            } else {
+                for rule in &rules {
+                    for line in describe_rule(rule) {
+                        println!("{line}");
+                    }
+                }
+            }
+        }
+        Action::LifecycleSet { rules } => {
+            let count = rules.len();
+            PutBucketLifecycleConfiguration::new(rules)
+                .request(s3)
+                .await?;
+            println!("lifecycle rules set: {count}");
+        }
+        Action::LifecycleDelete => {
+            DeleteBucketLifecycle::new().request(s3).await?;
+            println!("lifecycle rules removed");
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn describe_filter(rule: &LifecycleRule) -> String {
+    let Some(filter) = &rule.filter else {
+        return rule
+            .prefix
+            .as_ref()
+            .map_or_else(|| "whole bucket".to_string(), |p| format!("prefix={p}"));
+    };
+
+    let LifecycleFilter {
+        prefix,
+        tag,
+        object_size_greater_than,
+        object_size_less_than,
+        and,
+    } = filter;
+
+    let mut parts = Vec::new();
+    let (prefix, tags, greater, less) = and.as_ref().map_or_else(
+        || {
+            (
+                prefix.as_deref(),
+                tag.iter().collect::<Vec<_>>(),
+                *object_size_greater_than,
+                *object_size_less_than,
+            )
+        },
+        |and| {
+            (
+                and.prefix.as_deref(),
+                and.tags.iter().collect(),
+                and.object_size_greater_than,
+                and.object_size_less_than,
+            )
+        },
+    );
+
+    if let Some(prefix) = prefix {
+        parts.push(format!("prefix={prefix}"));
+    }
+    for tag in tags {
+        parts.push(format!("tag {}={}", tag.key, tag.value));
+    }
+    if let Some(size) = greater {
+        parts.push(format!("size>{size}"));
+    }
+    if let Some(size) = less {
+        parts.push(format!("size<{size}"));
+    }
+
+    if parts.is_empty() {
+        "whole bucket".to_string()
+    } else {
+        parts.join(", ")
+    }
+}
+
+fn describe_rule(rule: &LifecycleRule) -> Vec<String> {
+    let mut lines = vec![
+        format!(
+            "Rule: {} ({})",
+            rule.id.as_deref().unwrap_or("<no id>"),
+            rule.status
+        ),
+        format!("  applies to: {}", describe_filter(rule)),
+    ];
+
+    if let Some(expiration) = &rule.expiration {
+        if let Some(days) = expiration.days {
+            lines.push(format!("  expire current versions after: {days} days"));
+        }
+        if let Some(date) = &expiration.date {
+            lines.push(format!("  expire current versions on: {date}"));
+        }
+        if expiration.expired_object_delete_marker == Some(true) {
+            lines.push("  remove expired delete markers".to_string());
+        }
+    }
+
+    for transition in &rule.transitions {
+        let when = transition.days.map_or_else(
+            || transition.date.clone().unwrap_or_default(),
+            |days| format!("{days} days"),
+        );
+        lines.push(format!(
+            "  transition to {} after: {when}",
+            transition.storage_class
+        ));
+    }
+
+    for transition in &rule.noncurrent_version_transitions {
+        if let Some(days) = transition.noncurrent_days {
+            lines.push(format!(
+                "  transition noncurrent versions to {} after: {days} days",
+                transition.storage_class
+            ));
+        }
+    }
+
+    if let Some(expiration) = &rule.noncurrent_version_expiration {
+        if let Some(days) = expiration.noncurrent_days {
+            lines.push(format!("  expire noncurrent versions after: {days} days"));
+        }
+        if let Some(newer) = expiration.newer_noncurrent_versions {
+            lines.push(format!("  keep newer noncurrent versions: {newer}"));
+        }
+    }
+
+    if let Some(abort) = &rule.abort_incomplete_multipart_upload {
+        lines.push(format!(
+            "  abort incomplete multipart uploads after: {} days",
+            abort.days_after_initiation
+        ));
+    }
+
+    lines
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+mod tests {
+    use super::*;
+    use crate::s3::responses::{AbortIncompleteMultipartUpload, LifecycleExpiration};
+
+    #[test]
+    fn test_describe_rule() {
+        let rule = LifecycleRule {
+            id: Some("logs".to_string()),
+            status: "Enabled".to_string(),
+            filter: Some(LifecycleFilter {
+                prefix: Some("logs/".to_string()),
+                ..Default::default()
+            }),
+            expiration: Some(LifecycleExpiration {
+                days: Some(365),
+                ..Default::default()
+            }),
+            abort_incomplete_multipart_upload: Some(AbortIncompleteMultipartUpload {
+                days_after_initiation: 7,
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            describe_rule(&rule),
+            vec![
+                "Rule: logs (Enabled)",
+                "  applies to: prefix=logs/",
+                "  expire current versions after: 365 days",
+                "  abort incomplete multipart uploads after: 7 days",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_describe_filter_whole_bucket() {
+        let rule = LifecycleRule {
+            filter: Some(LifecycleFilter::default()),
+            ..Default::default()
+        };
+        assert_eq!(describe_filter(&rule), "whole bucket");
+        assert_eq!(describe_filter(&LifecycleRule::default()), "whole bucket");
+    }
+}
@@ -1,6 +1,8 @@
 pub mod acl;
 pub mod bucket;
+pub mod lifecycle;
 pub mod monitor;
+pub mod object_copy;
 pub mod object_delete;
 pub mod object_du;
 pub mod object_get;
@@ -8,10 +10,17 @@ pub mod object_list;
 pub mod object_lock;
 pub mod object_put;
 pub mod object_share;
+pub mod object_tag;
 pub mod streams;
+pub mod versioning;
 
 use crate::cli::age_filter::AgeFilter;
-use crate::s3::{ObjectLockMode, S3, actions::ObjectIdentifier};
+use crate::s3::{
+    CopyConditions, ObjectLockMode, S3, Tagging,
+    actions::{ObjectIdentifier, VersioningStatus},
+    responses::LifecycleRule,
+};
+use crate::stream::fanout::Require;
 use std::{collections::BTreeMap, path::PathBuf};
 
 /// Target of an `object-lock set` operation, resolved during dispatch.
@@ -39,6 +48,14 @@ pub struct DeleteGroup {
     pub s3: S3,
 }
 
+/// Another destination of a `--pipe` upload, with the S3 of its own host
+#[derive(Debug, Clone)]
+pub struct PutDestination {
+    pub host: String,
+    pub key: String,
+    pub s3: S3,
+}
+
 #[derive(Debug)]
 pub enum StreamCommand {
     List,
@@ -58,6 +75,18 @@ pub enum Action {
         acl: Option<String>,
         key: String,
     },
+    CopyObject {
+        key: String,
+        version_id: Option<String>,
+        dest_bucket: String,
+        dest_key: String,
+        acl: Option<String>,
+        meta: Option<BTreeMap<String, String>>,
+        conditions: CopyConditions,
+        buf_size: usize,
+        number: u8,
+        quiet: bool,
+    },
     CreateBucket {
         acl: String,
         object_lock: bool,
@@ -97,6 +126,9 @@ pub enum Action {
         json: bool,
         versions: bool,
         version: Option<String>,
+        raw: bool,
+        max_output: Option<usize>,
+        number: u8,
     },
     PutObject {
         acl: Option<String>,
@@ -109,8 +141,15 @@ pub enum Action {
         pipe: bool,
         quiet: bool,
         s3m_dir: PathBuf,
+        spool: bool,
         tmp_dir: PathBuf,
         number: u8,
+        max_staging: usize,
+        max_memory: Option<usize>,
+        size_hint: Option<usize>,
+        require: Require,
+        // the destinations after the first one, built by finalize_action
+        destinations: Vec<PutDestination>,
     },
     ShareObject {
         expire: usize,
@@ -123,6 +162,20 @@ pub enum Action {
         json: bool,
     },
     ObjectLockSet(ObjectLockSetTarget),
+    ObjectTagGet {
+        key: String,
+        version_id: Option<String>,
+        json: bool,
+    },
+    ObjectTagSet {
+        key: String,
+        version_id: Option<String>,
+        tagging: Tagging,
+    },
+    ObjectTagDelete {
+        key: String,
+        version_id: Option<String>,
+    },
     Monitor {
         host: String,
         checks: Vec<monitor::MonitorCheck>,
@@ -130,6 +183,19 @@ pub enum Action {
         exit_on_check_failure: bool,
         number: u8,
     },
+    LifecycleGet {
+        json: bool,
+    },
+    LifecycleSet {
+        rules: Vec<LifecycleRule>,
+    },
+    LifecycleDelete,
+    VersioningGet {
+        json: bool,
+    },
+    VersioningSet {
+        status: VersioningStatus,
+    },
     Streams {
         command: StreamCommand,
         config_file: PathBuf,
@@ -0,0 +1,372 @@
+use crate::{
+    cli::{actions::Action, globals::GlobalArgs, progressbar::Bar},
+    s3::{
+        CopyConditions, CopySource, MetadataDirective, S3, actions,
+        limits::{MAX_COPY_OBJECT_SIZE_BYTES, MIN_PART_SIZE_BYTES},
+        tools,
+    },
+    stream::{concurrency::Concurrency, iterator::PartIterator},
+};
+use anyhow::{Context, Result};
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::collections::BTreeMap;
+
+// Headers that describe the object and that a multipart copy must carry over
+// explicitly: unlike CopyObject, UploadPartCopy never copies the source
+// metadata, the new object only gets what CreateMultipartUpload sends.
+const COPIED_HEADERS: &[&str] = &[
+    "cache-control",
+    "content-disposition",
+    "content-encoding",
+    "content-language",
+    "content-type",
+    "expires",
+];
+
+// shared by the parts of a multipart copy
+struct PartCopy<'a> {
+    request: &'a CopyRequest<'a>,
+    upload_id: &'a str,
+    globals: &'a GlobalArgs,
+    concurrency: &'a Concurrency,
+}
+
+struct CopyRequest<'a> {
+    dest: &'a S3,
+    copy_source: CopySource,
+    dest_key: &'a str,
+    acl: Option<String>,
+    meta: Option<BTreeMap<String, String>>,
+    conditions: CopyConditions,
+}
+
+/// # Errors
+/// Will return an error if the action fails
+pub async fn handle(s3: &S3, action: Action, globals: GlobalArgs) -> Result<()> {
+    if let Action::CopyObject {
+        key,
+        version_id,
+        dest_bucket,
+        dest_key,
+        acl,
+        meta,
+        conditions,
+        buf_size,
+        number,
+        quiet,
+    } = action
+    {
+        let source_bucket = s3.bucket().context("source bucket missing")?;
+        let copy_source = CopySource::new(source_bucket, &key).version_id(version_id.clone());
+
+        let head = actions::HeadObject::new(&key, version_id)
+            .request(s3)
+            .await?;
+        let size = head
+            .get("content-length")
+            .and_then(|v| v.parse::<u64>().ok())
+            .context("could not get the size of the source object")?;
+
+        let dest = s3.with_bucket(Some(dest_bucket));
+
+        // --meta replaces the user metadata, the copy keeps the source headers
+        let meta = meta.map(|meta| copy_metadata(&head, meta));
+
+        let request = CopyRequest {
+            dest: &dest,
+            copy_source,
+            dest_key: &dest_key,
+            acl,
+            meta,
+            conditions,
+        };
+
+        let etag = if size <= MAX_COPY_OBJECT_SIZE_BYTES {
+            copy_object(request).await?
+        } else {
+            let part_size =
+                tools::calculate_part_size(size, (buf_size as u64).max(MIN_PART_SIZE_BYTES))?;
+
+            copy_multipart(request, &head, size, part_size, number, &globals, quiet).await?
+        };
+
+        println!("ETag: {etag}");
+    }
+
+    Ok(())
+}
+
+async fn copy_object(request: CopyRequest<'_>) -> Result<String> {
+    // new metadata only makes it to the copy with the REPLACE directive
+    let directive = request.meta.is_some().then_some(MetadataDirective::Replace);
+
+    let action = actions::CopyObject::new(request.dest_key, &request.copy_source)
+        .acl(request.acl)
+        .meta(request.meta)
+        .metadata_directive(directive)
+        .conditions(request.conditions);
+
+    let output = action.request(request.dest).await?;
+
+    if let Some(vid) = output.version_id {
+        log::info!("Version ID: {vid}");
+    }
+
+    Ok(output.result.e_tag)
+}
+
+// https://docs.aws.amazon.com/AmazonS3/latest/userguide/CopyingObjectsMPUapi.html
+// * Initiate Multipart Upload
+// * Upload Part - Copy
+// * Complete Multipart Upload
+async fn copy_multipart(
+    mut request: CopyRequest<'_>,
+    head: &BTreeMap<String, String>,
+    size: u64,
+    part_size: u64,
+    number: u8,
+    globals: &GlobalArgs,
+    quiet: bool,
+) -> Result<String> {
+    log::info!(
+        "Multipart copy of {size} bytes, part size: {part_size}, max concurrent requests: {number}"
+    );
+
+    let meta = request.meta.take().unwrap_or_else(|| source_metadata(head));
+
+    // pin every part to the object seen by HeadObject so an overwrite of the
+    // source during the copy fails instead of mixing two objects
+    if request.conditions.if_match.is_none() {
+        request.conditions.if_match = head.get("etag").cloned();
+    }
+
+    let action = actions::CreateMultipartUpload::new(
+        request.dest_key,
+        request.acl.take(),
+        Some(meta),
+        None,
+        None,
+    );
+    let upload_id = action.request(request.dest).await?.upload_id;
+
+    log::debug!("upload_id: {upload_id}");
+
+    match copy_parts(
+        &request, &upload_id, size, part_size, number, globals, quiet,
+    )
+    .await
+    {
+        Ok(parts) => {
+            let action =
+                actions::CompleteMultipartUpload::new(request.dest_key, &upload_id, parts, None);
+            Ok(action.request(request.dest).await?.e_tag)
+        }
+        Err(e) => {
+            log::error!("Multipart copy failed, aborting upload {upload_id}: {e}");
+
+            let action = actions::AbortMultipartUpload::new(request.dest_key, &upload_id);
+            if let Err(abort) = action.request(request.dest).await {
+                log::error!("Could not abort upload {upload_id}: {abort}");
+            }
+
+            Err(e)
+        }
+    }
+}
+
+async fn copy_parts(
+    request: &CopyRequest<'_>,
+    upload_id: &str,
+    size: u64,
+    part_size: u64,
+    number: u8,
+    globals: &GlobalArgs,
+    quiet: bool,
+) -> Result<BTreeMap<u16, actions::Part>> {
+    let pb = if quiet {
+        Bar::default()
+    } else {
+        Bar::new(size)
+    };
+
+    // starts at -n, lowered on SlowDown and raised while parts succeed
+    let concurrency = Concurrency::new(number.into());
+    let copy = PartCopy {
+        request,
+        upload_id,
+        globals,
+        concurrency: &concurrency,
+    };
+
+    let mut parts: BTreeMap<u16, actions::Part> = BTreeMap::new();
+    let mut tasks = FuturesUnordered::new();
+
+    show_concurrency(&pb, &concurrency);
+
+    for (part_number, seek, chunk) in PartIterator::new(size, part_size) {
+        tasks.push(copy_part(&copy, part_number, seek, chunk));
+
+        // limit to the current concurrency, it changes as parts complete
+        while tasks.len() >= concurrency.current() {
+            if let Some(part) = tasks.next().await {
+                collect_part(&mut parts, &pb, part?);
+                show_concurrency(&pb, &concurrency);
+            }
+        }
+    }
+
+    while let Some(part) = tasks.next().await {
+        collect_part(&mut parts, &pb, part?);
+    }
+
+    if let Some(pb) = pb.progress.as_ref() {
+        pb.finish();
+    }
+
+    Ok(parts)
+}
+
+fn show_concurrency(pb: &Bar, concurrency: &Concurrency) {
+    if let Some(pb) = pb.progress.as_ref() {
+        pb.set_message(format!("[{} parallel]", concurrency.current()));
+    }
+}
+
+fn collect_part(parts: &mut BTreeMap<u16, actions::Part>, pb: &Bar, part: (u16, String, u64)) {
+    let (number, etag, chunk) = part;
+
+    if let Some(pb) = pb.progress.as_ref() {
+        pb.inc(chunk);
+    }
+
+    parts.insert(
+        number,
+        actions::Part {
+            etag,
+            number,
+            checksum: None,
+        },
+    );
+}
+
+async fn copy_part(
+    copy: &PartCopy<'_>,
+    part_number: u16,
+    seek: u64,
+    chunk: u64,
+) -> Result<(u16, String, u64)> {
+    let request = copy.request;
+    let action = actions::UploadPartCopy::new(
+        request.dest_key,
+        copy.upload_id,
+        part_number,
+        &request.copy_source,
+    )
+    .range(seek, chunk)
+    .conditions(request.conditions.clone());
+
+    let policy = copy.globals.retry_policy();
+    let mut attempt = 1;
+
+    loop {
+        let epoch = copy.concurrency.epoch();
+
+        match action.request(request.dest).await {
+            Ok(etag) => {
+                copy.concurrency.success();
+
+                log::info!("Copied part: {part_number}, etag: {etag}");
+
+                return Ok((part_number, etag, chunk));
+            }
+            Err(e) => {
+                copy.concurrency.failure(epoch, &e);
+
+                log::error!(
+                    "Error copying part: {part_number}, attempt {attempt}/{} failed: {e}",
+                    policy.attempts()
+                );
+
+                // a failed precondition is not retryable, it will not change
+                if !policy.backoff(attempt, &e).await {
+                    return Err(e.into());
+                }
+
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Metadata of the source object (from `HeadObject`) to send with
+/// `CreateMultipartUpload` so the copy keeps it.
+fn source_metadata(head: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+    head.iter()
+        .filter(|(k, _)| k.starts_with("x-amz-meta-") || COPIED_HEADERS.contains(&k.as_str()))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+/// The headers of the source object with `meta` as its user metadata, for a
+/// copy with new metadata
+fn copy_metadata(
+    head: &BTreeMap<String, String>,
+    meta: BTreeMap<String, String>,
+) -> BTreeMap<String, String> {
+    let mut metadata = source_metadata(head);
+    metadata.retain(|k, _| !k.starts_with("x-amz-meta-"));
+    metadata.extend(meta);
+
+    metadata
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_metadata() {
+        let mut head = BTreeMap::new();
+        head.insert("content-length".to_string(), "10".to_string());
+        head.insert("content-type".to_string(), "text/plain".to_string());
+        head.insert("etag".to_string(), "\"abc\"".to_string());
+        head.insert("x-amz-meta-team".to_string(), "ops".to_string());
+        head.insert("x-amz-request-id".to_string(), "id".to_string());
+
+        let meta = source_metadata(&head);
+        assert_eq!(meta.len(), 2);
+        assert_eq!(
+            meta.get("content-type").map(String::as_str),
+            Some("text/plain")
+        );
+        assert_eq!(meta.get("x-amz-meta-team").map(String::as_str), Some("ops"));
+    }
+
+    #[test]
+    fn test_copy_metadata() {
+        let mut head = BTreeMap::new();
+        head.insert("content-type".to_string(), "text/plain".to_string());
+        head.insert("x-amz-meta-team".to_string(), "ops".to_string());
+
+        let mut meta = BTreeMap::new();
+        meta.insert("x-amz-meta-owner".to_string(), "alice".to_string());
+
+        let meta = copy_metadata(&head, meta);
+        assert_eq!(meta.len(), 2);
+        assert_eq!(
+            meta.get("content-type").map(String::as_str),
+            Some("text/plain")
+        );
+        assert_eq!(
+            meta.get("x-amz-meta-owner").map(String::as_str),
+            Some("alice")
+        );
+    }
+}
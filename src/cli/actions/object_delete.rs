@@ -23,6 +23,20 @@ pub async fn handle(s3: &S3, action: Action) -> Result<()> {
         bypass_governance,
     } = action
     {
+        // targets are built with their own host, retry them like the main S3
+        let targets: Vec<DeleteGroup> = targets
+            .into_iter()
+            .map(|group| DeleteGroup {
+                s3: group.s3.with_retry_policy(s3.retry_policy()),
+                ..group
+            })
+            .collect();
+
+        // every target may use the credentials provider of its own host
+        for group in &targets {
+            group.s3.refresh_credentials().await?;
+        }
+
         if bucket {
             if recursive {
                 delete_bucket_recursive(s3, bypass_governance).await?;
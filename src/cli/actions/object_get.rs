@@ -1,31 +1,64 @@
 use crate::{
     cli::{actions::Action, globals::GlobalArgs, progressbar::Bar},
-    s3::{S3, actions, tools::throttle_download},
-    stream::{cipher_from_key, decrypt_chunk, parse_nonce_header},
+    s3::{
+        Error, S3, actions,
+        checksum::{Checksum, ChecksumAlgorithm},
+        tools,
+    },
+    stream::{
+        cipher_from_key,
+        compression::{Codec, Decompressor, METADATA_HEADER},
+        concurrency::Concurrency,
+        decrypt_chunk,
+        iterator::PartIterator,
+        parse_nonce_header,
+    },
 };
 use aead_stream::DecryptorBE32;
 use anyhow::{Context, Result, anyhow};
-use bytes::{Buf, BytesMut};
+use base64ct::{Base64, Encoding};
+use bytes::{Buf, Bytes, BytesMut};
 use bytesize::ByteSize;
 use chacha20poly1305::ChaCha20Poly1305;
 use chrono::{DateTime, Utc};
 use colored::Colorize;
-use http::{HeaderMap, header::CONTENT_TYPE};
+use futures::stream::{FuturesUnordered, StreamExt};
+use http::{
+    HeaderMap, HeaderName, HeaderValue,
+    header::{AsHeaderName, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, ETAG},
+};
 use serde::Serialize;
 use std::{
     cmp::min,
     collections::BTreeMap,
     ffi::{OsStr, OsString},
+    io::{BufWriter, SeekFrom},
     path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
+use tokio::{
+    fs::OpenOptions,
+    io::{AsyncSeekExt, AsyncWriteExt},
+    time::sleep,
 };
-use tokio::{fs::OpenOptions, io::AsyncWriteExt};
 
 // Encrypted streams are framed as `[len(4)][ciphertext]`; a single frame is one
-// upload read-chunk, far below the 512 MiB multipart part buffer. Reject absurd
+// upload read-chunk, far below 512 MiB. Reject absurd
 // length prefixes (corrupt or hostile data) before buffering — a `u32` length
 // could otherwise claim up to ~4 GiB.
 const MAX_ENCRYPTED_CHUNK: usize = 512 * 1024 * 1024;
 
+// Without --max-output, a decompressed object may be this many times larger
+// than the stored object, and at least MIN_MAX_OUTPUT bytes.
+const MAX_DECOMPRESSION_RATIO: u64 = 1_024;
+const MIN_MAX_OUTPUT: u64 = 1_024 * 1_024 * 1_024;
+
+// Destination that writes the object to stdout
+const STDOUT: &str = "-";
+
+// Objects larger than one part are downloaded with -n parallel requests
+const DOWNLOAD_PART_SIZE: u64 = 64 * 1_024 * 1_024;
+
 #[derive(Debug, Serialize, PartialEq, Eq)]
 struct MetadataJsonOutput {
     bucket: Option<String>,
@@ -63,7 +96,7 @@ struct VersionJsonEntry {
 }
 
 struct DownloadState {
-    file: tokio::fs::File,
+    output: Output,
     pb: Bar,
     file_size: u64,
     downloaded: u64,
@@ -74,11 +107,75 @@ struct DownloadState {
     cipher: Option<ChaCha20Poly1305>,
 }
 
+/// Where the downloaded bytes go, decompressed on the way when the object is
+/// compressed
+enum Output {
+    File(tokio::fs::File),
+    Stdout(tokio::io::Stdout),
+    Decompress(Decompressor),
+}
+
 enum OutputFormat {
     Text,
     Json,
 }
 
+struct DownloadRequest {
+    key: String,
+    version: Option<String>,
+    dest: Option<String>,
+    quiet: bool,
+    force: bool,
+    raw: bool,
+    max_output: Option<usize>,
+    number: u8,
+}
+
+/// A part of a parallel download
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DownloadPart {
+    // set when the object is fetched by the parts it was uploaded with
+    number: Option<u16>,
+    seek: u64,
+    length: u64,
+}
+
+/// The object as told by a `HeadObject`, read before choosing how to download it
+struct ObjectHead {
+    headers: HeaderMap,
+    size: u64,
+    // number of parts and size of the first one, for objects uploaded in parts
+    parts: Option<(u64, u64)>,
+}
+
+impl ObjectHead {
+    fn etag(&self) -> Option<String> {
+        self.headers
+            .get(ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    }
+}
+
+/// How an object is downloaded, decided by its first `GetObject`
+enum Download {
+    Sequential(reqwest::Response),
+    Parallel(ObjectHead),
+}
+
+// shared by the parts of a parallel download
+struct ParallelDownload<'a> {
+    s3: &'a S3,
+    key: &'a str,
+    version: Option<String>,
+    etag: Option<String>,
+    path: &'a Path,
+    globals: &'a GlobalArgs,
+    concurrency: &'a Concurrency,
+    // set once a part differs from the plan, the rest is fetched by range
+    by_range: AtomicBool,
+}
+
 enum GetObjectRequest {
     Metadata {
         key: String,
@@ -89,13 +186,7 @@ enum GetObjectRequest {
         key: String,
         output: OutputFormat,
     },
-    Download {
-        key: String,
-        version: Option<String>,
-        dest: Option<String>,
-        quiet: bool,
-        force: bool,
-    },
+    Download(DownloadRequest),
 }
 
 /// # Errors
@@ -110,6 +201,9 @@ pub async fn handle(s3: &S3, action: Action, globals: GlobalArgs) -> Result<()>
         json,
         versions,
         version,
+        raw,
+        max_output,
+        number,
     } = action
     {
         let output = if json {
@@ -127,13 +221,16 @@ pub async fn handle(s3: &S3, action: Action, globals: GlobalArgs) -> Result<()>
         } else if versions {
             GetObjectRequest::Versions { key, output }
         } else {
-            GetObjectRequest::Download {
+            GetObjectRequest::Download(DownloadRequest {
                 key,
                 version,
                 dest,
                 quiet,
                 force,
-            }
+                raw,
+                max_output,
+                number,
+            })
         };
 
         return handle_get_action(s3, request, globals).await;
@@ -152,53 +249,55 @@ async fn handle_get_action(s3: &S3, request: GetObjectRequest, globals: GlobalAr
         GetObjectRequest::Versions { key, output } => {
             handle_versions(s3, &key, matches!(output, OutputFormat::Json)).await
         }
-        GetObjectRequest::Download {
-            key,
-            version,
-            dest,
-            quiet,
-            force,
-        } => download_object(s3, key, version, dest, quiet, force, globals).await,
+        GetObjectRequest::Download(request) => download_object(s3, request, globals).await,
     }
 }
 
-async fn download_object(
-    s3: &S3,
-    key: String,
-    version: Option<String>,
-    dest: Option<String>,
-    quiet: bool,
-    force: bool,
-    globals: GlobalArgs,
-) -> Result<()> {
-    let file_name = Path::new(&key)
+async fn download_object(s3: &S3, request: DownloadRequest, globals: GlobalArgs) -> Result<()> {
+    let file_name = Path::new(&request.key)
         .file_name()
-        .with_context(|| format!("Failed to get file name from: {key}"))?;
-    let action = actions::GetObject::new(&key, version);
-    let mut res = action.request(s3, &globals).await?;
+        .with_context(|| format!("Failed to get file name from: {}", request.key))?;
+    let mut res = match first_request(s3, &request, &globals).await? {
+        Download::Sequential(res) => res,
+        Download::Parallel(head) => {
+            let path = output_path(&request, file_name, false, None)?;
+            return download_parallel(s3, &request, &path, &head, &globals).await;
+        }
+    };
+
     let is_encrypted = is_s3m_encrypted(res.headers());
     let can_decrypt = is_encrypted && globals.enc_key.is_some();
 
+    // without the key the ciphertext is saved, it can not be decompressed
+    let codec = if request.raw || (is_encrypted && !can_decrypt) {
+        None
+    } else {
+        compression_codec(res.headers(), &request.key)
+    };
+
     log::info!(
-        "file_name: {}, is_encrypted: {}, can_decrypt: {}",
+        "file_name: {}, is_encrypted: {}, can_decrypt: {}, codec: {:?}",
         file_name.to_string_lossy(),
         is_encrypted,
-        can_decrypt
+        can_decrypt,
+        codec
     );
 
-    let final_file_name = determine_final_filename(file_name, can_decrypt);
-    let path = get_dest(dest, &final_file_name)?;
-    if path.is_file() && !force {
-        return Err(anyhow!("file {} already exists", path.display()));
-    }
-
-    let file = create_output_file(&path, force).await?;
     let file_size = res
         .content_length()
         .context("could not get content_length")?;
+
+    // none when the object goes to stdout
+    let path = if request.dest.as_deref() == Some(STDOUT) {
+        None
+    } else {
+        Some(output_path(&request, file_name, can_decrypt, codec)?)
+    };
+
+    let output = open_output(&request, path.as_deref(), codec, file_size).await?;
     let mut state = DownloadState::new(
-        file,
-        if quiet {
+        output,
+        if request.quiet {
             Bar::default()
         } else {
             Bar::new(file_size)
@@ -209,11 +308,580 @@ async fn download_object(
         create_cipher_if_needed(&globals, can_decrypt)?,
     );
 
-    download_response(&mut res, &mut state, &globals).await?;
-    state.finish();
+    let result = async {
+        download_response(&mut res, &mut state, &globals).await?;
+        state.finish().await
+    }
+    .await;
+
+    match (result, path) {
+        (Ok(()), _) => Ok(()),
+
+        // a truncated or partly decrypted file would pass for the object
+        (Err(e), Some(path)) => {
+            if let Err(remove) = tokio::fs::remove_file(&path).await {
+                log::error!("Could not remove {}: {remove}", path.display());
+            }
+
+            Err(e)
+        }
+
+        // bytes already piped can not be taken back, say so instead of hiding it
+        (Err(e), None) => {
+            Err(e.context("download failed, the output written to stdout is incomplete"))
+        }
+    }
+}
+
+/// The first `GetObject` of a download. Files are requested by a range of
+/// `DOWNLOAD_PART_SIZE` bytes, which is the whole object when it is small and
+/// tells the size of the larger ones from its `Content-Range`. Only objects
+/// that go parallel send a `HeadObject` for their parts.
+async fn first_request(
+    s3: &S3,
+    request: &DownloadRequest,
+    globals: &GlobalArgs,
+) -> Result<Download> {
+    let action = actions::GetObject::new(&request.key, request.version.clone());
+
+    // stdout takes the bytes in order, parts are written in place into a file
+    if request.number <= 1 || request.dest.as_deref() == Some(STDOUT) {
+        return Ok(Download::Sequential(action.request(s3, globals).await?));
+    }
+
+    let ranged = actions::GetObject::new(&request.key, request.version.clone())
+        .range(0, DOWNLOAD_PART_SIZE)
+        .request(s3, globals)
+        .await;
+
+    let res = match ranged {
+        Ok(res) => res,
+
+        // no range of an empty object is satisfiable
+        Err(e) if e.status() == Some(416) => {
+            return Ok(Download::Sequential(action.request(s3, globals).await?));
+        }
+
+        Err(e) => return Err(e.into()),
+    };
+
+    // a provider ignoring the range answers with the whole object
+    let size = res
+        .headers()
+        .get(CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(content_range_size)
+        .or_else(|| res.content_length())
+        .context("could not get content_length")?;
+
+    if res.content_length() == Some(size) {
+        return Ok(Download::Sequential(res));
+    }
+
+    let head = ObjectHead {
+        headers: res.headers().clone(),
+        size,
+        parts: None,
+    };
+
+    drop(res);
+
+    if is_parallel(&head, request) {
+        let parts = head_object(s3, request).await?;
+
+        if parts.etag() == head.etag() {
+            return Ok(Download::Parallel(parts));
+        }
+    }
+
+    // the object the first range was read from, in order
+    Ok(Download::Sequential(
+        action.if_match(head.etag()).request(s3, globals).await?,
+    ))
+}
+
+/// Opens the sink of a download: the destination file at `path`, else stdout.
+/// Compressed objects go through a [`Decompressor`] on the way.
+async fn open_output(
+    request: &DownloadRequest,
+    path: Option<&Path>,
+    codec: Option<Codec>,
+    file_size: u64,
+) -> Result<Output> {
+    let limit = max_output(request.max_output, file_size);
+
+    let Some(path) = path else {
+        return Ok(match codec {
+            Some(codec) => Output::Decompress(Decompressor::new(
+                codec,
+                BufWriter::new(std::io::stdout()),
+                limit,
+            )),
+            None => Output::Stdout(tokio::io::stdout()),
+        });
+    };
+
+    let file = create_output_file(path, request.force).await?;
+
+    Ok(match codec {
+        Some(codec) => Output::Decompress(Decompressor::new(
+            codec,
+            BufWriter::new(file.into_std().await),
+            limit,
+        )),
+        None => Output::File(file),
+    })
+}
+
+/// The destination file of a download, it must not exist without `--force`
+fn output_path(
+    request: &DownloadRequest,
+    file_name: &OsStr,
+    can_decrypt: bool,
+    codec: Option<Codec>,
+) -> Result<PathBuf> {
+    let final_file_name = determine_final_filename(file_name, can_decrypt, codec);
+    let path = get_dest(request.dest.clone(), &final_file_name)?;
+    if path.is_file() && !request.force {
+        return Err(anyhow!("file {} already exists", path.display()));
+    }
+
+    Ok(path)
+}
+
+/// Downloads an object with up to `-n` requests in flight, every part written
+/// at its offset of the pre-allocated file. A failed download removes the
+/// file, its zeroed gaps would look like a complete object.
+async fn download_parallel(
+    s3: &S3,
+    request: &DownloadRequest,
+    path: &Path,
+    head: &ObjectHead,
+    globals: &GlobalArgs,
+) -> Result<()> {
+    let file_size = head.size;
+    let parts = plan_parts(head)?;
+
+    log::info!(
+        "Parallel download of {file_size} bytes in {} parts, max concurrent requests: {}",
+        parts.len(),
+        request.number
+    );
+
+    let file = create_output_file(path, request.force).await?;
+    file.set_len(file_size).await?;
+    drop(file);
+
+    // starts at -n, lowered on SlowDown and raised while parts succeed
+    let concurrency = Concurrency::new(request.number.into());
+
+    let download = ParallelDownload {
+        s3,
+        key: &request.key,
+        version: request.version.clone(),
+        etag: head.etag(),
+        path,
+        globals,
+        concurrency: &concurrency,
+        by_range: AtomicBool::new(false),
+    };
+
+    let pb = if request.quiet {
+        Bar::default()
+    } else {
+        Bar::new(file_size)
+    };
+
+    if let Err(e) = download_parts(&download, parts, &pb).await {
+        if let Err(remove) = tokio::fs::remove_file(path).await {
+            log::error!("Could not remove {}: {remove}", path.display());
+        }
+
+        return Err(e);
+    }
+
+    if let Some(pb) = pb.progress.as_ref() {
+        pb.finish();
+    }
+
     Ok(())
 }
 
+/// `HeadObject` with `partNumber=1`, which also tells the parts of objects
+/// uploaded in parts. Their total size is taken from the `Content-Range` of the
+/// first part, a plain `HeadObject` is only sent when the provider omits it or
+/// does not know `partNumber`.
+async fn head_object(s3: &S3, request: &DownloadRequest) -> Result<ObjectHead> {
+    let mut action = actions::HeadObject::new(&request.key, request.version.clone());
+    action.part_number = Some("1".to_string());
+
+    // not every provider knows partNumber, ranges work everywhere
+    let headers = match action.request(s3).await {
+        Ok(head) => header_map(&head),
+        Err(e) => {
+            log::info!("HeadObject partNumber=1 failed, using ranges: {e}");
+            return plain_head(s3, request).await;
+        }
+    };
+
+    let first = header_u64(&headers, CONTENT_LENGTH).context("could not get content_length")?;
+    let Some(count) = header_u64(&headers, "x-amz-mp-parts-count").filter(|count| *count > 1)
+    else {
+        // the first part is the whole object
+        return Ok(ObjectHead {
+            headers,
+            size: first,
+            parts: None,
+        });
+    };
+
+    let range = headers
+        .get(CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(content_range_size);
+    let size = match range {
+        Some(size) => size,
+        None => plain_head(s3, request).await?.size,
+    };
+
+    Ok(ObjectHead {
+        headers,
+        size,
+        parts: Some((count, first)),
+    })
+}
+
+async fn plain_head(s3: &S3, request: &DownloadRequest) -> Result<ObjectHead> {
+    let action = actions::HeadObject::new(&request.key, request.version.clone());
+    let headers = header_map(&action.request(s3).await?);
+    let size = header_u64(&headers, CONTENT_LENGTH).context("could not get content_length")?;
+
+    Ok(ObjectHead {
+        headers,
+        size,
+        parts: None,
+    })
+}
+
+fn header_map(head: &BTreeMap<String, String>) -> HeaderMap {
+    head.iter()
+        .filter_map(|(name, value)| {
+            Some((
+                HeaderName::from_bytes(name.as_bytes()).ok()?,
+                HeaderValue::from_str(value).ok()?,
+            ))
+        })
+        .collect()
+}
+
+fn header_u64(headers: &HeaderMap, name: impl AsHeaderName) -> Option<u64> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+}
+
+/// Objects larger than one part are fetched in parallel, unless they must go
+/// through decryption or decompression which need the bytes in order
+fn is_parallel(head: &ObjectHead, request: &DownloadRequest) -> bool {
+    head.size > DOWNLOAD_PART_SIZE
+        && !is_s3m_encrypted(&head.headers)
+        && (request.raw || compression_codec(&head.headers, &request.key).is_none())
+}
+
+/// The parts the object was uploaded with when their count and the size of the
+/// first one fit the object, so they can be fetched by `partNumber` with their
+/// checksums, else ranges of `DOWNLOAD_PART_SIZE`. Parts are not required to
+/// have the same size, a part that differs from the plan switches the rest of
+/// the download to ranges of the planned bytes.
+fn plan_parts(head: &ObjectHead) -> Result<Vec<DownloadPart>> {
+    let file_size = head.size;
+
+    if let Some((parts, first)) = head.parts
+        && (parts - 1).saturating_mul(first) < file_size
+        && parts.saturating_mul(first) >= file_size
+    {
+        return Ok(PartIterator::new(file_size, first)
+            .map(|(number, seek, length)| DownloadPart {
+                number: Some(number),
+                seek,
+                length,
+            })
+            .collect());
+    }
+
+    let part_size = tools::calculate_part_size(file_size, DOWNLOAD_PART_SIZE)?;
+
+    Ok(PartIterator::new(file_size, part_size)
+        .map(|(_, seek, length)| DownloadPart {
+            number: None,
+            seek,
+            length,
+        })
+        .collect())
+}
+
+async fn download_parts(
+    download: &ParallelDownload<'_>,
+    parts: Vec<DownloadPart>,
+    pb: &Bar,
+) -> Result<()> {
+    let mut tasks = FuturesUnordered::new();
+
+    show_concurrency(pb, download.concurrency);
+
+    for part in parts {
+        tasks.push(download_part(download, part));
+
+        // limit to the current concurrency, it changes as parts complete
+        while tasks.len() >= download.concurrency.current() {
+            if let Some(length) = tasks.next().await {
+                increment_progress(pb, length?);
+                show_concurrency(pb, download.concurrency);
+            }
+        }
+    }
+
+    while let Some(length) = tasks.next().await {
+        increment_progress(pb, length?);
+    }
+
+    Ok(())
+}
+
+fn show_concurrency(pb: &Bar, concurrency: &Concurrency) {
+    if let Some(pb) = pb.progress.as_ref() {
+        pb.set_message(format!("[{} parallel]", concurrency.current()));
+    }
+}
+
+fn increment_progress(pb: &Bar, length: u64) {
+    if let Some(pb) = pb.progress.as_ref() {
+        pb.inc(length);
+    }
+}
+
+async fn download_part(download: &ParallelDownload<'_>, part: DownloadPart) -> Result<u64> {
+    let policy = download.globals.retry_policy();
+
+    for attempt in 1..=policy.attempts() {
+        let epoch = download.concurrency.epoch();
+
+        match try_download_part(download, part).await {
+            Ok(()) => {
+                download.concurrency.success();
+
+                log::info!(
+                    "Downloaded part: {}, bytes {}-{}",
+                    part.number.unwrap_or_default(),
+                    part.seek,
+                    part.seek + part.length - 1
+                );
+
+                return Ok(part.length);
+            }
+
+            Err(e) => {
+                log::error!(
+                    "Error downloading bytes {}-{}, attempt {}/{} failed: {}",
+                    part.seek,
+                    part.seek + part.length - 1,
+                    attempt,
+                    policy.attempts(),
+                    e
+                );
+
+                let error = e.downcast_ref::<Error>();
+
+                if let Some(error) = error {
+                    download.concurrency.failure(epoch, error);
+                }
+
+                // an overwritten object will not match again
+                if attempt == policy.attempts() || error.and_then(Error::status) == Some(412) {
+                    return Err(e);
+                }
+
+                // exponential backoff with jitter, or the Retry-After of SlowDown
+                let delay = policy.delay(attempt, error.and_then(Error::retry_after));
+
+                log::warn!(
+                    "Error downloading bytes {}-, retrying in {:.1} seconds",
+                    part.seek,
+                    delay.as_secs_f64()
+                );
+
+                sleep(delay).await;
+            }
+        }
+    }
+
+    Err(anyhow!("could not download bytes {}-", part.seek))
+}
+
+async fn try_download_part(download: &ParallelDownload<'_>, part: DownloadPart) -> Result<()> {
+    let (mut res, part) = request_part(download, part).await?;
+
+    let checksum = part_checksum(res.headers(), part);
+    let mut hasher = checksum.as_ref().map(Checksum::hasher);
+
+    let mut file = OpenOptions::new().write(true).open(download.path).await?;
+    file.seek(SeekFrom::Start(part.seek)).await?;
+
+    let mut written: u64 = 0;
+
+    while let Some(chunk) = res.chunk().await? {
+        written += chunk.len() as u64;
+
+        if written > part.length {
+            return Err(anyhow!(
+                "bytes {}- returned more than {} bytes",
+                part.seek,
+                part.length
+            ));
+        }
+
+        if let Some(hasher) = hasher.as_mut() {
+            hasher.update(&chunk);
+        }
+
+        file.write_all(&chunk).await?;
+
+        if let Some(bandwidth) = &download.globals.throttle {
+            bandwidth.consume(chunk.len()).await;
+        }
+    }
+
+    file.flush().await?;
+
+    if written != part.length {
+        return Err(anyhow!(
+            "bytes {}- ended after {written} of {} bytes",
+            part.seek,
+            part.length
+        ));
+    }
+
+    if let (Some(checksum), Some(hasher)) = (checksum, hasher) {
+        let computed = Base64::encode_string(&hasher.finalize());
+
+        if computed != checksum.checksum {
+            return Err(anyhow!(
+                "{} checksum mismatch for part {}: expected {}, got {computed}",
+                checksum.algorithm.as_algorithm(),
+                part.number.unwrap_or_default(),
+                checksum.checksum
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// The response of a part, with the part as fetched. A `partNumber` response
+/// with other bytes than planned is dropped, that part and the next ones are
+/// requested by range.
+async fn request_part(
+    download: &ParallelDownload<'_>,
+    part: DownloadPart,
+) -> Result<(reqwest::Response, DownloadPart)> {
+    if let Some(number) = part.number
+        && !download.by_range.load(Ordering::Relaxed)
+    {
+        let mut action = actions::GetObject::new(download.key, download.version.clone())
+            .if_match(download.etag.clone());
+        action.part_number = Some(number.to_string());
+
+        let res = action.request(download.s3, download.globals).await?;
+
+        match check_content_range(res.headers(), part) {
+            Ok(()) => return Ok((res, part)),
+            Err(e) => {
+                log::warn!("Part {number} does not match the plan, using ranges: {e}");
+                download.by_range.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+
+    let res = actions::GetObject::new(download.key, download.version.clone())
+        .if_match(download.etag.clone())
+        .range(part.seek, part.length)
+        .request(download.s3, download.globals)
+        .await?;
+
+    check_content_range(res.headers(), part)?;
+
+    Ok((
+        res,
+        DownloadPart {
+            number: None,
+            ..part
+        },
+    ))
+}
+
+/// The `Content-Range` of a part must be the requested bytes, so every part
+/// lands at its offset and together they cover the whole object
+fn check_content_range(headers: &HeaderMap, part: DownloadPart) -> Result<()> {
+    let range = headers
+        .get(CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok());
+
+    match range.and_then(parse_content_range) {
+        Some((first, last)) if first == part.seek && last + 1 == part.seek + part.length => Ok(()),
+        _ => Err(anyhow!(
+            "expected bytes {}-{} of the object, got Content-Range: {}; try -n 1",
+            part.seek,
+            part.seek + part.length - 1,
+            range.unwrap_or("none")
+        )),
+    }
+}
+
+// bytes first-last/size
+fn parse_content_range(value: &str) -> Option<(u64, u64)> {
+    let (range, _) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let (first, last) = range.split_once('-')?;
+
+    Some((first.parse().ok()?, last.parse().ok()?))
+}
+
+// the size of bytes first-last/size
+fn content_range_size(value: &str) -> Option<u64> {
+    let (_, size) = value.strip_prefix("bytes ")?.split_once('/')?;
+
+    size.parse().ok()
+}
+
+/// The checksum of a part fetched by `partNumber`. Composite (`-N`) and full
+/// object checksums can not be checked against a single part.
+fn part_checksum(headers: &HeaderMap, part: DownloadPart) -> Option<Checksum> {
+    part.number?;
+
+    if headers
+        .get("x-amz-checksum-type")
+        .is_some_and(|value| value != "COMPOSITE")
+    {
+        return None;
+    }
+
+    [
+        ChecksumAlgorithm::Crc32,
+        ChecksumAlgorithm::Crc32c,
+        ChecksumAlgorithm::Sha1,
+        ChecksumAlgorithm::Sha256,
+    ]
+    .into_iter()
+    .find_map(|algorithm| {
+        let value = headers.get(algorithm.as_amz())?.to_str().ok()?;
+
+        (!value.contains('-')).then(|| Checksum {
+            algorithm,
+            checksum: value.to_string(),
+        })
+    })
+}
+
 async fn download_response(
     res: &mut reqwest::Response,
     state: &mut DownloadState,
@@ -223,17 +891,40 @@ async fn download_response(
         let chunk_len = chunk.len();
         state.process_chunk(chunk).await?;
 
-        if let Some(bandwidth_kb) = globals.throttle {
-            throttle_download(bandwidth_kb, chunk_len).await?;
+        if let Some(bandwidth) = &globals.throttle {
+            bandwidth.consume(chunk_len).await;
         }
     }
 
     Ok(())
 }
 
+impl Output {
+    async fn write(&mut self, data: Bytes) -> Result<()> {
+        match self {
+            Self::File(file) => Ok(file.write_all(&data).await?),
+            Self::Stdout(stdout) => Ok(stdout.write_all(&data).await?),
+            Self::Decompress(decompressor) => decompressor.write(data).await,
+        }
+    }
+
+    async fn finish(self) -> Result<()> {
+        match self {
+            Self::File(mut file) => file.flush().await?,
+            Self::Stdout(mut stdout) => stdout.flush().await?,
+            Self::Decompress(decompressor) => {
+                let written = decompressor.finish().await?;
+                log::info!("decompressed {written} bytes");
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl DownloadState {
     fn new(
-        file: tokio::fs::File,
+        output: Output,
         pb: Bar,
         file_size: u64,
         is_encrypted: bool,
@@ -241,7 +932,7 @@ impl DownloadState {
         cipher: Option<ChaCha20Poly1305>,
     ) -> Self {
         Self {
-            file,
+            output,
             pb,
             file_size,
             downloaded: 0,
@@ -327,7 +1018,7 @@ impl DownloadState {
             )
             .map_err(|_| anyhow!("Decryption failed, check your encryption key"))?;
 
-            self.file.write_all(&decrypted_chunk).await?;
+            self.output.write(Bytes::from(decrypted_chunk)).await?;
             self.update_progress();
             self.buffer.advance(4 + len);
         }
@@ -336,8 +1027,7 @@ impl DownloadState {
     }
 
     async fn write_raw_buffer(&mut self) -> Result<()> {
-        self.file.write_all(&self.buffer).await?;
-        self.buffer.clear();
+        self.output.write(self.buffer.split().freeze()).await?;
         self.update_progress();
         Ok(())
     }
@@ -348,10 +1038,22 @@ impl DownloadState {
         }
     }
 
-    fn finish(&self) {
+    async fn finish(self) -> Result<()> {
+        // a partial chunk left means the object ended before its last tag
+        if self.can_decrypt && !self.buffer.is_empty() {
+            return Err(anyhow!(
+                "encrypted object is truncated, {} trailing bytes could not be decrypted",
+                self.buffer.len()
+            ));
+        }
+
+        self.output.finish().await?;
+
         if let Some(pb) = self.pb.progress.as_ref() {
             pb.finish();
         }
+
+        Ok(())
     }
 }
 
@@ -437,19 +1139,55 @@ async fn handle_versions(s3: &S3, key: &str, json: bool) -> Result<()> {
     Ok(())
 }
 
-fn determine_final_filename(file_name: &OsStr, can_decrypt: bool) -> OsString {
+fn determine_final_filename(
+    file_name: &OsStr,
+    can_decrypt: bool,
+    codec: Option<Codec>,
+) -> OsString {
+    let mut file_name = file_name.to_os_string();
+
     if can_decrypt {
-        let file_name_str = file_name.to_string_lossy();
-        if let Some(stripped) = file_name_str.strip_suffix(".enc") {
-            OsString::from(stripped)
-        } else {
-            file_name.to_os_string()
-        }
-    } else {
-        file_name.to_os_string()
+        file_name = strip_extension(&file_name, "enc");
+    }
+
+    if let Some(codec) = codec {
+        file_name = strip_extension(&file_name, codec.extension());
+    }
+
+    file_name
+}
+
+fn strip_extension(file_name: &OsStr, extension: &str) -> OsString {
+    let path = Path::new(file_name);
+
+    match path.file_stem() {
+        Some(stem) if path.extension() == Some(OsStr::new(extension)) => stem.to_os_string(),
+        _ => file_name.to_os_string(),
     }
 }
 
+/// Codec of a compressed object, from the `x-amz-meta-s3m-compression`
+/// metadata or else the extension of the key before `.enc`
+fn compression_codec(headers: &HeaderMap, key: &str) -> Option<Codec> {
+    headers
+        .get(METADATA_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .or_else(|| Codec::from_key(key.strip_suffix(".enc").unwrap_or(key)))
+}
+
+// --max-output, or MAX_DECOMPRESSION_RATIO times the object size
+fn max_output(max_output: Option<usize>, file_size: u64) -> u64 {
+    max_output.map_or_else(
+        || {
+            file_size
+                .saturating_mul(MAX_DECOMPRESSION_RATIO)
+                .max(MIN_MAX_OUTPUT)
+        },
+        |max_output| u64::try_from(max_output).unwrap_or(u64::MAX),
+    )
+}
+
 async fn create_output_file(path: &Path, force: bool) -> Result<tokio::fs::File> {
     let mut options = OpenOptions::new();
     options.write(true).create(true);
@@ -571,7 +1309,10 @@ fn normalize_json_timestamp(value: &str) -> Result<String> {
 )]
 mod tests {
     use super::*;
-    use crate::s3::{Credentials, Region, S3};
+    use crate::{
+        s3::{Credentials, Region, S3},
+        stream::compression::Compression,
+    };
     use anyhow::Result;
     use mockito::{Matcher, Server};
     use secrecy::SecretString;
@@ -579,7 +1320,14 @@ mod tests {
     #[tokio::test]
     async fn test_oversized_encrypted_chunk_rejected() {
         let file = tokio::fs::File::from_std(tempfile::tempfile().unwrap());
-        let mut state = DownloadState::new(file, Bar::default(), 1 << 30, true, true, None);
+        let mut state = DownloadState::new(
+            Output::File(file),
+            Bar::default(),
+            1 << 30,
+            true,
+            true,
+            None,
+        );
         // Move past the nonce-header stage by injecting a decryptor directly.
         let key = SecretString::new("0123456789abcdef0123456789abcdef".into());
         let (_, nonce) = crate::stream::init_encryption(&key).unwrap();
@@ -589,6 +1337,15 @@ mod tests {
         assert!(state.process_encrypted_buffer().await.is_err());
     }
 
+    #[tokio::test]
+    async fn test_truncated_encrypted_object_rejected() {
+        let file = tokio::fs::File::from_std(tempfile::tempfile().unwrap());
+        let mut state = DownloadState::new(Output::File(file), Bar::default(), 3, true, true, None);
+        state.buffer.extend_from_slice(&[0, 0, 1]);
+        let err = state.finish().await.unwrap_err();
+        assert!(err.to_string().contains("truncated"), "{err:#}");
+    }
+
     struct Test {
         dest: Option<String>,
         file_name: &'static OsStr,
@@ -710,6 +1467,621 @@ mod tests {
         assert!(err.contains("Failed to parse Last-Modified header"));
     }
 
+    #[test]
+    fn test_determine_final_filename() {
+        let tests = [
+            ("db.sql.zst.enc", true, Some(Codec::Zstd), "db.sql"),
+            ("db.sql.zst.enc", false, None, "db.sql.zst.enc"),
+            ("db.sql.gz", false, Some(Codec::Gzip), "db.sql"),
+            ("db.sql.gz", false, None, "db.sql.gz"),
+            ("db.sql", false, Some(Codec::Xz), "db.sql"),
+            ("db.sql.enc", true, None, "db.sql"),
+            (".zst", false, Some(Codec::Zstd), ".zst"),
+        ];
+
+        for (file_name, can_decrypt, codec, expected) in tests {
+            assert_eq!(
+                determine_final_filename(OsStr::new(file_name), can_decrypt, codec),
+                OsString::from(expected),
+                "{file_name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_compression_codec() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(compression_codec(&headers, "db.sql"), None);
+        assert_eq!(compression_codec(&headers, "db.sql.lz4"), Some(Codec::Lz4));
+        assert_eq!(
+            compression_codec(&headers, "db.sql.zst.enc"),
+            Some(Codec::Zstd)
+        );
+
+        // the metadata written at upload time wins over the key
+        headers.insert(METADATA_HEADER, "gzip".parse().unwrap());
+        assert_eq!(compression_codec(&headers, "db.sql.zst"), Some(Codec::Gzip));
+        assert_eq!(compression_codec(&headers, "db.sql"), Some(Codec::Gzip));
+    }
+
+    #[test]
+    fn test_max_output() {
+        assert_eq!(max_output(Some(10), 1 << 40), 10);
+        assert_eq!(max_output(None, 1), MIN_MAX_OUTPUT);
+        assert_eq!(
+            max_output(None, 1 << 40),
+            (1 << 40) * MAX_DECOMPRESSION_RATIO
+        );
+        assert_eq!(max_output(None, u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn test_parse_content_range() {
+        assert_eq!(parse_content_range("bytes 0-99/1000"), Some((0, 99)));
+        assert_eq!(parse_content_range("bytes 100-199/*"), Some((100, 199)));
+        assert_eq!(parse_content_range("bytes */1000"), None);
+        assert_eq!(parse_content_range("0-99/1000"), None);
+        assert_eq!(content_range_size("bytes 0-99/1000"), Some(1000));
+        assert_eq!(content_range_size("bytes 100-199/*"), None);
+    }
+
+    #[test]
+    fn test_part_checksum() {
+        let part = DownloadPart {
+            number: Some(2),
+            seek: 4,
+            length: 4,
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert("x-amz-checksum-crc32c", "yZRlqg==".parse().unwrap());
+
+        let checksum = part_checksum(&headers, part).unwrap();
+        assert_eq!(checksum.algorithm, ChecksumAlgorithm::Crc32c);
+        assert_eq!(checksum.checksum, "yZRlqg==");
+
+        // ranges carry no part checksum
+        assert!(
+            part_checksum(
+                &headers,
+                DownloadPart {
+                    number: None,
+                    ..part
+                }
+            )
+            .is_none()
+        );
+
+        headers.insert("x-amz-checksum-type", "FULL_OBJECT".parse().unwrap());
+        assert!(part_checksum(&headers, part).is_none());
+
+        headers.insert("x-amz-checksum-type", "COMPOSITE".parse().unwrap());
+        headers.insert("x-amz-checksum-crc32c", "yZRlqg==-3".parse().unwrap());
+        assert!(part_checksum(&headers, part).is_none());
+    }
+
+    fn parallel_request(key: &str, dest: &Path) -> DownloadRequest {
+        DownloadRequest {
+            key: key.to_string(),
+            version: None,
+            dest: Some(dest.display().to_string()),
+            quiet: true,
+            force: false,
+            raw: false,
+            max_output: None,
+            number: 2,
+        }
+    }
+
+    fn crc32(data: &[u8]) -> String {
+        Checksum::new(ChecksumAlgorithm::Crc32)
+            .digest(data)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_download_parallel_by_part_number() {
+        let data = b"0123456789";
+        let mut server = Server::new_async().await;
+        let _head = server
+            .mock("HEAD", "/bucket/backup.tar")
+            .match_query(Matcher::UrlEncoded("partNumber".into(), "1".into()))
+            .with_status(206)
+            .with_header("content-length", "4")
+            .with_header("content-range", "bytes 0-3/10")
+            .with_header("etag", "\"etag-3\"")
+            .with_header("x-amz-mp-parts-count", "3")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut mocks = Vec::new();
+        for (number, first, last) in [(1, 0, 3), (2, 4, 7), (3, 8, 9)] {
+            let body = &data[first..=last];
+            mocks.push(
+                server
+                    .mock("GET", "/bucket/backup.tar")
+                    .match_query(Matcher::UrlEncoded("partNumber".into(), number.to_string()))
+                    .match_header("if-match", "\"etag-3\"")
+                    .match_header("x-amz-checksum-mode", "ENABLED")
+                    .with_status(206)
+                    .with_header("content-range", &format!("bytes {first}-{last}/10"))
+                    .with_header("x-amz-checksum-crc32", &crc32(body))
+                    .with_body(body)
+                    .create_async()
+                    .await,
+            );
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.tar");
+        let s3 = test_s3(server.url());
+        let request = parallel_request("backup.tar", &path);
+
+        let head = head_object(&s3, &request).await.unwrap();
+        assert_eq!(head.size, 10);
+        assert_eq!(head.parts, Some((3, 4)));
+
+        download_parallel(&s3, &request, &path, &head, &GlobalArgs::new())
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_download_parallel_parts_of_different_sizes() {
+        let data = b"0123456789";
+        let mut server = Server::new_async().await;
+        // parts of 4, 1 and 5 bytes fit 3 parts of the first size
+        let _head = server
+            .mock("HEAD", "/bucket/backup.tar")
+            .match_query(Matcher::UrlEncoded("partNumber".into(), "1".into()))
+            .with_status(206)
+            .with_header("content-length", "4")
+            .with_header("content-range", "bytes 0-3/10")
+            .with_header("etag", "\"etag-3\"")
+            .with_header("x-amz-mp-parts-count", "3")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut mocks = Vec::new();
+        for (number, first, last, expect) in [(1, 0, 3, 1), (2, 4, 4, 1), (3, 5, 9, 0)] {
+            let body = &data[first..=last];
+            mocks.push(
+                server
+                    .mock("GET", "/bucket/backup.tar")
+                    .match_query(Matcher::UrlEncoded("partNumber".into(), number.to_string()))
+                    .with_status(206)
+                    .with_header("content-range", &format!("bytes {first}-{last}/10"))
+                    .with_header("x-amz-checksum-crc32", &crc32(body))
+                    .with_body(body)
+                    .expect(expect)
+                    .create_async()
+                    .await,
+            );
+        }
+        for (first, last) in [(4, 7), (8, 9)] {
+            mocks.push(
+                server
+                    .mock("GET", "/bucket/backup.tar")
+                    .match_query(Matcher::Missing)
+                    .match_header("range", format!("bytes={first}-{last}").as_str())
+                    .match_header("if-match", "\"etag-3\"")
+                    .with_status(206)
+                    .with_header("content-range", &format!("bytes {first}-{last}/10"))
+                    .with_body(&data[first..=last])
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.tar");
+        let s3 = test_s3(server.url());
+        let request = parallel_request("backup.tar", &path);
+
+        let head = head_object(&s3, &request).await.unwrap();
+        assert_eq!(head.parts, Some((3, 4)));
+
+        download_parallel(&s3, &request, &path, &head, &GlobalArgs::new())
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_download_parallel_checksum_mismatch_removes_file() {
+        let mut server = Server::new_async().await;
+        let _head = server
+            .mock("HEAD", "/bucket/backup.tar")
+            .match_query(Matcher::UrlEncoded("partNumber".into(), "1".into()))
+            .with_status(206)
+            .with_header("content-length", "5")
+            .with_header("content-range", "bytes 0-4/10")
+            .with_header("x-amz-mp-parts-count", "2")
+            .create_async()
+            .await;
+        let _first = server
+            .mock("GET", "/bucket/backup.tar")
+            .match_query(Matcher::UrlEncoded("partNumber".into(), "1".into()))
+            .with_status(206)
+            .with_header("content-range", "bytes 0-4/10")
+            .with_header("x-amz-checksum-crc32", &crc32(b"01234"))
+            .with_body("01234")
+            .create_async()
+            .await;
+        let _second = server
+            .mock("GET", "/bucket/backup.tar")
+            .match_query(Matcher::UrlEncoded("partNumber".into(), "2".into()))
+            .with_status(206)
+            .with_header("content-range", "bytes 5-9/10")
+            .with_header("x-amz-checksum-crc32", &crc32(b"56789"))
+            .with_body("5678X")
+            .create_async()
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.tar");
+        let s3 = test_s3(server.url());
+        let request = parallel_request("backup.tar", &path);
+        let mut globals = GlobalArgs::new();
+        globals.retries = 1;
+
+        let head = head_object(&s3, &request).await.unwrap();
+        let err = download_parallel(&s3, &request, &path, &head, &globals)
+            .await
+            .unwrap_err();
+
+        assert!(
+            err.to_string()
+                .contains("CRC32 checksum mismatch for part 2"),
+            "{err:#}"
+        );
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_download_parallel_by_range() {
+        let mut server = Server::new_async().await;
+        let _head = server
+            .mock("HEAD", "/bucket/backup.tar")
+            .match_query(Matcher::UrlEncoded("partNumber".into(), "1".into()))
+            .with_status(200)
+            .with_header("content-length", "10")
+            .create_async()
+            .await;
+        let get = server
+            .mock("GET", "/bucket/backup.tar")
+            .match_header("range", "bytes=0-9")
+            .with_status(206)
+            .with_header("content-range", "bytes 0-9/10")
+            .with_body("0123456789")
+            .create_async()
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.tar");
+        let s3 = test_s3(server.url());
+        let request = parallel_request("backup.tar", &path);
+
+        let head = head_object(&s3, &request).await.unwrap();
+        assert_eq!(head.parts, None);
+
+        download_parallel(&s3, &request, &path, &head, &GlobalArgs::new())
+            .await
+            .unwrap();
+
+        get.assert_async().await;
+        assert_eq!(std::fs::read(&path).unwrap(), b"0123456789");
+    }
+
+    #[tokio::test]
+    async fn test_head_object_without_content_range() {
+        let mut server = Server::new_async().await;
+        let part = server
+            .mock("HEAD", "/bucket/backup.tar")
+            .match_query(Matcher::UrlEncoded("partNumber".into(), "1".into()))
+            .with_status(206)
+            .with_header("content-length", "4")
+            .with_header("x-amz-mp-parts-count", "3")
+            .expect(1)
+            .create_async()
+            .await;
+        let object = server
+            .mock("HEAD", "/bucket/backup.tar")
+            .match_query(Matcher::Missing)
+            .with_status(200)
+            .with_header("content-length", "10")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let request = parallel_request("backup.tar", &dir.path().join("backup.tar"));
+        let head = head_object(&test_s3(server.url()), &request).await.unwrap();
+
+        assert_eq!(head.size, 10);
+        assert_eq!(head.parts, Some((3, 4)));
+        part.assert_async().await;
+        object.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_download_small_object_in_first_range() {
+        let mut server = Server::new_async().await;
+        let head = server
+            .mock("HEAD", "/bucket/notes.txt")
+            .match_query(Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+        let get = server
+            .mock("GET", "/bucket/notes.txt")
+            .match_header("range", "bytes=0-67108863")
+            .with_status(206)
+            .with_header("content-range", "bytes 0-4/5")
+            .with_body("hello")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut action = download_action("notes.txt", dir.path(), false, None);
+        if let Action::GetObject { number, .. } = &mut action {
+            *number = 4;
+        }
+
+        handle(&test_s3(server.url()), action, GlobalArgs::new())
+            .await
+            .unwrap();
+
+        head.assert_async().await;
+        get.assert_async().await;
+        assert_eq!(
+            std::fs::read(dir.path().join("notes.txt")).unwrap(),
+            b"hello"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_empty_object_after_unsatisfiable_range() {
+        let mut server = Server::new_async().await;
+        let ranged = server
+            .mock("GET", "/bucket/empty.txt")
+            .match_header("range", "bytes=0-67108863")
+            .with_status(416)
+            .with_body(
+                r"<Error><Code>InvalidRange</Code><Message>The requested range is not satisfiable</Message><RequestId>1</RequestId></Error>",
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let get = server
+            .mock("GET", "/bucket/empty.txt")
+            .match_header("range", Matcher::Missing)
+            .with_status(200)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut action = download_action("empty.txt", dir.path(), false, None);
+        if let Action::GetObject { number, .. } = &mut action {
+            *number = 4;
+        }
+
+        handle(&test_s3(server.url()), action, GlobalArgs::new())
+            .await
+            .unwrap();
+
+        ranged.assert_async().await;
+        get.assert_async().await;
+        assert!(
+            std::fs::read(dir.path().join("empty.txt"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_first_request_heads_parts_of_large_object() {
+        let mut server = Server::new_async().await;
+        let ranged = server
+            .mock("GET", "/bucket/backup.tar")
+            .match_header("range", "bytes=0-67108863")
+            .with_status(206)
+            .with_header("content-range", "bytes 0-67108863/100000000")
+            .with_header("etag", "\"etag-1\"")
+            .with_body("0123456789")
+            .expect(1)
+            .create_async()
+            .await;
+        let head = server
+            .mock("HEAD", "/bucket/backup.tar")
+            .match_query(Matcher::UrlEncoded("partNumber".into(), "1".into()))
+            .with_status(206)
+            .with_header("content-length", "50000000")
+            .with_header("content-range", "bytes 0-49999999/100000000")
+            .with_header("x-amz-mp-parts-count", "2")
+            .with_header("etag", "\"etag-1\"")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let request = parallel_request("backup.tar", &dir.path().join("backup.tar"));
+        let download = first_request(&test_s3(server.url()), &request, &GlobalArgs::new())
+            .await
+            .unwrap();
+
+        let Download::Parallel(head_parts) = download else {
+            panic!("expected a parallel download");
+        };
+        assert_eq!(head_parts.size, 100_000_000);
+        assert_eq!(head_parts.parts, Some((2, 50_000_000)));
+        ranged.assert_async().await;
+        head.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_first_request_reads_large_encrypted_object_in_order() {
+        let mut server = Server::new_async().await;
+        let head = server
+            .mock("HEAD", "/bucket/secret.enc")
+            .match_query(Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+        let ranged = server
+            .mock("GET", "/bucket/secret.enc")
+            .match_header("range", "bytes=0-67108863")
+            .with_status(206)
+            .with_header("content-range", "bytes 0-67108863/100000000")
+            .with_header("content-type", "application/vnd.s3m.encrypted")
+            .with_header("etag", "\"etag-1\"")
+            .with_body("ciphertext")
+            .expect(1)
+            .create_async()
+            .await;
+        let get = server
+            .mock("GET", "/bucket/secret.enc")
+            .match_header("range", Matcher::Missing)
+            .match_header("if-match", "\"etag-1\"")
+            .with_status(200)
+            .with_body("ciphertext")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let request = parallel_request("secret.enc", &dir.path().join("secret.enc"));
+        let download = first_request(&test_s3(server.url()), &request, &GlobalArgs::new())
+            .await
+            .unwrap();
+
+        assert!(matches!(download, Download::Sequential(_)));
+        head.assert_async().await;
+        ranged.assert_async().await;
+        get.assert_async().await;
+    }
+
+    fn download_action(key: &str, dest: &Path, raw: bool, max_output: Option<usize>) -> Action {
+        Action::GetObject {
+            dest: Some(dest.display().to_string()),
+            metadata: false,
+            key: key.to_string(),
+            quiet: true,
+            force: false,
+            json: false,
+            versions: false,
+            version: None,
+            raw,
+            max_output,
+            number: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_download_stdout_fails_on_bad_tag() {
+        // nonce header, then a 20-byte chunk that does not authenticate
+        let mut body = vec![7, 1, 2, 3, 4, 5, 6, 7];
+        body.extend_from_slice(&20_u32.to_be_bytes());
+        body.extend_from_slice(&[0; 20]);
+        let mut server = Server::new_async().await;
+        let _get = server
+            .mock("GET", "/bucket/db.sql.enc")
+            .with_status(200)
+            .with_header("content-type", "application/vnd.s3m.encrypted")
+            .with_body(&body)
+            .create_async()
+            .await;
+        let mut globals = GlobalArgs::new();
+        globals.enc_key = Some(SecretString::new("0123456789abcdef0123456789abcdef".into()));
+
+        let err = handle(
+            &test_s3(server.url()),
+            Action::GetObject {
+                dest: Some(STDOUT.to_string()),
+                metadata: false,
+                key: "db.sql.enc".to_string(),
+                quiet: true,
+                force: false,
+                json: false,
+                versions: false,
+                version: None,
+                raw: false,
+                max_output: None,
+                number: 1,
+            },
+            globals,
+        )
+        .await
+        .unwrap_err();
+        let err = format!("{err:#}");
+        assert!(err.contains("stdout is incomplete"), "{err}");
+        assert!(err.contains("Decryption failed"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_download_decompresses_object() {
+        let data = b"restore me ".repeat(10_000);
+        let body = Compression::new(Codec::Gzip).compress(&data).unwrap();
+        let mut server = Server::new_async().await;
+        let _get = server
+            .mock("GET", "/bucket/db.sql.gz")
+            .with_status(200)
+            .with_header("content-type", "application/gzip")
+            .with_header(METADATA_HEADER, "gzip")
+            .with_body(&body)
+            .expect(3)
+            .create_async()
+            .await;
+        let s3 = test_s3(server.url());
+        let dir = tempfile::tempdir().unwrap();
+
+        handle(
+            &s3,
+            download_action("db.sql.gz", dir.path(), false, None),
+            GlobalArgs::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(std::fs::read(dir.path().join("db.sql")).unwrap(), data);
+
+        handle(
+            &s3,
+            download_action("db.sql.gz", dir.path(), true, None),
+            GlobalArgs::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(std::fs::read(dir.path().join("db.sql.gz")).unwrap(), body);
+
+        let err = handle(
+            &s3,
+            download_action(
+                "db.sql.gz",
+                &dir.path().join("bomb.sql"),
+                false,
+                Some(1_000),
+            ),
+            GlobalArgs::new(),
+        )
+        .await
+        .unwrap_err();
+        assert!(format!("{err:#}").contains("1000-byte limit"), "{err:#}");
+        assert!(!dir.path().join("bomb.sql").exists());
+    }
+
     fn test_s3(endpoint: String) -> S3 {
         S3::new(
             &Credentials::new(
@@ -746,6 +2118,9 @@ mod tests {
                 json: true,
                 versions: false,
                 version: None,
+                raw: false,
+                max_output: None,
+                number: 1,
             },
             GlobalArgs::new(),
         )
@@ -781,6 +2156,9 @@ mod tests {
                 json: true,
                 versions: true,
                 version: None,
+                raw: false,
+                max_output: None,
+                number: 1,
             },
             GlobalArgs::new(),
         )
@@ -1,5 +1,9 @@
 use crate::{
-    cli::{actions::Action, globals::GlobalArgs, progressbar::Bar},
+    cli::{
+        actions::{Action, PutDestination},
+        globals::GlobalArgs,
+        progressbar::Bar,
+    },
     s3::{
         S3,
         checksum::{Checksum, ChecksumAlgorithm},
@@ -7,9 +11,14 @@ use crate::{
         tools,
     },
     stream::{
-        FileStreamUpload,
+        FileStreamUpload, StdinStreamUpload,
+        compression::Compression,
         db::Db,
+        fanout::{Destination, Require},
+        part_size::PartSize,
+        spool::SpoolOptions,
         state::{StreamMetadata, StreamMode, write_metadata},
+        transform::CheckpointOptions,
         upload_compressed::stream_compressed,
         upload_compressed_encrypted::stream_compressed_encrypted,
         upload_default::{UploadRequest, upload},
@@ -22,6 +31,7 @@ use crate::{
     },
 };
 use anyhow::{Context, Result, anyhow};
+use colored::Colorize;
 use std::{
     collections::BTreeMap,
     fs::metadata,
@@ -40,9 +50,15 @@ struct PutObjectRequest {
     pipe: bool,
     s3m_dir: PathBuf,
     quiet: bool,
+    spool: bool,
     tmp_dir: PathBuf,
     checksum_algorithm: Option<String>,
     number: u8,
+    max_staging: usize,
+    max_memory: Option<usize>,
+    size_hint: Option<usize>,
+    require: Require,
+    destinations: Vec<PutDestination>,
 }
 
 struct LocalFile {
@@ -64,9 +80,15 @@ pub async fn handle(s3: &S3, action: Action, globals: GlobalArgs) -> Result<()>
         pipe,
         s3m_dir,
         quiet,
+        spool,
         tmp_dir,
         checksum_algorithm,
         number,
+        max_staging,
+        max_memory,
+        size_hint,
+        require,
+        destinations,
     } = action
     {
         let has_object_lock = globals.object_lock.is_some();
@@ -82,9 +104,15 @@ pub async fn handle(s3: &S3, action: Action, globals: GlobalArgs) -> Result<()>
                 pipe,
                 s3m_dir,
                 quiet,
+                spool,
                 tmp_dir,
                 checksum_algorithm,
                 number,
+                max_staging,
+                max_memory,
+                size_hint,
+                require,
+                destinations,
             },
             globals,
         )
@@ -117,9 +145,7 @@ async fn handle_put_object(s3: &S3, request: PutObjectRequest, globals: GlobalAr
     let quiet = request.quiet;
 
     if request.pipe {
-        let etag = handle_pipe_upload(s3, request, globals).await?;
-        print_etag(&etag, quiet);
-        return Ok(());
+        return handle_pipe_upload(s3, request, globals).await;
     }
 
     let local_file = request.load_local_file()?;
@@ -130,37 +156,101 @@ async fn handle_put_object(s3: &S3, request: PutObjectRequest, globals: GlobalAr
     Ok(())
 }
 
-async fn handle_pipe_upload(
-    s3: &S3,
-    request: PutObjectRequest,
-    globals: GlobalArgs,
-) -> Result<String> {
+async fn handle_pipe_upload(s3: &S3, request: PutObjectRequest, globals: GlobalArgs) -> Result<()> {
     let PutObjectRequest {
         acl,
         meta,
+        host,
         key,
+        s3m_dir,
         quiet,
+        spool,
         tmp_dir,
+        number,
+        max_staging,
+        max_memory,
+        size_hint,
+        require,
+        destinations,
         ..
     } = request;
 
     log::debug!("PIPE - streaming from stdin");
 
-    match (globals.compress, globals.encrypt) {
+    // destinations are built with their own host, retry them like the main S3
+    let destinations: Vec<PutDestination> = destinations
+        .into_iter()
+        .map(|destination| PutDestination {
+            s3: destination.s3.with_retry_policy(s3.retry_policy()),
+            ..destination
+        })
+        .collect();
+
+    // every destination may use the credentials provider of its own host
+    for destination in &destinations {
+        destination.s3.refresh_credentials().await?;
+    }
+
+    let targets: Vec<(&str, &S3, &str)> = std::iter::once((host.as_str(), s3, key.as_str()))
+        .chain(destinations.iter().map(|destination| {
+            (
+                destination.host.as_str(),
+                &destination.s3,
+                destination.key.as_str(),
+            )
+        }))
+        .collect();
+
+    let upload = StdinStreamUpload {
+        destinations: targets
+            .iter()
+            .map(|&(host, s3, key)| Destination {
+                s3,
+                object_key: key,
+                label: format!("{host}/{}/{key}", s3.bucket().unwrap_or_default()),
+                spool: spool.then(|| SpoolOptions {
+                    s3m_dir: s3m_dir.clone(),
+                    host: host.to_string(),
+                }),
+            })
+            .collect(),
+        require,
+        acl,
+        meta,
+        quiet,
+        tmp_dir,
+        size_hint: size_hint.map(|size| size as u64),
+        max_requests: number,
+        max_staging: max_staging as u64,
+        max_memory: max_memory.map(|size| size as u64),
+        globals,
+    };
+
+    let labels: Vec<String> = upload
+        .destinations
+        .iter()
+        .map(|destination| destination.label.clone())
+        .collect();
+
+    let results = match (upload.globals.compress, upload.globals.encrypt) {
         (true, true) => {
             log::info!("COMPRESS + ENCRYPT - streaming compressed and encrypted data from stdin");
-            stream_stdin_compressed_encrypted(s3, &key, acl, meta, quiet, tmp_dir, globals).await
+            stream_stdin_compressed_encrypted(upload).await
         }
         (true, false) => {
             log::info!("COMPRESS - streaming compressed data from stdin");
-            stream_stdin_compressed(s3, &key, acl, meta, quiet, tmp_dir, globals).await
+            stream_stdin_compressed(upload).await
         }
         (false, true) => {
             log::info!("ENCRYPT - streaming encrypted data from stdin");
-            stream_stdin_encrypted(s3, &key, acl, meta, quiet, tmp_dir, globals).await
+            stream_stdin_encrypted(upload).await
         }
-        (false, false) => stream_stdin(s3, &key, acl, meta, quiet, tmp_dir, globals).await,
-    }
+        (false, false) => stream_stdin(upload).await,
+    }?;
+
+    print_pipe_results(&labels, results, quiet);
+
+    Ok(())
 }
 
 async fn handle_file_upload(
@@ -169,55 +259,55 @@ async fn handle_file_upload(
     globals: GlobalArgs,
     local_file: LocalFile,
 ) -> Result<Option<String>> {
-    let file_path = local_file.path.as_path();
+    let (compress, encrypt) = (globals.compress, globals.encrypt);
+
+    if !compress && !encrypt {
+        return handle_standard_file_upload(s3, request, globals, local_file).await;
+    }
 
-    match (globals.compress, globals.encrypt) {
+    // files of several parts keep a state to resume the upload
+    let checkpoint = if local_file.size > PartSize::from_hint(Some(local_file.size))?.size(1) {
+        let checksum = blake3_checksum(&local_file.path, request.quiet)?;
+        log::info!("checksum: {checksum}");
+
+        Some(CheckpointOptions {
+            s3m_dir: request.s3m_dir.clone(),
+            host: request.host.clone(),
+            checksum,
+            mtime: local_file.mtime,
+        })
+    } else {
+        None
+    };
+
+    let upload = FileStreamUpload {
+        s3,
+        object_key: &request.key,
+        acl: request.acl,
+        meta: request.meta,
+        quiet: request.quiet,
+        tmp_dir: request.tmp_dir,
+        globals,
+        file_path: local_file.path.as_path(),
+        max_requests: request.number,
+        max_staging: request.max_staging as u64,
+        max_memory: request.max_memory.map(|size| size as u64),
+        checkpoint,
+    };
+
+    match (compress, encrypt) {
         (true, true) => {
             log::info!("COMPRESS + ENCRYPT - streaming encrypted and compressed file");
-            stream_compressed_encrypted(FileStreamUpload {
-                s3,
-                object_key: &request.key,
-                acl: request.acl,
-                meta: request.meta,
-                quiet: request.quiet,
-                tmp_dir: request.tmp_dir,
-                globals,
-                file_path,
-            })
-            .await
-            .map(Some)
+            stream_compressed_encrypted(upload).await.map(Some)
         }
         (true, false) => {
             log::info!("COMPRESS - streaming compressed file");
-            stream_compressed(FileStreamUpload {
-                s3,
-                object_key: &request.key,
-                acl: request.acl,
-                meta: request.meta,
-                quiet: request.quiet,
-                tmp_dir: request.tmp_dir,
-                globals,
-                file_path,
-            })
-            .await
-            .map(Some)
+            stream_compressed(upload).await.map(Some)
         }
-        (false, true) => {
+        _ => {
             log::info!("ENCRYPT - streaming encrypted file");
-            stream_encrypted(FileStreamUpload {
-                s3,
-                object_key: &request.key,
-                acl: request.acl,
-                meta: request.meta,
-                quiet: request.quiet,
-                tmp_dir: request.tmp_dir,
-                globals,
-                file_path,
-            })
-            .await
-            .map(Some)
+            stream_encrypted(upload).await.map(Some)
         }
-        (false, false) => handle_standard_file_upload(s3, request, globals, local_file).await,
     }
 }
 
@@ -349,6 +439,7 @@ fn maybe_write_stream_metadata(
             updated_at: Some(created_at),
             pipe: false,
             compress: false,
+            compression: Compression::default(),
             encrypt: false,
             mode: StreamMode::FileMultipart,
         },
@@ -394,6 +485,26 @@ fn print_etag(etag: &str, quiet: bool) {
     }
 }
 
+/// Print the `ETag` of every destination of a `--pipe` upload, and the
+/// destinations that failed when `--require any` allowed it
+fn print_pipe_results(labels: &[String], results: Vec<Result<String>>, quiet: bool) {
+    if let [Ok(etag)] = results.as_slice() {
+        print_etag(etag, quiet);
+        return;
+    }
+
+    for (label, result) in labels.iter().zip(results) {
+        match result {
+            Ok(etag) => {
+                if !quiet {
+                    println!("{label} ETag: {etag}");
+                }
+            }
+            Err(e) => eprintln!("{} {e}", format!("{label}:").red()),
+        }
+    }
+}
+
 /// Calculate the blake3 checksum of a file
 /// # Errors
 /// Will return an error if the checksum fails
@@ -480,6 +591,7 @@ mod tests {
             code: Some(code.to_string()),
             message: None,
             details: String::new(),
+            retry_after: None,
         }))
     }
 
@@ -0,0 +1,74 @@
+use crate::{
+    cli::actions::Action,
+    s3::{
+        S3,
+        actions::{DeleteObjectTagging, GetObjectTagging, PutObjectTagging},
+        responses::ObjectTagging,
+    },
+};
+use anyhow::Result;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+/// # Errors
+/// Will return an error if the action fails
+pub async fn handle(s3: &S3, action: Action) -> Result<()> {
+    match action {
+        Action::ObjectTagGet {
+            key,
+            version_id,
+            json,
+        } => {
+            let tagging = GetObjectTagging::new(&key, version_id.clone())
+                .request(s3)
+                .await?;
+            let tags = tags_map(&tagging);
+
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&json!({
+                        "kind": "tagging",
+                        "key": key,
+                        "version_id": version_id,
+                        "tags": tags,
+                    }))?
+                );
+            } else if tags.is_empty() {
+                println!("No tags");
+            } else {
+                for (k, v) in &tags {
+                    println!("{k}={v}");
+                }
+            }
+        }
+        Action::ObjectTagSet {
+            key,
+            version_id,
+            tagging,
+        } => {
+            PutObjectTagging::new(&key, &tagging, version_id)
+                .request(s3)
+                .await?;
+            println!("tags set: {}", tagging.tags().len());
+        }
+        Action::ObjectTagDelete { key, version_id } => {
+            DeleteObjectTagging::new(&key, version_id)
+                .request(s3)
+                .await?;
+            println!("tags removed");
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn tags_map(tagging: &ObjectTagging) -> BTreeMap<&str, &str> {
+    tagging
+        .tag_set
+        .tags
+        .iter()
+        .map(|tag| (tag.key.as_str(), tag.value.as_str()))
+        .collect()
+}
@@ -5,12 +5,16 @@ use crate::{
         globals::GlobalArgs,
         start::get_host,
     },
-    s3::{Credentials, S3},
     stream::{
+        FileStreamUpload,
         db::Db,
+        pipeline::DEFAULT_MAX_STAGING,
+        spool::Spool,
         state::{
-            CleanSummary, StreamEntry, StreamMetadata, StreamStatus, clean_streams, scan_streams,
+            CleanSummary, StreamEntry, StreamMetadata, StreamMode, StreamStatus, clean_streams,
+            scan_streams,
         },
+        transform::{Checkpoint, resume_file},
         upload_multipart::{MultipartUploadRequest, upload_multipart},
     },
 };
@@ -18,6 +22,7 @@ use anyhow::{Context, Result, anyhow};
 use bytesize::ByteSize;
 use chrono::{DateTime, Utc};
 use colored::Colorize;
+use secrecy::SecretString;
 use serde::Serialize;
 use std::{
     fs,
@@ -358,7 +363,8 @@ fn validate_resume_entry(entry: &StreamEntry, id: &str) -> Result<StreamMetadata
         .clone()
         .ok_or_else(|| anyhow!("Stream state {id} is missing metadata"))?;
 
-    if stream_metadata.pipe || stream_metadata.compress || stream_metadata.encrypt {
+    // spooled pipes are resumed from the captured parts
+    if stream_metadata.mode == StreamMode::FileMultipart && stream_metadata.pipe {
         return Err(anyhow!(
             "Stream state {id} is not resumable through `streams resume`"
         ));
@@ -428,11 +434,14 @@ async fn resume_stream(
     streams_dir: &Path,
     id: &str,
     number: u8,
-    globals: GlobalArgs,
+    mut globals: GlobalArgs,
 ) -> Result<()> {
     let entry = find_entry(streams_dir, id)?;
     let stream_metadata = validate_resume_entry(&entry, id)?;
-    let file_metadata = validate_resume_file(&stream_metadata)?;
+    let file_metadata = match stream_metadata.mode {
+        StreamMode::FileMultipart => Some(validate_resume_file(&stream_metadata)?),
+        StreamMode::PipeSpool => None,
+    };
 
     let config = Config::new(config_file.to_path_buf())?;
     let location = crate::cli::s3_location::parse_location(
@@ -444,20 +453,73 @@ async fn resume_stream(
         false,
     )?;
     let host = get_host(&config, config_dir(config_file), &location)?;
-    let region = host.get_region()?;
-    let credentials = Credentials::new(&host.access_key, &host.secret_key);
-    let s3 = S3::new(
-        &credentials,
-        &region,
-        Some(stream_metadata.bucket.clone()),
-        false,
-    );
+
+    if globals.throttle.is_none() {
+        globals.throttle = host.bandwidth();
+    }
+
+    let s3 = host
+        .get_s3(Some(stream_metadata.bucket.clone()), false)?
+        .with_retry_policy(globals.retry_policy());
+    s3.refresh_credentials().await?;
 
     let db = validate_resume_db(&entry, &stream_metadata, id)?;
 
+    let Some(file_metadata) = file_metadata else {
+        let spool = Spool::open(entry.id.clone(), db)?;
+        let etag = spool
+            .resume(&s3, &stream_metadata.key, &globals, false)
+            .await?;
+
+        println!("{etag}");
+
+        return Ok(());
+    };
+
     let file_size = file_metadata.len();
     let max_requests = calculate_max_requests(number);
 
+    // compressed and encrypted parts are transformed again from the file
+    if stream_metadata.compress || stream_metadata.encrypt {
+        if stream_metadata.encrypt {
+            let enc_key = host.enc_key.as_ref().ok_or_else(|| {
+                anyhow!(
+                    "Stream state {id} is encrypted, host {} has no enc_key",
+                    stream_metadata.host
+                )
+            })?;
+            globals.enc_key = Some(SecretString::new(enc_key.clone().into()));
+        }
+
+        // chunks are compressed again with the codec the upload started with
+        globals.compression = stream_metadata.compression;
+
+        let etag = resume_file(
+            FileStreamUpload {
+                s3: &s3,
+                object_key: &stream_metadata.key,
+                acl: None,
+                meta: None,
+                quiet: false,
+                tmp_dir: std::env::temp_dir(),
+                globals,
+                file_path: &stream_metadata.source_path,
+                max_requests,
+                max_staging: DEFAULT_MAX_STAGING,
+                max_memory: None,
+                checkpoint: None,
+            },
+            Checkpoint::open(entry.id.clone(), db),
+            stream_metadata.compress,
+            stream_metadata.encrypt,
+        )
+        .await?;
+
+        println!("ETag: {etag}");
+
+        return Ok(());
+    }
+
     let etag = upload_multipart(MultipartUploadRequest {
         s3: &s3,
         key: &stream_metadata.key,
@@ -497,8 +559,9 @@ mod tests {
     use super::*;
     use crate::{
         cli::globals::GlobalArgs,
-        s3::{Credentials, Region},
+        s3::{Credentials, Region, S3},
         stream::{
+            compression::Compression,
             db::Db,
             state::{StreamMode, write_metadata},
         },
@@ -552,6 +615,7 @@ mod tests {
                 updated_at: Some(1),
                 pipe: false,
                 compress: false,
+                compression: Compression::default(),
                 encrypt: false,
                 mode: StreamMode::FileMultipart,
             }),
@@ -621,6 +685,7 @@ mod tests {
                 updated_at: Some(1),
                 pipe: false,
                 compress: false,
+                compression: Compression::default(),
                 encrypt: false,
                 mode: StreamMode::FileMultipart,
             }),
@@ -708,6 +773,7 @@ mod tests {
                 updated_at: Some(1),
                 pipe: false,
                 compress: false,
+                compression: Compression::default(),
                 encrypt: false,
                 mode: StreamMode::FileMultipart,
             },
@@ -740,6 +806,7 @@ mod tests {
                 updated_at: Some(1),
                 pipe: true,
                 compress: false,
+                compression: Compression::default(),
                 encrypt: false,
                 mode: StreamMode::FileMultipart,
             }),
@@ -758,6 +825,25 @@ mod tests {
             .unwrap_err()
             .to_string();
         assert!(err.contains("not resumable through `streams resume`"));
+
+        // a compressed and encrypted file is transformed again
+        let mut transformed = entry.clone();
+        if let Some(metadata) = transformed.metadata.as_mut() {
+            metadata.pipe = false;
+            metadata.compress = true;
+            metadata.encrypt = true;
+        }
+        assert!(validate_resume_entry(&transformed, "pipe").is_ok());
+
+        // a spooled pipe, even compressed and encrypted, is resumable
+        let mut spooled = entry;
+        if let Some(metadata) = spooled.metadata.as_mut() {
+            metadata.mode = StreamMode::PipeSpool;
+            metadata.compress = true;
+            metadata.encrypt = true;
+        }
+        let metadata = validate_resume_entry(&spooled, "pipe").unwrap();
+        assert_eq!(metadata.mode, StreamMode::PipeSpool);
     }
 
     #[test]
@@ -801,6 +887,7 @@ mod tests {
             updated_at: Some(1),
             pipe: false,
             compress: false,
+            compression: Compression::default(),
             encrypt: false,
             mode: StreamMode::FileMultipart,
         };
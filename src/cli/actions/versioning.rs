@@ -0,0 +1,55 @@
+use crate::{
+    cli::actions::Action,
+    s3::{
+        S3,
+        actions::{GetBucketVersioning, PutBucketVersioning},
+        responses::VersioningConfiguration,
+    },
+};
+use anyhow::Result;
+use serde_json::json;
+
+/// # Errors
+/// Will return an error if the action fails
+pub async fn handle(s3: &S3, action: Action) -> Result<()> {
+    match action {
+        Action::VersioningGet { json } => {
+            let config = GetBucketVersioning::new().request(s3).await?;
+
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&versioning_json(&config))?
+                );
+            } else {
+                println!("Versioning: {}", status(&config));
+                println!("MFA delete: {}", mfa_delete(&config));
+            }
+        }
+        Action::VersioningSet { status } => {
+            PutBucketVersioning::new(status).request(s3).await?;
+            println!("versioning: {}", status.as_amz());
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// A bucket that never had versioning enabled has no `Status` at all.
+fn status(config: &VersioningConfiguration) -> &str {
+    config.status.as_deref().unwrap_or("Unversioned")
+}
+
+fn mfa_delete(config: &VersioningConfiguration) -> &str {
+    config.mfa_delete.as_deref().unwrap_or("Disabled")
+}
+
+fn versioning_json(config: &VersioningConfiguration) -> serde_json::Value {
+    json!({
+        "kind": "versioning",
+        "enabled": config.status.as_deref() == Some("Enabled"),
+        "status": status(config),
+        "mfa_delete": mfa_delete(config),
+    })
+}
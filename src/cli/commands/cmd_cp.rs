@@ -0,0 +1,168 @@
+use crate::cli::commands::{validator_is_num, validator_key_value};
+use clap::{Arg, ArgAction, Command};
+
+pub fn command() -> Command {
+    Command::new("cp")
+        .about("Server-side copy of an object")
+        .long_about("Copy an object inside S3 without downloading it.\n\nObjects up to 5 GB are copied with a single CopyObject request; larger\nobjects are copied in parallel with a multipart UploadPartCopy.")
+        .after_long_help(
+            "Examples:\n\x20 s3m cp s3/my-bucket/file.dat s3/backups/file.dat\n\x20 s3m cp s3/my-bucket/file.dat s3/my-bucket/archive/\n\x20 s3m cp s3/my-bucket/big.iso s3/backups/big.iso -n 16\n\x20 s3m cp s3/my-bucket/file.dat s3/backups/file.dat --meta \"team=ops\"",
+        )
+        .arg(
+            Arg::new("arguments")
+                .help("host/bucket/object host/bucket/<optional object>")
+                .long_help("Source and destination.\n\nSyntax:\n  host/bucket/object host/bucket[/object]\n\nBoth locations must use the same host. When the destination has no\nobject (or ends with '/'), the source file name is used.")
+                .required(true)
+                .num_args(2),
+        )
+        .arg(
+            Arg::new("acl")
+                .help("The canned ACL to apply to the new object")
+                .long("acl")
+                .short('a')
+                .value_parser([
+                    "private",
+                    "public-read",
+                    "public-read-write",
+                    "authenticated-read",
+                    "aws-exec-read",
+                    "bucket-owner-read",
+                    "bucket-owner-full-control",
+                ])
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("meta")
+                .long("meta")
+                .short('m')
+                .help("Replace the object metadata, example: \"key1=value1;key2=value2\"")
+                .long_help("Replace the metadata of the copy instead of keeping the source metadata.\nThe Content-Type and the other headers of the source are kept.\n\nFormat:\n  key1=value1;key2=value2")
+                .value_parser(validator_key_value())
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("version")
+                .long("version")
+                .help("Copy a specific version of the source object")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("if-match")
+                .long("if-match")
+                .help("Copy only if the source ETag matches")
+                .value_name("etag")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("if-none-match")
+                .long("if-none-match")
+                .help("Copy only if the source ETag does not match")
+                .value_name("etag")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("if-modified-since")
+                .long("if-modified-since")
+                .help("Copy only if the source was modified after this HTTP date")
+                .value_name("date")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("if-unmodified-since")
+                .long("if-unmodified-since")
+                .help("Copy only if the source was not modified after this HTTP date")
+                .value_name("date")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("buffer")
+                .default_value("10485760")
+                .help("Part size in bytes for multipart copies")
+                .long_help("Part size in bytes used when the source is larger than 5 GB.\n\nThe size grows automatically to stay within 10,000 parts.")
+                .long("buffer")
+                .short('b')
+                .num_args(1)
+                .value_parser(validator_is_num()),
+        )
+        .arg(
+            Arg::new("number")
+                .help("Number of max concurrent part copies")
+                .long_help("Initial number of concurrent part copies, lowered when the server throttles and raised up to twice the value while parts succeed.")
+                .long("number")
+                .short('n')
+                .value_parser(clap::value_parser!(u8).range(1..=255))
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("quiet")
+                .long("quiet")
+                .short('q')
+                .help("Don't show progress bar")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_check_arguments() -> Result<()> {
+        let cmd = command();
+        let m = cmd.try_get_matches_from(vec!["s3m", "s3/a/file", "s3/b/file"])?;
+        let args: Vec<&str> = m
+            .get_many::<String>("arguments")
+            .unwrap_or_default()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(args, vec!["s3/a/file", "s3/b/file"]);
+        assert_eq!(m.get_one::<usize>("buffer").copied(), Some(10_485_760));
+        assert_eq!(m.get_one::<u8>("number"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_missing_destination() {
+        let cmd = command();
+        assert!(cmd.try_get_matches_from(vec!["s3m", "s3/a/file"]).is_err());
+    }
+
+    #[test]
+    fn test_check_options() -> Result<()> {
+        let cmd = command();
+        let m = cmd.try_get_matches_from(vec![
+            "s3m",
+            "s3/a/file",
+            "s3/b/file",
+            "--meta",
+            "a=1",
+            "--version",
+            "v1",
+            "--if-match",
+            "\"abc\"",
+            "-n",
+            "8",
+            "-q",
+        ])?;
+        assert_eq!(m.get_one::<String>("meta").map(String::as_str), Some("a=1"));
+        assert_eq!(
+            m.get_one::<String>("version").map(String::as_str),
+            Some("v1")
+        );
+        assert_eq!(
+            m.get_one::<String>("if-match").map(String::as_str),
+            Some("\"abc\"")
+        );
+        assert_eq!(m.get_one::<u8>("number").copied(), Some(8));
+        assert!(m.get_flag("quiet"));
+        Ok(())
+    }
+}
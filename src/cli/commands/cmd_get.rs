@@ -1,15 +1,16 @@
+use crate::cli::commands::validator_is_num;
 use clap::{Arg, Command};
 
 pub fn command() -> Command {
     Command::new("get")
         .about("Download an object or show its metadata")
         .after_long_help(
-            "Examples:\n  s3m get s3/my-bucket/file.dat\n  s3m get s3/my-bucket/file.dat /tmp/file.dat\n  s3m get s3/my-bucket/file.dat --meta",
+            "Examples:\n  s3m get s3/my-bucket/file.dat\n  s3m get s3/my-bucket/file.dat /tmp/file.dat\n  s3m get s3/my-bucket/file.dat --meta\n  s3m get s3/backups/db.sql.zst --raw\n  s3m get s3/backups/db.sql.zst.enc - | mysql\n  s3m get -n 16 s3/backups/full.tar",
         )
         .arg(
             Arg::new("arguments")
-                .help("host/bucket/object <optional local path>")
-                .long_help("Object to download.\n\nSyntax:\n  host/bucket/object [local path]\n\nExamples:\n  s3/my-bucket/file.dat\n  s3/my-bucket/file.dat /tmp/file.dat")
+                .help("host/bucket/object <optional local path or - for stdout>")
+                .long_help("Object to download.\n\nSyntax:\n  host/bucket/object [local path | -]\n\nUse - to write the object to stdout, decrypted and decompressed; the\nprogress bar goes to stderr.\n\nExamples:\n  s3/my-bucket/file.dat\n  s3/my-bucket/file.dat /tmp/file.dat\n  s3/my-bucket/file.dat -")
                 .required(true)
                 .num_args(1..=2),
         )
@@ -56,6 +57,32 @@ pub fn command() -> Command {
                 .long("json")
                 .num_args(0),
         )
+        .arg(
+            Arg::new("raw")
+                .long("raw")
+                .help("Keep compressed objects compressed")
+                .long_help("Save compressed objects as they are stored. Without it, objects with the\nx-amz-meta-s3m-compression metadata or a .zst, .gz, .lz4 or .xz key (before\n.enc) are decompressed while downloading, after decryption, and the extension\nis removed from the file name.")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("max-output")
+                .long("max-output")
+                .help("Max bytes of a decompressed object")
+                .long_help("Stop decompressing once the output exceeds this many bytes, a guard against\ndecompression bombs.\n\nDefault: 1024 times the object size, at least 1073741824 (1 GiB).")
+                .value_name("bytes")
+                .value_parser(validator_is_num())
+                .num_args(1)
+                .conflicts_with("raw"),
+        )
+        .arg(
+            Arg::new("number")
+                .help("Number of max concurrent part downloads")
+                .long_help("Download objects larger than 64 MiB with this many ranged requests in\nparallel, written in place into the destination file. Objects uploaded in\nparts are fetched by part number and their part checksums verified.\nEncrypted, decompressed and stdout downloads read one response in order.\n\nDefault: the global -n, based on available CPUs.")
+                .long("number")
+                .short('n')
+                .value_parser(clap::value_parser!(u8).range(1..=255))
+                .num_args(1),
+        )
 }
 
 #[cfg(test)]
@@ -171,6 +198,38 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_check_raw_and_max_output() -> Result<()> {
+        let m = command().try_get_matches_from(vec!["s3m", "test", "--raw"])?;
+        assert_eq!(m.get_one::<bool>("raw").copied(), Some(true));
+
+        let m = command().try_get_matches_from(vec!["s3m", "test", "--max-output", "1024"])?;
+        assert_eq!(m.get_one::<usize>("max-output").copied(), Some(1024));
+
+        assert!(
+            command()
+                .try_get_matches_from(vec!["s3m", "test", "--raw", "--max-output", "1"])
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_number() -> Result<()> {
+        let m = command().try_get_matches_from(vec!["s3m", "test", "-n", "16"])?;
+        assert_eq!(m.get_one::<u8>("number").copied(), Some(16));
+
+        let m = command().try_get_matches_from(vec!["s3m", "test"])?;
+        assert_eq!(m.get_one::<u8>("number"), None);
+
+        assert!(
+            command()
+                .try_get_matches_from(vec!["s3m", "test", "-n", "0"])
+                .is_err()
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_check_json() -> Result<()> {
         let cmd = command();
@@ -0,0 +1,154 @@
+use crate::cli::commands::{validator_days, validator_is_file};
+use clap::{Arg, ArgAction, Command};
+
+fn location_arg() -> Arg {
+    Arg::new("arguments")
+        .help("host/bucket")
+        .required(true)
+        .num_args(1)
+}
+
+pub fn command() -> Command {
+    Command::new("lifecycle")
+        .about("Manage bucket lifecycle rules")
+        .after_long_help(
+            "Examples:\n\
+             \x20 s3m lifecycle get s3/my-bucket\n\
+             \x20 s3m lifecycle set s3/my-bucket --abort-incomplete-after 7d\n\
+             \x20 s3m lifecycle set s3/my-bucket --expire-noncurrent-after 30d --prefix logs/\n\
+             \x20 s3m lifecycle set s3/my-bucket rules.yml\n\
+             \x20 s3m lifecycle rm s3/my-bucket\n\n\
+             Rules file (YAML, or the JSON printed by `aws s3api get-bucket-lifecycle-configuration`):\n\
+             \x20 Rules:\n\
+             \x20   - ID: logs\n\
+             \x20     Status: Enabled\n\
+             \x20     Filter:\n\
+             \x20       Prefix: logs/\n\
+             \x20     Expiration:\n\
+             \x20       Days: 365",
+        )
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("get")
+                .about("Show the lifecycle rules of a bucket")
+                .arg(location_arg())
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .help("Output as JSON")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("set")
+                .about("Replace the lifecycle rules of a bucket")
+                .long_about("Replace the lifecycle rules of a bucket.\n\nRules come from a YAML file and/or the shortcut flags; together they\nreplace the whole existing configuration.")
+                .arg(location_arg())
+                .arg(
+                    Arg::new("file")
+                        .help("YAML file with the lifecycle rules")
+                        .value_parser(validator_is_file())
+                        .value_name("rules.yml")
+                        .required_unless_present_any([
+                            "abort-incomplete-after",
+                            "expire-noncurrent-after",
+                        ])
+                        .num_args(1),
+                )
+                .arg(
+                    Arg::new("abort-incomplete-after")
+                        .long("abort-incomplete-after")
+                        .help("Abort incomplete multipart uploads after this age, e.g. 7d")
+                        .value_parser(validator_days())
+                        .value_name("days")
+                        .num_args(1),
+                )
+                .arg(
+                    Arg::new("expire-noncurrent-after")
+                        .long("expire-noncurrent-after")
+                        .help("Delete noncurrent object versions after this age, e.g. 30d")
+                        .value_parser(validator_days())
+                        .value_name("days")
+                        .num_args(1),
+                )
+                .arg(
+                    Arg::new("prefix")
+                        .long("prefix")
+                        .help("Limit the shortcut rules to keys with this prefix")
+                        .num_args(1),
+                ),
+        )
+        .subcommand(
+            Command::new("rm")
+                .about("Remove all lifecycle rules from a bucket")
+                .arg(location_arg()),
+        )
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_set_requires_rules() {
+        assert!(
+            command()
+                .try_get_matches_from(vec!["lifecycle", "set", "s3/bucket"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_set_shortcuts() {
+        let m = command()
+            .try_get_matches_from(vec![
+                "lifecycle",
+                "set",
+                "s3/bucket",
+                "--abort-incomplete-after",
+                "7d",
+                "--expire-noncurrent-after",
+                "2592000",
+            ])
+            .unwrap();
+        let sub = m.subcommand_matches("set").unwrap();
+        assert_eq!(
+            sub.get_one::<u32>("abort-incomplete-after").copied(),
+            Some(7)
+        );
+        assert_eq!(
+            sub.get_one::<u32>("expire-noncurrent-after").copied(),
+            Some(30)
+        );
+        assert_eq!(sub.get_one::<PathBuf>("file"), None);
+    }
+
+    #[test]
+    fn test_set_rejects_partial_days() {
+        for value in ["12h", "0d", "1w"] {
+            assert!(
+                command()
+                    .try_get_matches_from(vec![
+                        "lifecycle",
+                        "set",
+                        "s3/bucket",
+                        "--abort-incomplete-after",
+                        value,
+                    ])
+                    .is_err(),
+                "{value} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_get_json() {
+        let m = command()
+            .try_get_matches_from(vec!["lifecycle", "get", "s3/bucket", "--json"])
+            .unwrap();
+        assert!(m.subcommand_matches("get").unwrap().get_flag("json"));
+    }
+}
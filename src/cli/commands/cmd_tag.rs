@@ -0,0 +1,119 @@
+use crate::cli::commands::validator_key_value;
+use clap::{Arg, ArgAction, Command};
+
+fn location_arg() -> Arg {
+    Arg::new("arguments")
+        .help("host/bucket/key")
+        .required(true)
+        .num_args(1)
+}
+
+fn version_id_arg() -> Arg {
+    Arg::new("version-id")
+        .long("version-id")
+        .help("Object version id")
+        .num_args(1)
+}
+
+pub fn command() -> Command {
+    Command::new("tag")
+        .about("Manage object tags")
+        .after_long_help(
+            "Examples:\n\
+             \x20 s3m tag set s3/my-bucket/file.dat \"team=ops;retention=30d\"\n\
+             \x20 s3m tag get s3/my-bucket/file.dat\n\
+             \x20 s3m tag get s3/my-bucket/file.dat --json\n\
+             \x20 s3m tag rm s3/my-bucket/file.dat",
+        )
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("get")
+                .about("Show the tags of an object")
+                .arg(location_arg())
+                .arg(version_id_arg())
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .help("Output as JSON")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("set")
+                .about("Replace the tags of an object")
+                .arg(location_arg())
+                .arg(
+                    Arg::new("tags")
+                        .help("Tags, example: \"key1=value1;key2=value2\"")
+                        .long_help("Tags replacing the current tag set.\n\nFormat:\n  key1=value1;key2=value2\n\nAt most 10 tags; keys up to 128 and values up to 256 characters.")
+                        .required(true)
+                        .value_parser(validator_key_value())
+                        .num_args(1),
+                )
+                .arg(version_id_arg()),
+        )
+        .subcommand(
+            Command::new("rm")
+                .about("Remove all tags from an object")
+                .arg(location_arg())
+                .arg(version_id_arg()),
+        )
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requires_subcommand() {
+        assert!(command().try_get_matches_from(vec!["tag"]).is_err());
+    }
+
+    #[test]
+    fn test_get_json() {
+        let m = command()
+            .try_get_matches_from(vec!["tag", "get", "s3/b/k", "--json"])
+            .unwrap();
+        let sub = m.subcommand_matches("get").unwrap();
+        assert!(sub.get_flag("json"));
+    }
+
+    #[test]
+    fn test_set() {
+        let m = command()
+            .try_get_matches_from(vec![
+                "tag",
+                "set",
+                "s3/b/k",
+                "a=1;b=2",
+                "--version-id",
+                "v1",
+            ])
+            .unwrap();
+        let sub = m.subcommand_matches("set").unwrap();
+        assert_eq!(
+            sub.get_one::<String>("tags").map(String::as_str),
+            Some("a=1;b=2")
+        );
+        assert_eq!(
+            sub.get_one::<String>("version-id").map(String::as_str),
+            Some("v1")
+        );
+    }
+
+    #[test]
+    fn test_set_requires_tags() {
+        assert!(
+            command()
+                .try_get_matches_from(vec!["tag", "set", "s3/b/k"])
+                .is_err()
+        );
+        assert!(
+            command()
+                .try_get_matches_from(vec!["tag", "set", "s3/b/k", "invalid"])
+                .is_err()
+        );
+    }
+}
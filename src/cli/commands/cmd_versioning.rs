@@ -0,0 +1,77 @@
+use clap::{Arg, ArgAction, Command};
+
+fn location_arg() -> Arg {
+    Arg::new("arguments")
+        .help("host/bucket")
+        .required(true)
+        .num_args(1)
+}
+
+pub fn command() -> Command {
+    Command::new("versioning")
+        .about("Manage bucket versioning")
+        .after_long_help(
+            "Examples:\n\
+             \x20 s3m versioning get s3/my-bucket\n\
+             \x20 s3m versioning get s3/my-bucket --json\n\
+             \x20 s3m versioning enable s3/my-bucket\n\
+             \x20 s3m versioning suspend s3/my-bucket\n\n\
+             Once enabled, versioning can only be suspended, never turned off.",
+        )
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("get")
+                .about("Show the versioning and MFA delete status of a bucket")
+                .arg(location_arg())
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .help("Output as JSON")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("enable")
+                .about("Enable versioning on a bucket")
+                .arg(location_arg()),
+        )
+        .subcommand(
+            Command::new("suspend")
+                .about("Suspend versioning on a bucket; existing versions are kept")
+                .arg(location_arg()),
+        )
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requires_subcommand() {
+        assert!(command().try_get_matches_from(vec!["versioning"]).is_err());
+        assert!(
+            command()
+                .try_get_matches_from(vec!["versioning", "enable"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_get_json() {
+        let m = command()
+            .try_get_matches_from(vec!["versioning", "get", "s3/bucket", "--json"])
+            .unwrap();
+        let sub = m.subcommand_matches("get").unwrap();
+        assert!(sub.get_flag("json"));
+    }
+
+    #[test]
+    fn test_suspend() {
+        let m = command()
+            .try_get_matches_from(vec!["versioning", "suspend", "s3/bucket"])
+            .unwrap();
+        assert_eq!(m.subcommand_name(), Some("suspend"));
+    }
+}
@@ -1,7 +1,9 @@
 pub mod cmd_acl;
 pub mod cmd_cb;
+pub mod cmd_cp;
 pub mod cmd_du;
 pub mod cmd_get;
+pub mod cmd_lifecycle;
 pub mod cmd_ls;
 pub mod cmd_monitor;
 pub mod cmd_object_lock;
@@ -9,8 +11,13 @@ pub mod cmd_rm;
 pub mod cmd_share;
 pub mod cmd_show;
 pub mod cmd_streams;
+pub mod cmd_tag;
+pub mod cmd_versioning;
 
-use crate::cli::age_filter::{AgeFilter, parse_age_filter};
+use crate::cli::{
+    age_filter::{AgeFilter, parse_age_filter},
+    config::parse_monitor_age_text,
+};
 use clap::{
     Arg, ArgAction, ColorChoice, Command,
     builder::ValueParser,
@@ -70,6 +77,22 @@ pub fn validator_age_filter() -> ValueParser {
     })
 }
 
+/// A duration in whole days, e.g. `7d` or `604800` (seconds), returned in days.
+pub fn validator_days() -> ValueParser {
+    ValueParser::from(move |s: &str| -> std::result::Result<u32, String> {
+        const DAY: u64 = 86_400;
+
+        let invalid = || format!("Invalid duration '{s}', expected whole days, e.g. 7d");
+
+        let seconds = parse_monitor_age_text(s).map_err(|_| invalid())?;
+        if seconds == 0 || seconds % DAY != 0 {
+            return Err(invalid());
+        }
+
+        u32::try_from(seconds / DAY).map_err(|_| invalid())
+    })
+}
+
 const LONG_ABOUT: &str =
     "Store files and streams in S3-compatible object storage using a named host from config.yml.";
 
@@ -87,12 +110,13 @@ Examples:
   s3m file.dat s3/my-bucket/file.dat
   mariadb-dump db | s3m --pipe s3/backups/db.sql
   s3m --compress dump.sql s3/backups/dump.sql.zst
+  pg_dump db | s3m --pipe --compress=xz --compress-level 9 s3/backups/db.sql
   s3m get s3/my-bucket/file.dat /tmp/file.dat
 
 Notes:
   File multipart uploads can be resumed.
-  STDIN / --pipe uploads cannot be resumed after interruption.
-  Unknown-size STDIN uploads use fixed 512 MiB multipart parts.
+  STDIN / --pipe uploads can be resumed with --spool.
+  Unknown-size STDIN uploads start with 8 MiB parts that grow, see --size-hint.
 ";
 
 fn command_styles() -> Styles {
@@ -185,19 +209,11 @@ fn add_transfer_args(
             .value_parser(validator_key_value())
             .num_args(1)
         )
-        .arg(
-            Arg::new("pipe")
-            .long("pipe")
-            .short('p')
-            .help("Read from STDIN")
-            .long_help("Read upload data from STDIN instead of a local file.\n\nUseful for pipelines such as pg_dump, mariadb-dump or backup tools.\nSTDIN uploads are not resumable after interruption.")
-            .num_args(0)
-        )
         .arg(
             Arg::new("buffer")
             .default_value("10485760")
             .help("Buffer \"part size\" in bytes, doesn't apply when reading from STDIN (--pipe option)")
-            .long_help("Multipart part size in bytes for file uploads.\n\nDefault: 10485760 (10 MiB).\nDoes not apply to --pipe, which uses a fixed streaming buffer.")
+            .long_help("Multipart part size in bytes for file uploads.\n\nDefault: 10485760 (10 MiB).\nDoes not apply to --pipe, whose parts start at 8 MiB and grow, see --size-hint.")
             .long("buffer")
             .short('b')
             .num_args(1)
@@ -216,12 +232,90 @@ fn add_transfer_args(
         .arg(
             Arg::new("arguments")
             .help("/path/to/file host/bucket/object")
-            .long_help("Upload syntax:\n  /path/to/file host/bucket/object\n\nExamples:\n  file.dat s3/my-bucket/file.dat\n  /backups/db.sql minio/backups/db.sql\n\nWhen using --pipe, only the destination object path is required:\n  s3/backups/db.sql")
+            .long_help("Upload syntax:\n  /path/to/file host/bucket/object\n\nExamples:\n  file.dat s3/my-bucket/file.dat\n  /backups/db.sql minio/backups/db.sql\n\nWhen using --pipe, only the destination object path is required:\n  s3/backups/db.sql\n\nWith --pipe, the input can be sent to several destinations, see --require:\n  aws/backups/db.sql b2/backups/db.sql")
             .required_unless_present_any(["clean", "decrypt"])
-            .num_args(1..=2)
+            .num_args(1..)
         );
 
-    add_object_lock_args(cmd)
+    add_object_lock_args(add_tag_args(add_staging_args(add_pipe_args(cmd))))
+}
+
+/// STDIN upload flags.
+fn add_pipe_args(cmd: Command) -> Command {
+    cmd
+        .arg(
+            Arg::new("pipe")
+            .long("pipe")
+            .short('p')
+            .help("Read from STDIN")
+            .long_help("Read upload data from STDIN instead of a local file.\n\nUseful for pipelines such as pg_dump, mariadb-dump or backup tools.\nSTDIN uploads are only resumable with --spool.")
+            .num_args(0)
+        )
+        .arg(
+            Arg::new("spool")
+            .long("spool")
+            .help("Keep the STDIN parts on disk until S3 confirms them, to resume the upload")
+            .long_help("Keep every STDIN part in the stream state directory until S3 confirms it.\n\nIf the upload fails, the rest of the input is still captured to disk and\nthe upload can be finished later with:\n  s3m streams resume <id>\n\nNeeds free disk space for the parts not yet uploaded, up to the whole input.")
+            .num_args(0)
+            .requires("pipe")
+        )
+        .arg(
+            Arg::new("size-hint")
+            .long("size-hint")
+            .help("Approximate size in bytes of the STDIN input, to start with larger parts")
+            .long_help("Approximate size in bytes of the STDIN input.\n\nParts of an unknown-size stream start at 8 MiB and double every 1000 parts,\nso up to 5 TB can be uploaded. With a hint, the first part size is computed\nlike for a file of that size, so large inputs use fewer, larger parts.\nA hint that is too low is safe, the parts still grow.")
+            .value_name("bytes")
+            .num_args(1)
+            .value_parser(validator_is_num())
+            .requires("pipe")
+        )
+        .arg(
+            Arg::new("require")
+            .long("require")
+            .help("Destinations of a --pipe upload that must succeed: all or any")
+            .long_help("With several --pipe destinations, STDIN is read once and sent to each of them\nwith its own multipart upload, retries and progress line.\n\nall: fail as soon as one destination fails (default).\nany: keep uploading to the other destinations, fail only if all of them fail.")
+            .value_parser(["all", "any"])
+            .default_value("all")
+            .num_args(1)
+        )
+}
+
+/// Staging limits of streamed uploads.
+fn add_staging_args(cmd: Command) -> Command {
+    cmd
+        .arg(
+            Arg::new("max-staging")
+            .default_value("2147483648")
+            .help("Max bytes of staged parts waiting for S3 with --pipe, --compress or --encrypt")
+            .long_help("Streamed uploads (--pipe, --compress, --encrypt) send up to --number staged\nparts in parallel while the next part is read.\n\nLimits the bytes of the staged parts waiting for S3, the disk space used in\n--tmp-dir. At least one part is always sent.\n\nDefault: 2147483648 (2 GiB).")
+            .long("max-staging")
+            .value_name("bytes")
+            .num_args(1)
+            .value_parser(validator_is_num())
+        )
+        .arg(
+            Arg::new("max-memory")
+            .help("Stage streamed parts in memory instead of --tmp-dir, within this many bytes")
+            .long_help("Stage the parts of streamed uploads (--pipe, --compress, --encrypt) in memory\ninstead of --tmp-dir, for read-only or tiny filesystems. Parts are sent from\nmemory.\n\nHalf of the budget holds the part being read and half the parts being sent,\nso parts are limited to half of it and the object to 10,000 such parts,\ne.g. 1073741824 (1 GiB) allows 512 MiB parts and objects up to ~4.8 TiB.\nMust be at least 10485760 (two 5 MiB parts). Not available with --spool.")
+            .long("max-memory")
+            .value_name("bytes")
+            .num_args(1)
+            .value_parser(validator_is_num())
+            .conflicts_with("spool")
+        )
+}
+
+/// Object tagging (`x-amz-tagging`) flag for the upload path.
+fn add_tag_args(cmd: Command) -> Command {
+    cmd
+        .arg(
+            Arg::new("tag")
+            .long("tag")
+            .help("Object tags, example: \"key1=value1;key2=value2\"")
+            .long_help("Tag the uploaded object.\n\nFormat:\n  key1=value1;key2=value2\n\nSent as the x-amz-tagging header, also with --pipe.")
+            .value_parser(validator_key_value())
+            .num_args(1)
+        )
 }
 
 /// S3 Object Lock (WORM) flags for the upload path.
@@ -277,7 +371,7 @@ fn add_runtime_args(cmd: Command, num_threads: String) -> Command {
         .arg(
             Arg::new("number")
             .help("Number of max concurrent requests")
-            .long_help("Maximum number of concurrent multipart requests.\n\nDefault is based on available CPUs.")
+            .long_help("Initial number of concurrent multipart requests, lowered when the server throttles and raised up to twice the value while parts succeed.\nAlso the number of streamed parts (--pipe, --compress, --encrypt) sent in parallel, and of parts fetched by `get`.\n\nDefault is based on available CPUs.")
             .short('n')
             .long("number")
             .default_value(num_threads)
@@ -295,7 +389,7 @@ fn add_runtime_args(cmd: Command, num_threads: String) -> Command {
         .arg(
             Arg::new("throttle")
             .help("Bandwidth throttle in kilobytes per second, 0 to disable")
-            .long_help("Limit upload/download bandwidth in kilobytes per second.\n\nUse 0 to disable throttling.")
+            .long_help("Limit the total upload/download bandwidth in kilobytes per second, shared by all the parallel requests.\n\nUse 0 to disable throttling.")
             .long("kilobytes")
             .short('k')
             .default_value("0")
@@ -307,7 +401,7 @@ fn add_runtime_args(cmd: Command, num_threads: String) -> Command {
         .arg(
             Arg::new("retries")
             .help("Number of retries")
-            .long_help("Number of retry attempts for failed upload parts or streaming requests.")
+            .long_help("Number of attempts for every request that fails with a retryable error (SlowDown, 5xx, timeouts, reset connections).")
             .long("retries")
             .short('r')
             .default_value("3")
@@ -316,11 +410,24 @@ fn add_runtime_args(cmd: Command, num_threads: String) -> Command {
         )
         .arg(
             Arg::new("compress")
-            .help("Compress")
-            .long_help("Compress input using zstd before upload.")
+            .help("Compress, --compress=<zstd|gzip|lz4|xz>")
+            .long_help("Compress input before upload, with zstd unless a codec is given:\n  --compress=gzip\n\nWithout a codec the codec of the host is used. The key gets the extension of\nthe codec (.zst, .gz, .lz4, .xz) and the codec is stored in the\nx-amz-meta-s3m-compression metadata.")
             .long("compress")
             .short('x')
-            .num_args(0)
+            .value_name("codec")
+            .value_parser(["zstd", "gzip", "lz4", "xz"])
+            .num_args(0..=1)
+            .require_equals(true)
+            .conflicts_with("checksum")
+        )
+        .arg(
+            Arg::new("compress-level")
+            .help("Compression level, implies --compress")
+            .long_help("Compression level: 1-22 for zstd, 0-9 for gzip and xz, 1-12 for lz4.\nImplies --compress.\n\nDefault: 3 for zstd, 6 for gzip and xz, fast lz4.")
+            .long("compress-level")
+            .value_name("level")
+            .value_parser(clap::value_parser!(i32))
+            .num_args(1)
             .conflicts_with("checksum")
         )
         .arg(
@@ -336,8 +443,10 @@ fn add_runtime_args(cmd: Command, num_threads: String) -> Command {
 
 fn add_subcommands(cmd: Command) -> Command {
     cmd.subcommand(cmd_acl::command())
+        .subcommand(cmd_cp::command())
         .subcommand(cmd_du::command())
         .subcommand(cmd_get::command())
+        .subcommand(cmd_lifecycle::command())
         .subcommand(cmd_ls::command())
         .subcommand(cmd_monitor::command())
         .subcommand(cmd_object_lock::command())
@@ -346,6 +455,8 @@ fn add_subcommands(cmd: Command) -> Command {
         .subcommand(cmd_share::command())
         .subcommand(cmd_show::command())
         .subcommand(cmd_streams::command())
+        .subcommand(cmd_tag::command())
+        .subcommand(cmd_versioning::command())
 }
 
 pub fn new(config_path: &Path) -> Command {
@@ -487,7 +598,7 @@ hosts:
 
         assert!(long_help.contains("Examples:"));
         assert!(long_help.contains("mariadb-dump db | s3m --pipe"));
-        assert!(long_help.contains("STDIN / --pipe uploads cannot be resumed"));
+        assert!(long_help.contains("STDIN / --pipe uploads can be resumed with --spool"));
 
         Ok(())
     }
@@ -637,13 +748,37 @@ hosts:
         let m = cmd.try_get_matches_from(vec!["s3m", "test", "--compress"]);
         assert!(m.is_ok());
         let m = m.unwrap();
-        assert_eq!(m.get_one::<bool>("compress").copied(), Some(true));
+        assert!(m.contains_id("compress"));
+        assert!(m.get_one::<String>("compress").is_none());
 
         let cmd2 = new(&config);
-        let m2 = cmd2.try_get_matches_from(vec!["s3m", "test", "-x"]);
+        let m2 = cmd2.try_get_matches_from(vec!["s3m", "-x", "file", "test"]);
         assert!(m2.is_ok());
         let m2 = m2.unwrap();
-        assert_eq!(m2.get_one::<bool>("compress").copied(), Some(true));
+        assert!(m2.contains_id("compress"));
+        assert!(m2.get_one::<String>("compress").is_none());
+
+        let cmd3 = new(&config);
+        let m3 = cmd3.try_get_matches_from(vec![
+            "s3m",
+            "--compress=xz",
+            "--compress-level",
+            "9",
+            "test",
+        ]);
+        assert!(m3.is_ok());
+        let m3 = m3.unwrap();
+        assert_eq!(
+            m3.get_one::<String>("compress").map(String::as_str),
+            Some("xz")
+        );
+        assert_eq!(m3.get_one::<i32>("compress-level").copied(), Some(9));
+
+        let cmd4 = new(&config);
+        assert!(
+            cmd4.try_get_matches_from(vec!["s3m", "--compress=brotli", "test"])
+                .is_err()
+        );
         Ok(())
     }
 
@@ -1,9 +1,13 @@
-use crate::s3::Region;
+use crate::s3::{
+    Addressing, Bandwidth, BandwidthSchedule, ClientConfig, Credentials, Region, S3,
+    credentials::{Profile, ProvideCredentials, ProviderConfig},
+};
+use crate::stream::compression::{Codec, Compression};
 use anyhow::{Context, Result};
 use secrecy::SecretString;
 use serde::{Deserialize, Deserializer, de::Error};
 use serde_yaml_ng as serde_yaml;
-use std::{collections::BTreeMap, fs::File, path::PathBuf};
+use std::{collections::BTreeMap, env, fs::File, path::PathBuf, sync::Arc};
 
 const fn default_monitor_age() -> u64 {
     86_400
@@ -16,7 +20,7 @@ enum MonitorAgeValue {
     Text(String),
 }
 
-fn parse_monitor_age_text(value: &str) -> std::result::Result<u64, String> {
+pub(crate) fn parse_monitor_age_text(value: &str) -> std::result::Result<u64, String> {
     if let Ok(seconds) = value.parse::<u64>() {
         return Ok(seconds);
     }
@@ -60,6 +64,16 @@ where
     }
 }
 
+/// The `compress` setting of a host: `true`, a codec (`compress: xz`) or a
+/// codec and level (`compress: { codec: zstd, level: 19 }`)
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum HostCompression {
+    Enabled(bool),
+    Codec(Codec),
+    Settings(Compression),
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Config {
     pub hosts: BTreeMap<String, Host>,
@@ -95,13 +109,34 @@ pub struct Host {
     #[serde(default)]
     pub secret_key: SecretString,
 
+    // temporary credentials (STS)
+    pub session_token: Option<SecretString>,
+
+    // profile of ~/.aws/credentials and ~/.aws/config
+    pub profile: Option<String>,
+
+    // credential_process, role_arn/web_identity_token_file, credential_source
+    #[serde(flatten)]
+    pub provider: ProviderConfig,
+
+    // path, virtual or auto bucket addressing
+    #[serde(default)]
+    pub addressing: Addressing,
+
+    // ca_bundle, client_cert/client_key, insecure_skip_verify
+    #[serde(flatten)]
+    pub client: ClientConfig,
+
     pub bucket: Option<String>,
 
     #[serde(default)]
     pub buckets: BTreeMap<String, Vec<MonitorRule>>,
 
     pub enc_key: Option<String>,
-    pub compress: Option<bool>,
+    pub compress: Option<HostCompression>,
+
+    // bandwidth limit by time of day, "08:00-18:00": 20480, default: 0
+    pub bandwidth_schedule: Option<BandwidthSchedule>,
 }
 
 impl Config {
@@ -126,15 +161,29 @@ impl Config {
 }
 
 impl Host {
-    /// Get the region for the host
+    /// Get the region for the host, the `region` and `endpoint_url` of the AWS
+    /// profile are used when the host does not define them
     /// # Errors
     /// Will return an error if the region is not found
     pub fn get_region(&self) -> Result<Region> {
-        match (&self.endpoint, &self.region) {
-            (Some(endpoint), Some(region)) => Ok(Region::custom(region.clone(), endpoint.clone())),
+        let profile = self.get_profile()?;
+
+        let endpoint = self.endpoint.clone().or_else(|| {
+            profile
+                .as_ref()
+                .and_then(|p| p.endpoint_url().map(ToString::to_string))
+        });
+        let region = self.region.clone().or_else(|| {
+            profile
+                .as_ref()
+                .and_then(|p| p.region().map(ToString::to_string))
+        });
+
+        match (endpoint, region) {
+            (Some(endpoint), Some(region)) => Ok(Region::custom(region, endpoint)),
             (Some(endpoint), None) => Ok(Region::Custom {
                 name: String::new(),
-                endpoint: endpoint.clone(),
+                endpoint,
             }),
             (None, Some(region)) => Ok(region.parse::<Region>()?),
             (None, None) => Err(anyhow::anyhow!(
@@ -142,6 +191,112 @@ impl Host {
             )),
         }
     }
+
+    /// Get the credentials for the host with the following priority:
+    /// 1. Environment variables (`AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`,
+    ///    `AWS_SESSION_TOKEN`)
+    /// 2. The AWS profile, see [`Host::profile_name`]
+    /// 3. `access_key`, `secret_key` and `session_token`
+    /// # Errors
+    /// Will return an error if the profile can not be loaded
+    pub fn get_credentials(&self) -> Result<Credentials> {
+        match self.get_profile()? {
+            Some(profile) => Ok(profile.credentials()?),
+            None => Ok(Credentials::new(&self.access_key, &self.secret_key)
+                .with_session_token(self.session_token.clone())),
+        }
+    }
+
+    /// Get the provider of temporary credentials for the host, `None` when it
+    /// uses static keys:
+    /// 1. `AWS_ACCESS_KEY_ID` in the environment, always static
+    /// 2. `credential_process`, `role_arn` with `web_identity_token_file` or
+    ///    `credential_source` of the host
+    /// 3. the same settings of the AWS profile
+    /// 4. for hosts without `access_key` and profile, `AWS_ROLE_ARN` with
+    ///    `AWS_WEB_IDENTITY_TOKEN_FILE` or `AWS_CONTAINER_CREDENTIALS_*`
+    /// # Errors
+    /// Will return an error if the profile can not be loaded
+    pub fn get_credentials_provider(&self) -> Result<Option<Arc<dyn ProvideCredentials>>> {
+        if env::var_os("AWS_ACCESS_KEY_ID").is_some() {
+            return Ok(None);
+        }
+
+        if let Some(provider) = self.provider.provider() {
+            return Ok(Some(provider));
+        }
+
+        match self.get_profile()? {
+            Some(profile) => Ok(profile.provider_config().provider()),
+            None if self.access_key.is_empty() => Ok(ProviderConfig::from_env().provider()),
+            None => Ok(None),
+        }
+    }
+
+    /// The bandwidth limit following the `bandwidth_schedule` of the host
+    #[must_use]
+    pub fn bandwidth(&self) -> Option<Bandwidth> {
+        self.bandwidth_schedule
+            .clone()
+            .map(Bandwidth::with_schedule)
+    }
+
+    /// The compression of uploads to the host, `None` when they are not
+    /// compressed
+    /// # Errors
+    /// Will return an error if the codec does not support the level
+    pub fn compression(&self) -> Result<Option<Compression>> {
+        match self.compress {
+            None | Some(HostCompression::Enabled(false)) => Ok(None),
+            Some(HostCompression::Enabled(true)) => Ok(Some(Compression::default())),
+            Some(HostCompression::Codec(codec)) => Ok(Some(Compression::new(codec))),
+            Some(HostCompression::Settings(compression)) => compression.validate().map(Some),
+        }
+    }
+
+    /// Get the S3 client for the host, signing with the credentials provider
+    /// when there is one (fetched by [`S3::refresh_credentials`])
+    /// # Errors
+    /// Will return an error if the region or the credentials can not be loaded
+    pub fn get_s3(&self, bucket: Option<String>, no_sign_request: bool) -> Result<S3> {
+        let region = self.get_region()?;
+
+        if let Some(provider) = self.get_credentials_provider()? {
+            let credentials = Credentials::from_parts("", SecretString::default(), None);
+
+            return Ok(S3::new(&credentials, &region, bucket, no_sign_request)
+                .with_addressing(self.addressing)
+                .with_client_config(&self.client)?
+                .with_credentials_provider(provider));
+        }
+
+        let credentials = self.get_credentials()?;
+
+        Ok(S3::new(&credentials, &region, bucket, no_sign_request)
+            .with_addressing(self.addressing)
+            .with_client_config(&self.client)?)
+    }
+
+    /// The AWS profile of the host: `profile`, or `AWS_PROFILE` for hosts
+    /// without an `access_key`
+    #[must_use]
+    pub fn profile_name(&self) -> Option<String> {
+        self.profile.clone().or_else(|| {
+            if self.access_key.is_empty() {
+                env::var("AWS_PROFILE").ok().filter(|p| !p.is_empty())
+            } else {
+                None
+            }
+        })
+    }
+
+    fn get_profile(&self) -> Result<Option<Profile>> {
+        self.profile_name()
+            .map(|name| {
+                Profile::load(&name).with_context(|| format!("could not load AWS profile {name}"))
+            })
+            .transpose()
+    }
 }
 
 #[cfg(test)]
@@ -492,7 +647,8 @@ hosts:
         assert!(r.is_ok());
         let r = r.unwrap();
         assert_eq!(r, Region::aws("us-east-2"));
-        assert_eq!(h.compress, Some(true));
+        assert_eq!(h.compress, Some(HostCompression::Enabled(true)));
+        assert_eq!(h.compression().unwrap(), Some(Compression::default()));
     }
 
     #[test]
@@ -509,10 +665,199 @@ hosts:
         assert!(r.is_ok());
         let r = r.unwrap();
         assert_eq!(r, Region::aws("us-east-2"));
-        assert_eq!(h.compress, Some(true));
+        assert_eq!(h.compress, Some(HostCompression::Enabled(true)));
+        assert_eq!(h.compression().unwrap(), Some(Compression::default()));
         assert_eq!(h.enc_key, Some(String::from("secret")));
     }
 
+    #[test]
+    fn test_config_get_compress_codec() {
+        const CONF_CODEC: &str = r"---
+hosts:
+  gzip:
+    region: us-east-2
+    compress: gzip
+  xz:
+    region: us-east-2
+    compress:
+      codec: xz
+      level: 9
+  off:
+    region: us-east-2
+    compress: false
+  bad:
+    region: us-east-2
+    compress:
+      codec: lz4
+      level: 13";
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        tmp_file.write_all(CONF_CODEC.as_bytes()).unwrap();
+        let c = Config::new(tmp_file.into_temp_path().to_path_buf()).unwrap();
+
+        let h = c.get_host("gzip").unwrap();
+        assert_eq!(
+            h.compression().unwrap(),
+            Some(Compression::new(Codec::Gzip))
+        );
+
+        let h = c.get_host("xz").unwrap();
+        assert_eq!(
+            h.compression().unwrap(),
+            Some(Compression::new(Codec::Xz).with_level(9).unwrap())
+        );
+
+        assert_eq!(c.get_host("off").unwrap().compression().unwrap(), None);
+        assert!(c.get_host("bad").unwrap().compression().is_err());
+    }
+
+    #[test]
+    fn test_config_get_session_token() {
+        const CONF_SESSION_TOKEN: &str = r"---
+hosts:
+  s3:
+    region: us-east-2
+    access_key: ASIAXXX
+    secret_key: YYY
+    session_token: ZZZ";
+
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        tmp_file.write_all(CONF_SESSION_TOKEN.as_bytes()).unwrap();
+        let c = Config::new(tmp_file.into_temp_path().to_path_buf()).unwrap();
+        let h = c.get_host("s3").unwrap();
+        assert_eq!(
+            h.session_token.as_ref().map(ExposeSecret::expose_secret),
+            Some("ZZZ")
+        );
+    }
+
+    #[test]
+    fn test_config_addressing() {
+        const CONF_ADDRESSING: &str = r"---
+hosts:
+  aws:
+    region: us-west-1
+    access_key: XXX
+    secret_key: YYY
+    addressing: virtual
+  minio:
+    endpoint: http://localhost:9000
+    access_key: XXX
+    secret_key: YYY";
+
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        tmp_file.write_all(CONF_ADDRESSING.as_bytes()).unwrap();
+        let c = Config::new(tmp_file.into_temp_path().to_path_buf()).unwrap();
+
+        let h = c.get_host("aws").unwrap();
+        assert_eq!(h.addressing, Addressing::Virtual);
+        let s3 = h.get_s3(Some("bucket1".to_string()), false).unwrap();
+        assert_eq!(
+            s3.endpoint().unwrap().as_str(),
+            "https://bucket1.s3.us-west-1.amazonaws.com/"
+        );
+
+        let h = c.get_host("minio").unwrap();
+        assert_eq!(h.addressing, Addressing::Path);
+        let s3 = h.get_s3(Some("bucket1".to_string()), false).unwrap();
+        assert_eq!(
+            s3.endpoint().unwrap().as_str(),
+            "http://localhost:9000/bucket1"
+        );
+    }
+
+    #[test]
+    fn test_config_bandwidth_schedule() {
+        const CONF_SCHEDULE: &str = r#"---
+hosts:
+  backup:
+    region: us-west-1
+    access_key: XXX
+    secret_key: YYY
+    bandwidth_schedule:
+      "08:00-18:00": 20480
+      default: 0
+  s3:
+    region: us-west-1
+    access_key: XXX
+    secret_key: YYY"#;
+
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        tmp_file.write_all(CONF_SCHEDULE.as_bytes()).unwrap();
+        let c = Config::new(tmp_file.into_temp_path().to_path_buf()).unwrap();
+
+        let h = c.get_host("backup").unwrap();
+        let schedule = h.bandwidth_schedule.as_ref().unwrap();
+        assert_eq!(schedule.to_string(), "08:00-18:00=20480,default=0");
+        assert!(h.bandwidth().is_some());
+
+        let h = c.get_host("s3").unwrap();
+        assert!(h.bandwidth().is_none());
+
+        // invalid windows are rejected when loading the config
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        tmp_file
+            .write_all(b"---\nhosts:\n  s3:\n    bandwidth_schedule:\n      office: 1\n")
+            .unwrap();
+        assert!(Config::new(tmp_file.into_temp_path().to_path_buf()).is_err());
+    }
+
+    #[test]
+    fn test_config_get_profile() {
+        const CONF_PROFILE: &str = r"---
+hosts:
+  s3:
+    profile: backup
+    bucket: my-bucket";
+
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        tmp_file.write_all(CONF_PROFILE.as_bytes()).unwrap();
+        let c = Config::new(tmp_file.into_temp_path().to_path_buf()).unwrap();
+        let h = c.get_host("s3").unwrap();
+        assert_eq!(h.profile.as_deref(), Some("backup"));
+        assert_eq!(h.profile_name().as_deref(), Some("backup"));
+        assert!(h.access_key.is_empty());
+    }
+
+    #[test]
+    fn test_config_get_credentials_provider() {
+        const CONF_PROVIDER: &str = r"---
+hosts:
+  process:
+    region: us-east-2
+    credential_process: /usr/local/bin/get-credentials --role backup
+  irsa:
+    region: us-east-2
+    role_arn: arn:aws:iam::123456789012:role/s3m
+    web_identity_token_file: /var/run/secrets/token
+    sts_endpoint: https://sts.us-east-2.amazonaws.com
+  ec2:
+    region: us-east-2
+    credential_source: Ec2InstanceMetadata";
+
+        let mut tmp_file = NamedTempFile::new().unwrap();
+        tmp_file.write_all(CONF_PROVIDER.as_bytes()).unwrap();
+        let c = Config::new(tmp_file.into_temp_path().to_path_buf()).unwrap();
+
+        let h = c.get_host("process").unwrap();
+        assert_eq!(
+            h.provider.credential_process.as_deref(),
+            Some("/usr/local/bin/get-credentials --role backup")
+        );
+
+        let h = c.get_host("irsa").unwrap();
+        assert_eq!(
+            h.provider.sts_endpoint.as_deref(),
+            Some("https://sts.us-east-2.amazonaws.com")
+        );
+        assert!(h.provider.provider().is_some());
+
+        let h = c.get_host("ec2").unwrap();
+        assert_eq!(
+            h.provider.credential_source,
+            Some(crate::s3::credentials::CredentialSource::Ec2InstanceMetadata)
+        );
+    }
+
     #[test]
     fn test_config_get_aws_regions_endpoints() {
         // https://docs.aws.amazon.com/general/latest/gr/rande.html
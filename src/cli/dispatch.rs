@@ -2,7 +2,7 @@ use crate::{
     cli::{
         Config,
         actions::{
-            Action, DeleteGroup, DuGroupBy, ObjectLockSetTarget, StreamCommand,
+            Action, DeleteGroup, DuGroupBy, ObjectLockSetTarget, PutDestination, StreamCommand,
             monitor::{MonitorOutputFormat, prepare_checks},
         },
         age_filter::AgeFilter,
@@ -10,11 +10,20 @@ use crate::{
         s3_location::{S3Location, parse_location},
         start::get_host,
     },
-    s3::{Credentials, ObjectLock, ObjectLockMode, S3, actions::ObjectIdentifier},
+    s3::{
+        CopyConditions, ObjectLock, ObjectLockMode, Tagging,
+        actions::{ObjectIdentifier, VersioningStatus},
+        responses::{
+            AbortIncompleteMultipartUpload, LifecycleConfiguration, LifecycleFilter, LifecycleRule,
+            NoncurrentVersionExpiration,
+        },
+    },
+    stream::compression::{Codec, Compression},
 };
 use anyhow::{Context, Result, anyhow};
 use chrono::{DateTime, SecondsFormat, Utc};
 use colored::Colorize;
+use serde_yaml_ng as serde_yaml;
 use std::{
     borrow::ToOwned,
     collections::BTreeMap,
@@ -150,6 +159,12 @@ fn dispatch_get(hbk: &S3Location, matches: &clap::ArgMatches) -> Result<Action>
     let json = sub_m.get_one("json").copied().unwrap_or(false);
     let versions = sub_m.get_one("versions").copied().unwrap_or(false);
     let version = sub_m.get_one("version").cloned();
+    let raw = sub_m.get_one("raw").copied().unwrap_or(false);
+    let max_output = sub_m.get_one::<usize>("max-output").copied();
+    let number = sub_m
+        .get_one::<u8>("number")
+        .copied()
+        .unwrap_or_else(|| requested_parallel_requests(matches));
 
     if json && !metadata && !versions {
         return Err(anyhow!(
@@ -172,6 +187,72 @@ fn dispatch_get(hbk: &S3Location, matches: &clap::ArgMatches) -> Result<Action>
         quiet,
         versions,
         version,
+        raw,
+        max_output,
+        number,
+    })
+}
+
+fn dispatch_cp(hbk: &S3Location, matches: &clap::ArgMatches) -> Result<Action> {
+    let key = required_key(hbk)?;
+    let sub_m = subcommand_matches(matches, "cp")?;
+
+    let dest = sub_m
+        .get_many::<String>("arguments")
+        .unwrap_or_default()
+        .nth(1)
+        .context("destination missing, expected <s3 provider>/<bucket>/<file name>")?;
+    let dest = parse_location(dest, false, false)?;
+
+    if dest.host != hbk.host {
+        return Err(anyhow!(
+            "server-side copy requires source and destination on the same host: '{}' != '{}'",
+            hbk.host,
+            dest.host
+        ));
+    }
+
+    let dest_bucket = dest
+        .bucket
+        .context("destination bucket missing, expected <s3 provider>/<bucket>")?;
+
+    // copy into a "directory" keeps the source file name
+    let file_name = key.rsplit('/').next().unwrap_or(&key);
+    let dest_key = match dest.key {
+        Some(k) if k.ends_with('/') => format!("{k}{file_name}"),
+        Some(k) => k,
+        None => file_name.to_string(),
+    };
+
+    let meta = sub_m
+        .get_one::<String>("meta")
+        .map(|m| parse_metadata(m))
+        .transpose()?;
+
+    let conditions = CopyConditions {
+        if_match: sub_m.get_one::<String>("if-match").cloned(),
+        if_none_match: sub_m.get_one::<String>("if-none-match").cloned(),
+        if_modified_since: sub_m.get_one::<String>("if-modified-since").cloned(),
+        if_unmodified_since: sub_m.get_one::<String>("if-unmodified-since").cloned(),
+    };
+
+    Ok(Action::CopyObject {
+        key,
+        version_id: sub_m.get_one::<String>("version").cloned(),
+        dest_bucket,
+        dest_key,
+        acl: sub_m.get_one::<String>("acl").cloned(),
+        meta,
+        conditions,
+        buf_size: sub_m
+            .get_one::<usize>("buffer")
+            .copied()
+            .unwrap_or(10_485_760),
+        number: sub_m
+            .get_one::<u8>("number")
+            .copied()
+            .unwrap_or_else(|| requested_parallel_requests(matches)),
+        quiet: sub_m.get_flag("quiet"),
     })
 }
 
@@ -335,6 +416,28 @@ fn parse_retain_until(date: &str) -> Result<String> {
         .to_rfc3339_opts(SecondsFormat::Secs, true))
 }
 
+/// Turn compression on with `--compress`, keeping the codec and level of the
+/// host unless `--compress=<codec>` picks another codec. `--compress-level`
+/// implies `--compress` and is checked against the codec.
+fn apply_compression(matches: &clap::ArgMatches, global_args: &mut GlobalArgs) -> Result<()> {
+    if let Some(codec) = matches.get_one::<String>("compress") {
+        let codec = codec.parse::<Codec>()?;
+        if !global_args.compress || global_args.compression.codec != codec {
+            global_args.compression = Compression::new(codec);
+        }
+        global_args.compress = true;
+    } else if matches.contains_id("compress") {
+        global_args.compress = true;
+    }
+
+    if let Some(level) = matches.get_one::<i32>("compress-level") {
+        global_args.compression = global_args.compression.with_level(*level)?;
+        global_args.compress = true;
+    }
+
+    Ok(())
+}
+
 /// Build the [`ObjectLock`] settings from the upload-time flags, validating
 /// that retention `mode` and `retain-until` are supplied together and that the
 /// date parses as RFC 3339. Returns `None` when no Object Lock flag is set.
@@ -450,6 +553,145 @@ fn dispatch_object_lock_set(hbk: &S3Location, sub: &clap::ArgMatches) -> Result<
     }
 }
 
+fn dispatch_tag(hbk: &S3Location, matches: &clap::ArgMatches) -> Result<Action> {
+    let (verb, sub) = matches
+        .subcommand_matches("tag")
+        .and_then(clap::ArgMatches::subcommand)
+        .ok_or_else(|| anyhow!("tag requires a 'get', 'set' or 'rm' subcommand"))?;
+
+    let key = required_key(hbk)?;
+    let version_id = sub.get_one::<String>("version-id").cloned();
+
+    match verb {
+        "get" => Ok(Action::ObjectTagGet {
+            key,
+            version_id,
+            json: sub.get_flag("json"),
+        }),
+        "set" => {
+            let tags = sub
+                .get_one::<String>("tags")
+                .ok_or_else(|| anyhow!("missing tags, expected key1=value1;key2=value2"))?;
+
+            Ok(Action::ObjectTagSet {
+                key,
+                version_id,
+                tagging: tags.parse::<Tagging>()?,
+            })
+        }
+        "rm" => Ok(Action::ObjectTagDelete { key, version_id }),
+        other => Err(anyhow!("unknown tag subcommand: {other}")),
+    }
+}
+
+fn dispatch_versioning(hbk: &S3Location, matches: &clap::ArgMatches) -> Result<Action> {
+    let verb = matches
+        .subcommand_matches("versioning")
+        .and_then(clap::ArgMatches::subcommand)
+        .ok_or_else(|| anyhow!("versioning requires a 'get', 'enable' or 'suspend' subcommand"))?;
+
+    if hbk.key.is_some() {
+        return Err(anyhow!(
+            "versioning applies to a bucket (host/bucket), not an object"
+        ));
+    }
+
+    match verb {
+        ("get", sub) => Ok(Action::VersioningGet {
+            json: sub.get_flag("json"),
+        }),
+        ("enable", _) => Ok(Action::VersioningSet {
+            status: VersioningStatus::Enabled,
+        }),
+        ("suspend", _) => Ok(Action::VersioningSet {
+            status: VersioningStatus::Suspended,
+        }),
+        (other, _) => Err(anyhow!("unknown versioning subcommand: {other}")),
+    }
+}
+
+fn dispatch_lifecycle(hbk: &S3Location, matches: &clap::ArgMatches) -> Result<Action> {
+    let (verb, sub) = matches
+        .subcommand_matches("lifecycle")
+        .and_then(clap::ArgMatches::subcommand)
+        .ok_or_else(|| anyhow!("lifecycle requires a 'get', 'set' or 'rm' subcommand"))?;
+
+    if hbk.key.is_some() {
+        return Err(anyhow!(
+            "lifecycle applies to a bucket (host/bucket), not an object"
+        ));
+    }
+
+    match verb {
+        "get" => Ok(Action::LifecycleGet {
+            json: sub.get_flag("json"),
+        }),
+        "set" => Ok(Action::LifecycleSet {
+            rules: build_lifecycle_rules(sub)?,
+        }),
+        "rm" => Ok(Action::LifecycleDelete),
+        other => Err(anyhow!("unknown lifecycle subcommand: {other}")),
+    }
+}
+
+/// Rules from the YAML file followed by the shortcut rules; a shortcut replaces
+/// a file rule with the same ID.
+fn build_lifecycle_rules(sub: &clap::ArgMatches) -> Result<Vec<LifecycleRule>> {
+    let mut rules = match sub.get_one::<PathBuf>("file") {
+        Some(path) => {
+            let file = std::fs::File::open(path)
+                .with_context(|| format!("could not open {}", path.display()))?;
+            let config: LifecycleConfiguration = serde_yaml::from_reader(file)
+                .with_context(|| format!("unable to parse lifecycle rules {}", path.display()))?;
+            config.rules
+        }
+        None => Vec::new(),
+    };
+
+    let filter = Some(LifecycleFilter {
+        prefix: sub.get_one::<String>("prefix").cloned(),
+        ..Default::default()
+    });
+
+    let mut shortcuts = Vec::new();
+
+    if let Some(days) = sub.get_one::<u32>("abort-incomplete-after") {
+        shortcuts.push(LifecycleRule {
+            id: Some("s3m-abort-incomplete-uploads".to_string()),
+            filter: filter.clone(),
+            status: "Enabled".to_string(),
+            abort_incomplete_multipart_upload: Some(AbortIncompleteMultipartUpload {
+                days_after_initiation: *days,
+            }),
+            ..Default::default()
+        });
+    }
+
+    if let Some(days) = sub.get_one::<u32>("expire-noncurrent-after") {
+        shortcuts.push(LifecycleRule {
+            id: Some("s3m-expire-noncurrent-versions".to_string()),
+            filter,
+            status: "Enabled".to_string(),
+            noncurrent_version_expiration: Some(NoncurrentVersionExpiration {
+                noncurrent_days: Some(*days),
+                newer_noncurrent_versions: None,
+            }),
+            ..Default::default()
+        });
+    }
+
+    for rule in shortcuts {
+        rules.retain(|r| r.id != rule.id);
+        rules.push(rule);
+    }
+
+    if rules.is_empty() {
+        return Err(anyhow!("no lifecycle rules to set"));
+    }
+
+    Ok(rules)
+}
+
 fn dispatch_put(
     hbk: &S3Location,
     buf_size: usize,
@@ -464,7 +706,11 @@ fn dispatch_put(
         .map(String::as_str)
         .collect();
 
+    let pipe = matches.get_one("pipe").copied().unwrap_or(false);
+
+    // with --pipe every argument is a destination
     if args.len() == 2
+        && !pipe
         && let Some(arg) = args.first()
     {
         src = Some((*arg).to_string());
@@ -499,15 +745,16 @@ fn dispatch_put(
         .map(|meta_str| parse_metadata(meta_str))
         .transpose()?;
 
-    if !global_args.compress {
-        global_args.compress = matches.get_one("compress").copied().unwrap_or(false);
-    }
+    apply_compression(matches, global_args)?;
 
     if let Some(object_lock) = build_object_lock(matches)? {
         global_args.object_lock = Some(object_lock);
     }
 
-    let pipe = matches.get_one("pipe").copied().unwrap_or(false);
+    if let Some(tags) = matches.get_one::<String>("tag") {
+        global_args.tagging = Some(tags.parse::<Tagging>()?);
+    }
+
     if src.is_none() && !pipe {
         return Err(anyhow!(
             "Source file missing. Expected: {} {} {}\nFor more information try {}",
@@ -528,12 +775,25 @@ fn dispatch_put(
         key,
         pipe,
         quiet: matches.get_one("quiet").copied().unwrap_or(false),
+        spool: matches.get_one("spool").copied().unwrap_or(false),
         tmp_dir: matches
             .get_one::<PathBuf>("tmp-dir")
             .cloned()
             .unwrap_or_else(std::env::temp_dir),
         checksum_algorithm: matches.get_one("checksum").cloned(),
         number: matches.get_one::<u8>("number").copied().unwrap_or(1),
+        max_staging: matches
+            .get_one::<usize>("max-staging")
+            .copied()
+            .unwrap_or(2_147_483_648),
+        max_memory: matches.get_one::<usize>("max-memory").copied(),
+        size_hint: matches.get_one::<usize>("size-hint").copied(),
+        require: matches
+            .get_one::<String>("require")
+            .map(|require| require.parse())
+            .transpose()?
+            .unwrap_or_default(),
+        destinations: Vec::new(),
     })
 }
 
@@ -548,6 +808,7 @@ pub fn dispatch(
     match matches.subcommand_name() {
         Some("acl") => dispatch_acl(hbk, matches),
         Some("get") => dispatch_get(hbk, matches),
+        Some("cp") => dispatch_cp(hbk, matches),
         Some("du") => dispatch_du(hbk, matches),
         Some("ls") => dispatch_ls(hbk, matches),
         Some("monitor") => dispatch_monitor(hbk, matches),
@@ -555,6 +816,9 @@ pub fn dispatch(
         Some("rm") => dispatch_delete(hbk, matches),
         Some("share") => dispatch_share(hbk, matches),
         Some("object-lock") => dispatch_object_lock(hbk, matches),
+        Some("tag") => dispatch_tag(hbk, matches),
+        Some("versioning") => dispatch_versioning(hbk, matches),
+        Some("lifecycle") => dispatch_lifecycle(hbk, matches),
         _ => dispatch_put(hbk, buf_size, s3m_dir, matches, global_args),
     }
 }
@@ -568,9 +832,82 @@ pub fn finalize_action(
     config_path: &Path,
 ) -> Result<Action> {
     let action = finalize_monitor_action(action, config)?;
+    let action = finalize_put_action(action, matches, config, config_path)?;
     finalize_delete_action(action, matches, config, config_path)
 }
 
+fn finalize_put_action(
+    mut action: Action,
+    matches: &clap::ArgMatches,
+    config: &Config,
+    config_path: &Path,
+) -> Result<Action> {
+    if let Action::PutObject {
+        pipe: true,
+        destinations,
+        ..
+    } = &mut action
+        && matches.subcommand_name().is_none()
+    {
+        let args: Vec<&str> = matches
+            .get_many::<String>("arguments")
+            .unwrap_or_default()
+            .map(String::as_str)
+            .collect();
+
+        let no_sign_request = matches
+            .get_one::<bool>("no-sign-request")
+            .copied()
+            .unwrap_or(false);
+
+        *destinations = build_put_destinations(&args, config, config_path, no_sign_request)?;
+    }
+
+    Ok(action)
+}
+
+/// The destinations of a `--pipe` upload after the first one. The input is
+/// compressed and encrypted once, so their hosts must agree with the first
+/// one on `compress` and `enc_key`.
+fn build_put_destinations(
+    args: &[&str],
+    config: &Config,
+    config_path: &Path,
+    no_sign_request: bool,
+) -> Result<Vec<PutDestination>> {
+    let Some((first, others)) = args.split_first() else {
+        return Ok(Vec::new());
+    };
+
+    let first = parse_location(first, false, false)?;
+    let first_host = get_host(config, config_path, &first)?;
+
+    let mut destinations = Vec::with_capacity(others.len());
+
+    for arg in others {
+        let location = parse_location(arg, false, false)?;
+        let key = location.key.clone().ok_or_else(|| {
+            anyhow!("Destination missing key: {arg}. Expected <s3 provider>/<bucket>/<key>")
+        })?;
+
+        let host = get_host(config, config_path, &location)?;
+
+        if host.compression()? != first_host.compression()? || host.enc_key != first_host.enc_key {
+            return Err(anyhow!(
+                "Destination {arg}: the hosts of a --pipe upload must share their compress and enc_key settings"
+            ));
+        }
+
+        destinations.push(PutDestination {
+            host: location.host.clone(),
+            key,
+            s3: host.get_s3(location.bucket.clone(), no_sign_request)?,
+        });
+    }
+
+    Ok(destinations)
+}
+
 fn finalize_monitor_action(action: Action, config: &Config) -> Result<Action> {
     match action {
         Action::Monitor {
@@ -764,8 +1101,6 @@ fn build_delete_groups(
         }
 
         let host = get_host(config, config_path, &location)?;
-        let region = host.get_region()?;
-        let credentials = Credentials::new(&host.access_key, &host.secret_key);
 
         groups.insert(
             group_key,
@@ -774,7 +1109,7 @@ fn build_delete_groups(
                     key,
                     version_id: None,
                 }],
-                s3: S3::new(&credentials, &region, Some(bucket), no_sign_request),
+                s3: host.get_s3(Some(bucket), no_sign_request)?,
             },
         );
     }
@@ -788,14 +1123,18 @@ mod tests {
     use super::*;
     use crate::cli::{
         actions::Action,
-        commands::{cmd_acl, cmd_cb, cmd_du, cmd_get, cmd_ls, cmd_monitor, cmd_rm, cmd_share, new},
+        commands::{
+            cmd_acl, cmd_cb, cmd_cp, cmd_du, cmd_get, cmd_lifecycle, cmd_ls, cmd_monitor, cmd_rm,
+            cmd_share, cmd_tag, cmd_versioning, new,
+        },
         config::Config,
         globals::GlobalArgs,
         s3_location::host_bucket_key,
         start::get_host,
     };
+    use crate::stream::fanout::Require;
     use clap::Command;
-    use std::fs::File;
+    use std::fs::{self, File};
     use std::io::Write;
     use tempfile::Builder;
 
@@ -943,6 +1282,9 @@ hosts:
                 json,
                 versions,
                 version,
+                raw,
+                max_output,
+                number,
             } => {
                 assert_eq!(key, "f");
                 assert!(!metadata);
@@ -952,6 +1294,9 @@ hosts:
                 assert!(!json);
                 assert!(!versions);
                 assert_eq!(version, None);
+                assert!(!raw);
+                assert_eq!(max_output, None);
+                assert_eq!(number, default_parallel_requests());
                 assert!(!globals.compress);
             }
             _ => panic!("wrong action"),
@@ -989,6 +1334,9 @@ hosts:
                 json,
                 versions,
                 version,
+                raw,
+                max_output,
+                number,
             } => {
                 assert_eq!(key, "f");
                 assert!(!metadata);
@@ -998,6 +1346,9 @@ hosts:
                 assert!(!json);
                 assert!(!versions);
                 assert_eq!(version, None);
+                assert!(!raw);
+                assert_eq!(max_output, None);
+                assert_eq!(number, default_parallel_requests());
                 assert!(!globals.compress);
             }
             _ => panic!("wrong action"),
@@ -1035,6 +1386,9 @@ hosts:
                 json,
                 versions,
                 version,
+                raw,
+                max_output,
+                number,
             } => {
                 assert_eq!(key, "key");
                 assert!(!metadata);
@@ -1044,6 +1398,9 @@ hosts:
                 assert!(!json);
                 assert!(!versions);
                 assert_eq!(version, None);
+                assert!(!raw);
+                assert_eq!(max_output, None);
+                assert_eq!(number, default_parallel_requests());
                 assert!(!globals.compress);
             }
             _ => panic!("wrong action"),
@@ -1563,6 +1920,115 @@ hosts:
         }
     }
 
+    #[test]
+    fn test_finalize_action_put_pipe_destinations() {
+        let (tmp_dir, config_path, config) = write_config_file();
+        let filepath = config_path.as_os_str().to_str().unwrap();
+        let cmd = new(&tmp_dir.keep());
+        let matches = cmd
+            .clone()
+            .try_get_matches_from(vec![
+                "test",
+                "--config",
+                filepath,
+                "--pipe",
+                "--require",
+                "any",
+                "s3/bucket-a/db.sql",
+                "s3alt/bucket-b/backups/db.sql",
+            ])
+            .unwrap();
+        let mut globals = GlobalArgs::new();
+        let s3_location = host_bucket_key(&matches).unwrap();
+        assert_eq!(s3_location.host, "s3");
+
+        let action = dispatch(&s3_location, 0, Path::new(""), &matches, &mut globals).unwrap();
+        let action = finalize_action(action, &matches, &config, &config_path).unwrap();
+
+        match action {
+            Action::PutObject {
+                host,
+                key,
+                file,
+                require,
+                destinations,
+                ..
+            } => {
+                assert_eq!(host, "s3");
+                assert_eq!(key, "db.sql");
+                assert_eq!(file, None);
+                assert_eq!(require, Require::Any);
+                assert_eq!(destinations.len(), 1);
+                let destination = destinations.first().unwrap();
+                assert_eq!(destination.host, "s3alt");
+                assert_eq!(destination.key, "backups/db.sql");
+                assert_eq!(destination.s3.bucket(), Some("bucket-b"));
+            }
+            _ => panic!("wrong action"),
+        }
+
+        // several destinations need --pipe
+        let matches = cmd
+            .clone()
+            .try_get_matches_from(vec![
+                "test",
+                "--config",
+                filepath,
+                "s3/bucket-a/db.sql",
+                "s3alt/bucket-b/db.sql",
+                "s3alt/bucket-c/db.sql",
+            ])
+            .unwrap();
+        assert!(host_bucket_key(&matches).is_err());
+
+        // every destination needs a key
+        let matches = cmd
+            .try_get_matches_from(vec![
+                "test",
+                "--config",
+                filepath,
+                "--pipe",
+                "s3/bucket-a/db.sql",
+                "s3alt/bucket-b",
+            ])
+            .unwrap();
+        let s3_location = host_bucket_key(&matches).unwrap();
+        let action = dispatch(&s3_location, 0, Path::new(""), &matches, &mut globals).unwrap();
+        let err = finalize_action(action, &matches, &config, &config_path).unwrap_err();
+        assert!(err.to_string().contains("missing key"), "{err}");
+    }
+
+    #[test]
+    fn test_finalize_action_put_pipe_destinations_share_encryption() {
+        let tmp_dir = Builder::new().prefix("test-s3m-").tempdir().unwrap();
+        let config_path = tmp_dir.path().join("config.yaml");
+        fs::write(
+            &config_path,
+            format!("{CONF}\n  s3enc:\n    region: us-east-1\n    access_key: XXX\n    secret_key: YYY\n    enc_key: 0123456789abcdef0123456789abcdef"),
+        )
+        .unwrap();
+        let config = Config::new(config_path.clone()).unwrap();
+        let filepath = config_path.as_os_str().to_str().unwrap();
+
+        let matches = new(tmp_dir.path())
+            .try_get_matches_from(vec![
+                "test",
+                "--config",
+                filepath,
+                "--pipe",
+                "s3/bucket-a/db.sql",
+                "s3enc/bucket-b/db.sql",
+            ])
+            .unwrap();
+        let mut globals = GlobalArgs::new();
+        let s3_location = host_bucket_key(&matches).unwrap();
+        let action = dispatch(&s3_location, 0, Path::new(""), &matches, &mut globals).unwrap();
+
+        // the input would not be encrypted for s3enc
+        let err = finalize_action(action, &matches, &config, &config_path).unwrap_err();
+        assert!(err.to_string().contains("enc_key"), "{err}");
+    }
+
     #[test]
     fn test_finalize_action_groups_multiple_objects_by_bucket() {
         let (tmp_dir, config_path, config) = write_config_file();
@@ -1816,6 +2282,302 @@ hosts:
         assert!(err.contains("Object target missing key"));
     }
 
+    fn dispatch_cp_args(args: &[&str]) -> Result<Action> {
+        let cmd = Command::new("test").subcommand(cmd_cp::command());
+        let mut cli = vec!["test", "cp"];
+        cli.extend_from_slice(args);
+        let matches = cmd.try_get_matches_from(cli).unwrap();
+        let s3_location = host_bucket_key(&matches)?;
+        dispatch(
+            &s3_location,
+            0,
+            Path::new(""),
+            &matches,
+            &mut GlobalArgs::new(),
+        )
+    }
+
+    #[test]
+    fn test_dispatch_cp() {
+        let action = dispatch_cp_args(&[
+            "h/bucket/dir/file.dat",
+            "h/dest/copy.dat",
+            "--meta",
+            "team=ops",
+            "--version",
+            "v1",
+            "--if-none-match",
+            "\"abc\"",
+            "-n",
+            "4",
+        ])
+        .unwrap();
+        match action {
+            Action::CopyObject {
+                key,
+                version_id,
+                dest_bucket,
+                dest_key,
+                acl,
+                meta,
+                conditions,
+                buf_size,
+                number,
+                quiet,
+            } => {
+                assert_eq!(key, "dir/file.dat");
+                assert_eq!(version_id, Some("v1".to_string()));
+                assert_eq!(dest_bucket, "dest");
+                assert_eq!(dest_key, "copy.dat");
+                assert_eq!(acl, None);
+                assert_eq!(
+                    meta.unwrap().get("x-amz-meta-team").map(String::as_str),
+                    Some("ops")
+                );
+                assert_eq!(conditions.if_none_match, Some("\"abc\"".to_string()));
+                assert_eq!(buf_size, 10_485_760);
+                assert_eq!(number, 4);
+                assert!(!quiet);
+            }
+            _ => panic!("wrong action"),
+        }
+    }
+
+    #[test]
+    fn test_dispatch_cp_keeps_file_name() {
+        for (dest, expected) in [
+            ("h/dest", "file.dat"),
+            ("h/dest/", "file.dat"),
+            ("h/dest/archive/", "archive/file.dat"),
+        ] {
+            match dispatch_cp_args(&["h/bucket/dir/file.dat", dest]).unwrap() {
+                Action::CopyObject { dest_key, .. } => assert_eq!(dest_key, expected),
+                _ => panic!("wrong action"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_dispatch_cp_rejects_other_host() {
+        let err = dispatch_cp_args(&["h/bucket/file", "other/bucket/file"])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("same host"));
+    }
+
+    fn dispatch_tag_args(args: &[&str]) -> Result<Action> {
+        let cmd = Command::new("test").subcommand(cmd_tag::command());
+        let mut cli = vec!["test", "tag"];
+        cli.extend_from_slice(args);
+        let matches = cmd.try_get_matches_from(cli).unwrap();
+        let s3_location = host_bucket_key(&matches)?;
+        dispatch(
+            &s3_location,
+            0,
+            Path::new(""),
+            &matches,
+            &mut GlobalArgs::new(),
+        )
+    }
+
+    #[test]
+    fn test_dispatch_tag() {
+        match dispatch_tag_args(&["get", "h/bucket/dir/file", "--json"]).unwrap() {
+            Action::ObjectTagGet {
+                key,
+                version_id,
+                json,
+            } => {
+                assert_eq!(key, "dir/file");
+                assert_eq!(version_id, None);
+                assert!(json);
+            }
+            _ => panic!("wrong action"),
+        }
+
+        match dispatch_tag_args(&["set", "h/bucket/file", "a=1;b=2", "--version-id", "v1"]).unwrap()
+        {
+            Action::ObjectTagSet {
+                key,
+                version_id,
+                tagging,
+            } => {
+                assert_eq!(key, "file");
+                assert_eq!(version_id, Some("v1".to_string()));
+                assert_eq!(tagging.tags().len(), 2);
+            }
+            _ => panic!("wrong action"),
+        }
+
+        assert!(matches!(
+            dispatch_tag_args(&["rm", "h/bucket/file"]).unwrap(),
+            Action::ObjectTagDelete { .. }
+        ));
+    }
+
+    #[test]
+    fn test_dispatch_tag_requires_key() {
+        assert!(dispatch_tag_args(&["get", "h/bucket"]).is_err());
+        assert!(dispatch_tag_args(&["set", "h/bucket/file", "=v"]).is_err());
+    }
+
+    fn dispatch_versioning_args(args: &[&str]) -> Result<Action> {
+        let cmd = Command::new("test").subcommand(cmd_versioning::command());
+        let mut cli = vec!["test", "versioning"];
+        cli.extend_from_slice(args);
+        let matches = cmd.try_get_matches_from(cli).unwrap();
+        let s3_location = host_bucket_key(&matches)?;
+        dispatch(
+            &s3_location,
+            0,
+            Path::new(""),
+            &matches,
+            &mut GlobalArgs::new(),
+        )
+    }
+
+    #[test]
+    fn test_dispatch_versioning() {
+        assert!(matches!(
+            dispatch_versioning_args(&["get", "h/bucket", "--json"]).unwrap(),
+            Action::VersioningGet { json: true }
+        ));
+        assert!(matches!(
+            dispatch_versioning_args(&["enable", "h/bucket"]).unwrap(),
+            Action::VersioningSet {
+                status: VersioningStatus::Enabled
+            }
+        ));
+        assert!(matches!(
+            dispatch_versioning_args(&["suspend", "h/bucket"]).unwrap(),
+            Action::VersioningSet {
+                status: VersioningStatus::Suspended
+            }
+        ));
+    }
+
+    #[test]
+    fn test_dispatch_versioning_rejects_object() {
+        let err = dispatch_versioning_args(&["enable", "h/bucket/key"])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("applies to a bucket"));
+    }
+
+    fn dispatch_lifecycle_args(args: &[&str]) -> Result<Action> {
+        let cmd = Command::new("test").subcommand(cmd_lifecycle::command());
+        let mut cli = vec!["test", "lifecycle"];
+        cli.extend_from_slice(args);
+        let matches = cmd.try_get_matches_from(cli).unwrap();
+        let s3_location = host_bucket_key(&matches)?;
+        dispatch(
+            &s3_location,
+            0,
+            Path::new(""),
+            &matches,
+            &mut GlobalArgs::new(),
+        )
+    }
+
+    #[test]
+    fn test_dispatch_lifecycle_shortcuts() {
+        let action = dispatch_lifecycle_args(&[
+            "set",
+            "h/bucket",
+            "--abort-incomplete-after",
+            "7d",
+            "--expire-noncurrent-after",
+            "30d",
+            "--prefix",
+            "logs/",
+        ])
+        .unwrap();
+        let Action::LifecycleSet { rules } = action else {
+            panic!("wrong action");
+        };
+        let [abort, noncurrent] = rules.as_slice() else {
+            panic!("expected two rules");
+        };
+        assert_eq!(
+            abort
+                .abort_incomplete_multipart_upload
+                .as_ref()
+                .map(|a| a.days_after_initiation),
+            Some(7)
+        );
+        assert_eq!(
+            noncurrent
+                .noncurrent_version_expiration
+                .as_ref()
+                .and_then(|e| e.noncurrent_days),
+            Some(30)
+        );
+        assert_eq!(
+            noncurrent.filter.as_ref().and_then(|f| f.prefix.as_deref()),
+            Some("logs/")
+        );
+    }
+
+    #[test]
+    fn test_dispatch_lifecycle_file_and_shortcut() {
+        let tmp_dir = Builder::new().prefix("test-s3m-").tempdir().unwrap();
+        let rules_path = tmp_dir.path().join("rules.yml");
+        std::fs::write(
+            &rules_path,
+            "Rules:\n  - ID: logs\n    Status: Enabled\n    Filter:\n      Prefix: logs/\n    Expiration:\n      Days: 365\n  - ID: s3m-abort-incomplete-uploads\n    Status: Disabled\n    AbortIncompleteMultipartUpload:\n      DaysAfterInitiation: 1\n",
+        )
+        .unwrap();
+
+        let action = dispatch_lifecycle_args(&[
+            "set",
+            "h/bucket",
+            rules_path.to_str().unwrap(),
+            "--abort-incomplete-after",
+            "3d",
+        ])
+        .unwrap();
+        let Action::LifecycleSet { rules } = action else {
+            panic!("wrong action");
+        };
+        let [logs, abort] = rules.as_slice() else {
+            panic!("expected two rules");
+        };
+        assert_eq!(logs.id.as_deref(), Some("logs"));
+        assert_eq!(abort.status, "Enabled");
+        assert_eq!(
+            abort
+                .abort_incomplete_multipart_upload
+                .as_ref()
+                .map(|a| a.days_after_initiation),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_dispatch_lifecycle_invalid_file() {
+        let tmp_dir = Builder::new().prefix("test-s3m-").tempdir().unwrap();
+        let rules_path = tmp_dir.path().join("rules.yml");
+        std::fs::write(&rules_path, "Rules:\n  - ID: [broken\n").unwrap();
+
+        let err = dispatch_lifecycle_args(&["set", "h/bucket", rules_path.to_str().unwrap()])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unable to parse lifecycle rules"));
+    }
+
+    #[test]
+    fn test_dispatch_lifecycle_get_rm() {
+        assert!(matches!(
+            dispatch_lifecycle_args(&["get", "h/bucket", "--json"]).unwrap(),
+            Action::LifecycleGet { json: true }
+        ));
+        assert!(matches!(
+            dispatch_lifecycle_args(&["rm", "h/bucket"]).unwrap(),
+            Action::LifecycleDelete
+        ));
+        assert!(dispatch_lifecycle_args(&["rm", "h/bucket/key"]).is_err());
+    }
+
     #[test]
     fn test_dispatch_share() {
         let cmd = Command::new("test").subcommand(cmd_share::command());
@@ -1885,9 +2647,15 @@ hosts:
                 key,
                 pipe,
                 quiet,
+                spool,
                 tmp_dir,
                 checksum_algorithm,
                 number,
+                max_staging,
+                max_memory,
+                size_hint,
+                require,
+                destinations,
             } => {
                 assert_eq!(acl, None);
                 assert_eq!(meta, None);
@@ -1898,6 +2666,12 @@ hosts:
                 assert_eq!(key, "key");
                 assert!(!pipe);
                 assert!(!quiet);
+                assert!(!spool);
+                assert_eq!(max_staging, 2_147_483_648);
+                assert_eq!(max_memory, None);
+                assert_eq!(size_hint, None);
+                assert_eq!(require, Require::All);
+                assert!(destinations.is_empty());
                 assert_eq!(tmp_dir, std::env::temp_dir());
                 assert_eq!(checksum_algorithm, None);
                 assert_eq!(
@@ -1971,9 +2745,15 @@ hosts:
                 key,
                 pipe,
                 quiet,
+                spool,
                 tmp_dir,
                 checksum_algorithm,
                 number,
+                max_staging,
+                max_memory,
+                size_hint,
+                require,
+                destinations,
             } => {
                 assert_eq!(acl, None);
                 assert_eq!(meta, None);
@@ -1984,6 +2764,12 @@ hosts:
                 assert_eq!(key, filepath.to_string());
                 assert!(!pipe);
                 assert!(!quiet);
+                assert!(!spool);
+                assert_eq!(max_staging, 2_147_483_648);
+                assert_eq!(max_memory, None);
+                assert_eq!(size_hint, None);
+                assert_eq!(require, Require::All);
+                assert!(destinations.is_empty());
                 assert_eq!(tmp_dir, std::env::temp_dir());
                 assert_eq!(checksum_algorithm, None);
                 assert_eq!(
@@ -2040,9 +2826,15 @@ hosts:
                 key,
                 pipe,
                 quiet,
+                spool,
                 tmp_dir,
                 checksum_algorithm,
                 number,
+                max_staging,
+                max_memory,
+                size_hint,
+                require,
+                destinations,
             } => {
                 assert_eq!(acl, Some("public-read".to_string()));
                 assert_eq!(meta, None);
@@ -2053,6 +2845,12 @@ hosts:
                 assert_eq!(key, "key");
                 assert!(!pipe);
                 assert!(!quiet);
+                assert!(!spool);
+                assert_eq!(max_staging, 2_147_483_648);
+                assert_eq!(max_memory, None);
+                assert_eq!(size_hint, None);
+                assert_eq!(require, Require::All);
+                assert!(destinations.is_empty());
                 assert_eq!(tmp_dir, std::env::temp_dir());
                 assert_eq!(checksum_algorithm, None);
                 assert_eq!(number, 32);
@@ -2108,9 +2906,15 @@ hosts:
                 key,
                 pipe,
                 quiet,
+                spool,
                 tmp_dir,
                 checksum_algorithm,
                 number,
+                max_staging,
+                max_memory,
+                size_hint,
+                require,
+                destinations,
             } => {
                 assert_eq!(acl, None);
                 assert_eq!(
@@ -2132,6 +2936,12 @@ hosts:
                 assert_eq!(key, "f");
                 assert!(!pipe);
                 assert!(!quiet);
+                assert!(!spool);
+                assert_eq!(max_staging, 2_147_483_648);
+                assert_eq!(max_memory, None);
+                assert_eq!(size_hint, None);
+                assert_eq!(require, Require::All);
+                assert!(destinations.is_empty());
                 assert_eq!(tmp_dir, std::env::temp_dir());
                 assert_eq!(checksum_algorithm, Some("sha256".to_string()));
                 assert_eq!(number, 4);
@@ -2182,9 +2992,15 @@ hosts:
                 key,
                 pipe,
                 quiet,
+                spool,
                 tmp_dir,
                 checksum_algorithm,
                 number,
+                max_staging,
+                max_memory,
+                size_hint,
+                require,
+                destinations,
             } => {
                 assert_eq!(acl, None);
                 assert_eq!(meta, None);
@@ -2195,6 +3011,12 @@ hosts:
                 assert_eq!(key, "f");
                 assert!(!pipe);
                 assert!(!quiet);
+                assert!(!spool);
+                assert_eq!(max_staging, 2_147_483_648);
+                assert_eq!(max_memory, None);
+                assert_eq!(size_hint, None);
+                assert_eq!(require, Require::All);
+                assert!(destinations.is_empty());
                 assert_eq!(tmp_dir, std::env::temp_dir());
                 assert_eq!(checksum_algorithm, None);
                 assert_eq!(
@@ -2207,6 +3029,58 @@ hosts:
         }
     }
 
+    #[test]
+    fn test_apply_compression() {
+        let tmp_dir = Builder::new().prefix("test-s3m-").tempdir().unwrap();
+        let config_path = tmp_dir.path().join("config.yaml");
+        let mut config = File::create(&config_path).unwrap();
+        config.write_all(CONF.as_bytes()).unwrap();
+        let filepath = config_path.as_os_str().to_str().unwrap();
+
+        let matches_from = |args: &[&str]| {
+            let mut argv = vec!["test", "--config", filepath];
+            argv.extend_from_slice(args);
+            argv.extend([filepath, "s3/bucket/f"]);
+            new(tmp_dir.path()).try_get_matches_from(argv).unwrap()
+        };
+
+        // --compress keeps the codec and level of the host
+        let mut globals = GlobalArgs::new();
+        globals.compress = true;
+        globals.compression = Compression::new(Codec::Xz).with_level(9).unwrap();
+        apply_compression(&matches_from(&["-x"]), &mut globals).unwrap();
+        assert_eq!(
+            globals.compression,
+            Compression::new(Codec::Xz).with_level(9).unwrap()
+        );
+
+        // another codec drops the level of the host
+        apply_compression(&matches_from(&["--compress=gzip"]), &mut globals).unwrap();
+        assert_eq!(globals.compression, Compression::new(Codec::Gzip));
+
+        // --compress-level implies --compress
+        let mut globals = GlobalArgs::new();
+        apply_compression(&matches_from(&["--compress-level", "19"]), &mut globals).unwrap();
+        assert!(globals.compress);
+        assert_eq!(
+            globals.compression,
+            Compression::new(Codec::Zstd).with_level(19).unwrap()
+        );
+
+        let mut globals = GlobalArgs::new();
+        assert!(
+            apply_compression(
+                &matches_from(&["--compress=lz4", "--compress-level", "13"]),
+                &mut globals
+            )
+            .is_err()
+        );
+
+        let mut globals = GlobalArgs::new();
+        apply_compression(&matches_from(&[]), &mut globals).unwrap();
+        assert!(!globals.compress);
+    }
+
     #[test]
     fn test_dispatch_put_compress() {
         let tmp_dir = Builder::new().prefix("test-s3m-").tempdir().unwrap();
@@ -2251,9 +3125,15 @@ hosts:
                 key,
                 pipe,
                 quiet,
+                spool,
                 tmp_dir,
                 checksum_algorithm,
                 number,
+                max_staging,
+                max_memory,
+                size_hint,
+                require,
+                destinations,
             } => {
                 assert_eq!(acl, None);
                 assert_eq!(meta, None);
@@ -2264,6 +3144,12 @@ hosts:
                 assert_eq!(key, "f");
                 assert!(!pipe);
                 assert!(!quiet);
+                assert!(!spool);
+                assert_eq!(max_staging, 2_147_483_648);
+                assert_eq!(max_memory, None);
+                assert_eq!(size_hint, None);
+                assert_eq!(require, Require::All);
+                assert!(destinations.is_empty());
                 assert_eq!(tmp_dir, std::env::temp_dir());
                 assert_eq!(checksum_algorithm, None);
                 assert_eq!(
@@ -2276,6 +3162,122 @@ hosts:
         }
     }
 
+    #[test]
+    fn test_dispatch_put_pipe_tag() {
+        let tmp_dir = Builder::new().prefix("test-s3m-").tempdir().unwrap();
+        let config_path = tmp_dir.path().join("config.yaml");
+        let mut config = File::create(&config_path).unwrap();
+        config.write_all(CONF.as_bytes()).unwrap();
+        let filepath = config_path.as_os_str().to_str().unwrap();
+
+        let cmd = new(&tmp_dir.keep());
+        let matches = cmd
+            .try_get_matches_from(vec![
+                "test",
+                "--config",
+                filepath,
+                "--pipe",
+                "s3/bucket/f",
+                "--tag",
+                "team=ops;env=prod",
+            ])
+            .unwrap();
+        let mut globals = GlobalArgs::new();
+        let s3_location = host_bucket_key(&matches).unwrap();
+
+        let action = dispatch(&s3_location, 0, Path::new(""), &matches, &mut globals).unwrap();
+        assert!(matches!(action, Action::PutObject { pipe: true, .. }));
+        assert_eq!(
+            globals.tagging.map(|t| t.header_value()),
+            Some("env=prod&team=ops".to_string())
+        );
+    }
+
+    #[test]
+    fn test_dispatch_put_pipe_spool() {
+        let tmp_dir = Builder::new().prefix("test-s3m-").tempdir().unwrap();
+        let config_path = tmp_dir.path().join("config.yaml");
+        let mut config = File::create(&config_path).unwrap();
+        config.write_all(CONF.as_bytes()).unwrap();
+        let filepath = config_path.as_os_str().to_str().unwrap();
+
+        let cmd = new(&tmp_dir.keep());
+        let matches = cmd
+            .clone()
+            .try_get_matches_from(vec![
+                "test",
+                "--config",
+                filepath,
+                "--pipe",
+                "--spool",
+                "s3/bucket/f",
+            ])
+            .unwrap();
+        let mut globals = GlobalArgs::new();
+        let s3_location = host_bucket_key(&matches).unwrap();
+
+        let action = dispatch(&s3_location, 0, Path::new(""), &matches, &mut globals).unwrap();
+        assert!(matches!(
+            action,
+            Action::PutObject {
+                pipe: true,
+                spool: true,
+                ..
+            }
+        ));
+
+        let matches = cmd
+            .clone()
+            .try_get_matches_from(vec![
+                "test",
+                "--config",
+                filepath,
+                "--pipe",
+                "--max-staging",
+                "1073741824",
+                "--size-hint",
+                "1099511627776",
+                "--max-memory",
+                "67108864",
+                "s3/bucket/f",
+            ])
+            .unwrap();
+        let action = dispatch(&s3_location, 0, Path::new(""), &matches, &mut globals).unwrap();
+        assert!(matches!(
+            action,
+            Action::PutObject {
+                max_staging: 1_073_741_824,
+                max_memory: Some(67_108_864),
+                size_hint: Some(1_099_511_627_776),
+                ..
+            }
+        ));
+
+        // a spool is on disk
+        assert!(
+            cmd.clone()
+                .try_get_matches_from(vec![
+                    "test",
+                    "--config",
+                    filepath,
+                    "--pipe",
+                    "--spool",
+                    "--max-memory",
+                    "67108864",
+                    "s3/bucket/f",
+                ])
+                .is_err()
+        );
+
+        // --spool and --size-hint only apply to --pipe
+        for flag in [vec!["--spool"], vec!["--size-hint", "1024"]] {
+            let mut args = vec!["test", "--config", filepath];
+            args.extend(flag);
+            args.extend([filepath, "s3/bucket/f"]);
+            assert!(cmd.clone().try_get_matches_from(args).is_err());
+        }
+    }
+
     #[test]
     fn test_metadata_parsing_valid() {
         // Test the metadata parsing logic directly
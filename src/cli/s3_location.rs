@@ -137,21 +137,24 @@ pub fn host_bucket_key(matches: &ArgMatches) -> Result<S3Location> {
     log::debug!("Subcommand: {subcommand:?}");
 
     match subcommand {
-        Some("object-lock") => parse_object_lock_args(matches),
-        Some(cmd @ ("acl" | "du" | "get" | "ls" | "cb" | "rm" | "share" | "monitor")) => {
+        Some(cmd @ ("lifecycle" | "object-lock" | "tag" | "versioning")) => {
+            parse_nested_subcommand_args(matches, cmd)
+        }
+        Some(cmd @ ("acl" | "cp" | "du" | "get" | "ls" | "cb" | "rm" | "share" | "monitor")) => {
             parse_subcommand_args(matches, cmd)
         }
         _ => parse_put_object_args(matches),
     }
 }
 
-/// `object-lock` nests a `get`/`set` subcommand that carries the `arguments`,
-/// so descend one extra level to find the `host/bucket[/key]` location.
-fn parse_object_lock_args(matches: &ArgMatches) -> Result<S3Location> {
+/// `lifecycle`, `object-lock`, `tag` and `versioning` nest a `get`/`set`/...
+/// subcommand that carries the `arguments`, so descend one extra level to find
+/// the `host/bucket[/key]` location.
+fn parse_nested_subcommand_args(matches: &ArgMatches, subcommand: &str) -> Result<S3Location> {
     let (_, sub_m) = matches
-        .subcommand_matches("object-lock")
+        .subcommand_matches(subcommand)
         .and_then(ArgMatches::subcommand)
-        .ok_or_else(|| anyhow!("object-lock requires a 'get' or 'set' subcommand"))?;
+        .ok_or_else(|| anyhow!("{subcommand} requires a subcommand"))?;
 
     let location = sub_m
         .get_many::<String>("arguments")
@@ -190,7 +193,13 @@ fn parse_subcommand_args(matches: &ArgMatches, subcommand: &str) -> Result<S3Loc
 fn parse_put_object_args(matches: &ArgMatches) -> Result<S3Location> {
     let args = get_main_arguments(matches);
 
+    let pipe = matches.get_one::<bool>("pipe").copied().unwrap_or(false);
+
     let s3_location = match args.len() {
+        // Format: s3m host/bucket/key [host/bucket/key ...] (with --pipe)
+        n if pipe && n > 1 => args
+            .first()
+            .ok_or_else(|| anyhow!("Missing S3 location argument"))?,
         2 => {
             // Format: s3m /path/to/file host/bucket/key
             args.get(1)
@@ -79,8 +79,8 @@ fn build_global_args(matches: &ArgMatches) -> GlobalArgs {
     let mut global_args = GlobalArgs::new();
 
     if let Some(throttle) = matches.get_one::<usize>("throttle").filter(|&n| *n > 0) {
-        global_args.throttle = Some(*throttle);
-        log::info!("throttle bandwidth: {throttle}KB/s");
+        global_args.set_throttle(*throttle);
+        log::info!("throttle bandwidth: {throttle}KB/s in total");
     }
 
     let retries: usize = matches.get_one::<usize>("retries").map_or(3, |size| *size);
@@ -126,8 +126,9 @@ fn handle_config_only_subcommands(
 }
 
 fn apply_host_defaults(host: &Host, global_args: &mut GlobalArgs) -> Result<()> {
-    if host.compress.unwrap_or(false) {
+    if let Some(compression) = host.compression()? {
         global_args.compress = true;
+        global_args.compression = compression;
     }
 
     if let Some(enc_key) = &host.enc_key {
@@ -139,18 +140,21 @@ fn apply_host_defaults(host: &Host, global_args: &mut GlobalArgs) -> Result<()>
         global_args.enc_key = Some(SecretString::new(enc_key.clone().into()));
     }
 
+    // -k takes precedence over the schedule of the host
+    if global_args.throttle.is_none() {
+        global_args.throttle = host.bandwidth();
+    }
+
     Ok(())
 }
 
 fn build_s3(matches: &ArgMatches, host: &Host, bucket: Option<String>) -> Result<S3> {
-    let region = host.get_region()?;
-    let credentials = Credentials::new(&host.access_key, &host.secret_key);
     let no_sign_request = matches
         .get_one::<bool>("no-sign-request")
         .copied()
         .unwrap_or(false);
 
-    Ok(S3::new(&credentials, &region, bucket, no_sign_request))
+    host.get_s3(bucket, no_sign_request)
 }
 
 fn resolve_s3_and_action(
@@ -167,7 +171,8 @@ fn resolve_s3_and_action(
     log::debug!("host: {host:#?}");
 
     apply_host_defaults(host, global_args)?;
-    let s3 = build_s3(matches, host, s3_location.bucket.clone())?;
+    let s3 = build_s3(matches, host, s3_location.bucket.clone())?
+        .with_retry_policy(global_args.retry_policy());
     log::debug!("S3:\n{s3}");
 
     let action = dispatch::dispatch(&s3_location, buf_size, config_path, matches, global_args)?;
@@ -256,6 +261,7 @@ mod tests {
         cli::commands::new,
         s3::{Credentials, Region, S3},
         stream::{
+            compression::Compression,
             db::Db,
             state::{StreamMetadata, StreamMode, state_dir, write_metadata},
         },
@@ -360,6 +366,7 @@ hosts:
                 updated_at: Some(1),
                 pipe: false,
                 compress: false,
+                compression: Compression::default(),
                 encrypt: false,
                 mode: StreamMode::FileMultipart,
             },
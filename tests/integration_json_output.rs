@@ -271,3 +271,198 @@ fn test_du_json_grouped_by_day() {
     assert_eq!(stdout["days"][1]["date"], "2026-03-14");
     assert_eq!(stdout["total"]["bytes"], 12);
 }
+
+#[test]
+fn test_aws_profile_credentials_and_endpoint() {
+    let mut server = Server::new();
+    let _versioning = server
+        .mock("GET", "/bucket")
+        .match_query(Matcher::UrlEncoded("versioning".into(), String::new()))
+        .match_header("x-amz-security-token", "profile-token")
+        .match_header(
+            "authorization",
+            Matcher::Regex("Credential=ASIAPROFILE/.*/eu-west-1/s3/".to_string()),
+        )
+        .with_status(200)
+        .with_body(
+            r#"<VersioningConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/"><Status>Enabled</Status></VersioningConfiguration>"#,
+        )
+        .create();
+
+    let dir = tempfile::tempdir().unwrap();
+    let credentials = dir.path().join("credentials");
+    let aws_config = dir.path().join("config");
+    std::fs::write(
+        &credentials,
+        "[backup]\naws_access_key_id = ASIAPROFILE\naws_secret_access_key = secret\naws_session_token = profile-token\n",
+    )
+    .unwrap();
+    std::fs::write(
+        &aws_config,
+        format!(
+            "[profile backup]\nregion = eu-west-1\nendpoint_url = {}\n",
+            server.url()
+        ),
+    )
+    .unwrap();
+
+    let config = dir.path().join("config.yml");
+    std::fs::write(&config, "---\nhosts:\n  s3:\n    profile: backup\n").unwrap();
+
+    let output = Command::new(get_s3m_binary())
+        .arg("--config")
+        .arg(&config)
+        .args(["versioning", "get", "s3/bucket", "--json"])
+        .env("AWS_SHARED_CREDENTIALS_FILE", &credentials)
+        .env("AWS_CONFIG_FILE", &aws_config)
+        .env_remove("AWS_ACCESS_KEY_ID")
+        .env_remove("AWS_SECRET_ACCESS_KEY")
+        .env_remove("AWS_SESSION_TOKEN")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stdout["status"], "Enabled");
+}
+
+#[test]
+fn test_versioning_get_json_output() {
+    let mut server = Server::new();
+    let _versioning = server
+        .mock("GET", "/bucket")
+        .match_query(Matcher::UrlEncoded("versioning".into(), String::new()))
+        .with_status(200)
+        .with_header("content-type", "application/xml")
+        .with_body(
+            r#"<?xml version="1.0" encoding="UTF-8"?><VersioningConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/"><Status>Suspended</Status><MfaDelete>Enabled</MfaDelete></VersioningConfiguration>"#,
+        )
+        .create();
+
+    let config = create_config_file(&server.url(), "minioadmin", "minioadmin");
+    let output = run_s3m_with_config(config.path(), &["versioning", "get", "s3/bucket", "--json"]);
+
+    assert!(output.status.success());
+    let stdout: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stdout["kind"], "versioning");
+    assert_eq!(stdout["enabled"], false);
+    assert_eq!(stdout["status"], "Suspended");
+    assert_eq!(stdout["mfa_delete"], "Enabled");
+}
+
+#[test]
+fn test_versioning_get_unversioned_bucket() {
+    let mut server = Server::new();
+    let _versioning = server
+        .mock("GET", "/bucket")
+        .match_query(Matcher::UrlEncoded("versioning".into(), String::new()))
+        .with_status(200)
+        .with_body(r#"<VersioningConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/"/>"#)
+        .create();
+
+    let config = create_config_file(&server.url(), "minioadmin", "minioadmin");
+    let output = run_s3m_with_config(config.path(), &["versioning", "get", "s3/bucket"]);
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Versioning: Unversioned"));
+    assert!(stdout.contains("MFA delete: Disabled"));
+}
+
+#[test]
+fn test_versioning_enable() {
+    let mut server = Server::new();
+    let put = server
+        .mock("PUT", "/bucket")
+        .match_query(Matcher::UrlEncoded("versioning".into(), String::new()))
+        .match_body(Matcher::Regex("<Status>Enabled</Status>".into()))
+        .with_status(200)
+        .expect(1)
+        .create();
+
+    let config = create_config_file(&server.url(), "minioadmin", "minioadmin");
+    let output = run_s3m_with_config(config.path(), &["versioning", "enable", "s3/bucket"]);
+
+    assert!(output.status.success());
+    put.assert();
+}
+
+#[test]
+fn test_lifecycle_get_json_output() {
+    let mut server = Server::new();
+    let _lifecycle = server
+        .mock("GET", "/bucket")
+        .match_query(Matcher::UrlEncoded("lifecycle".into(), String::new()))
+        .with_status(200)
+        .with_header("content-type", "application/xml")
+        .with_body(
+            r#"<?xml version="1.0" encoding="UTF-8"?><LifecycleConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/"><Rule><ID>mpu</ID><Filter></Filter><Status>Enabled</Status><AbortIncompleteMultipartUpload><DaysAfterInitiation>7</DaysAfterInitiation></AbortIncompleteMultipartUpload></Rule></LifecycleConfiguration>"#,
+        )
+        .create();
+
+    let config = create_config_file(&server.url(), "minioadmin", "minioadmin");
+    let output = run_s3m_with_config(config.path(), &["lifecycle", "get", "s3/bucket", "--json"]);
+
+    assert!(output.status.success());
+    let stdout: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stdout["kind"], "lifecycle");
+    assert_eq!(stdout["rules"][0]["ID"], "mpu");
+    assert_eq!(
+        stdout["rules"][0]["AbortIncompleteMultipartUpload"]["DaysAfterInitiation"],
+        7
+    );
+}
+
+#[test]
+fn test_lifecycle_get_without_configuration() {
+    let mut server = Server::new();
+    let _lifecycle = server
+        .mock("GET", "/bucket")
+        .match_query(Matcher::UrlEncoded("lifecycle".into(), String::new()))
+        .with_status(404)
+        .with_body(
+            r"<Error><Code>NoSuchLifecycleConfiguration</Code><Message>The lifecycle configuration does not exist</Message></Error>",
+        )
+        .create();
+
+    let config = create_config_file(&server.url(), "minioadmin", "minioadmin");
+    let output = run_s3m_with_config(config.path(), &["lifecycle", "get", "s3/bucket", "--json"]);
+
+    assert!(output.status.success());
+    let stdout: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stdout["rules"], Value::Array(vec![]));
+}
+
+#[test]
+fn test_lifecycle_set_shortcut() {
+    let mut server = Server::new();
+    let put = server
+        .mock("PUT", "/bucket")
+        .match_query(Matcher::UrlEncoded("lifecycle".into(), String::new()))
+        .match_header("content-md5", Matcher::Any)
+        .match_body(Matcher::Regex(
+            "<Filter/><Status>Enabled</Status><AbortIncompleteMultipartUpload><DaysAfterInitiation>7</DaysAfterInitiation>".into(),
+        ))
+        .with_status(200)
+        .expect(1)
+        .create();
+
+    let config = create_config_file(&server.url(), "minioadmin", "minioadmin");
+    let output = run_s3m_with_config(
+        config.path(),
+        &[
+            "lifecycle",
+            "set",
+            "s3/bucket",
+            "--abort-incomplete-after",
+            "7d",
+        ],
+    );
+
+    assert!(output.status.success());
+    put.assert();
+}
@@ -0,0 +1,189 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+
+use mockito::Server;
+use s3m::{
+    cli::Config,
+    s3::{
+        ClientConfig, Credentials, Region, RequestOptions, S3,
+        actions::{HeadObject, PutObject},
+        request::{RetryPolicy, is_retryable},
+    },
+};
+use secrecy::SecretString;
+use std::{
+    io::Write,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+use tempfile::NamedTempFile;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+    sync::Mutex,
+};
+
+fn test_s3(endpoint: &str) -> S3 {
+    let credentials = Credentials::new(
+        "minioadmin",
+        &SecretString::new("minioadmin".to_string().into()),
+    );
+    let region = Region::custom("us-east-1", endpoint);
+
+    S3::new(&credentials, &region, Some("bucket".to_string()), false)
+}
+
+/// Plain HTTP proxy answering `200 OK` itself, returns its URL and the request
+/// lines it received.
+async fn proxy() -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let seen = requests.clone();
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut request = Vec::new();
+            let mut buf = [0; 4096];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                match stream.read(&mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+
+            let line = String::from_utf8_lossy(&request)
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            seen.lock().await.push(line);
+
+            let _ = stream
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\netag: \"proxy\"\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                )
+                .await;
+        }
+    });
+
+    (url, requests)
+}
+
+#[tokio::test]
+async fn test_proxy() {
+    let (proxy_url, requests) = proxy().await;
+
+    // the endpoint only resolves through the proxy
+    let config = ClientConfig {
+        proxy: Some(proxy_url),
+        ..ClientConfig::default()
+    };
+    let s3 = test_s3("http://s3.example.invalid")
+        .with_client_config(&config)
+        .unwrap();
+
+    let headers = HeadObject::new("key", None).request(&s3).await.unwrap();
+    assert_eq!(headers.get("etag"), Some(&"\"proxy\"".to_string()));
+
+    let requests = requests.lock().await;
+    assert_eq!(requests.len(), 1);
+    assert!(
+        requests[0].starts_with("HEAD http://s3.example.invalid/bucket/key "),
+        "{}",
+        requests[0]
+    );
+}
+
+#[tokio::test]
+async fn test_no_proxy() {
+    let (proxy_url, requests) = proxy().await;
+
+    let mut server = Server::new_async().await;
+    let direct = server
+        .mock("HEAD", "/bucket/key")
+        .with_status(200)
+        .with_header("etag", "\"direct\"")
+        .create_async()
+        .await;
+
+    let config = ClientConfig {
+        proxy: Some(proxy_url),
+        no_proxy: Some("localhost,127.0.0.1".to_string()),
+        ..ClientConfig::default()
+    };
+    let s3 = test_s3(&server.url()).with_client_config(&config).unwrap();
+
+    let headers = HeadObject::new("key", None).request(&s3).await.unwrap();
+    assert_eq!(headers.get("etag"), Some(&"\"direct\"".to_string()));
+
+    direct.assert_async().await;
+    assert!(requests.lock().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_read_timeout_is_retried() {
+    // accepts connections and never answers
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let connections = Arc::new(AtomicUsize::new(0));
+    let accepted = connections.clone();
+
+    tokio::spawn(async move {
+        let mut stalled = Vec::new();
+        while let Ok((stream, _)) = listener.accept().await {
+            accepted.fetch_add(1, Ordering::SeqCst);
+            stalled.push(stream);
+        }
+    });
+
+    let config = ClientConfig {
+        read_timeout: Some(Duration::from_millis(300)),
+        ..ClientConfig::default()
+    };
+    let s3 = test_s3(&endpoint)
+        .with_client_config(&config)
+        .unwrap()
+        .with_retry_policy(
+            RetryPolicy::new(2).with_delays(Duration::from_millis(1), Duration::from_millis(1)),
+        );
+
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(b"stalled upload").unwrap();
+
+    let err = PutObject::new("key", file.path(), None, None, None, None, None)
+        .request(&s3, RequestOptions::new())
+        .await
+        .unwrap_err();
+
+    assert!(is_retryable(&err), "{err}");
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_host_network_config() {
+    let (proxy_url, requests) = proxy().await;
+
+    let yaml = format!(
+        "---\nhosts:\n  dmz:\n    endpoint: http://s3.example.invalid\n    access_key: XXX\n    secret_key: YYY\n    proxy: {proxy_url}\n    connect_timeout: 5\n    read_timeout: 30\n    pool_idle_timeout: 90\n"
+    );
+
+    let mut config_file = NamedTempFile::new().unwrap();
+    config_file.write_all(yaml.as_bytes()).unwrap();
+    let config = Config::new(config_file.path().to_path_buf()).unwrap();
+
+    let host = config.get_host("dmz").unwrap();
+    assert_eq!(host.client.read_timeout, Some(Duration::from_secs(30)));
+
+    let s3 = host.get_s3(Some("bucket".to_string()), false).unwrap();
+    HeadObject::new("key", None).request(&s3).await.unwrap();
+    assert_eq!(requests.lock().await.len(), 1);
+}
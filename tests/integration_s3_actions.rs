@@ -10,21 +10,30 @@ use chrono::Utc;
 use mockito::{Matcher, Server};
 use s3m::{
     cli::{
-        actions::{Action as CliAction, DeleteGroup, DuGroupBy, object_delete, object_du},
+        actions::{
+            Action as CliAction, DeleteGroup, DuGroupBy, object_copy, object_delete, object_du,
+        },
         globals::GlobalArgs,
     },
     s3::{
-        Credentials, Region, S3,
+        CopyConditions, CopySource, Credentials, MetadataDirective, Region, S3, Tagging,
         actions::{
-            AbortMultipartUpload, CreateBucket, CreateMultipartUpload, DeleteBucket, DeleteObject,
-            DeleteObjects, GetObject, GetObjectAcl, GetObjectAttributes, HeadObject, ListBuckets,
-            ListMultipartUploads, ListObjectVersions, ListObjectsV2, ObjectIdentifier,
-            PutObjectAcl,
+            AbortMultipartUpload, CopyObject, CreateBucket, CreateMultipartUpload, DeleteBucket,
+            DeleteObject, DeleteObjectTagging, DeleteObjects, GetObject, GetObjectAcl,
+            GetObjectAttributes, GetObjectTagging, HeadObject, ListBuckets, ListMultipartUploads,
+            ListObjectVersions, ListObjectsV2, ObjectIdentifier, PutObjectAcl, PutObjectTagging,
+            UploadPartCopy,
         },
+        credentials::ContainerProvider,
+        request::RetryPolicy,
     },
 };
 use secrecy::SecretString;
-use std::fmt::Write as _;
+use std::{
+    fmt::Write as _,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 fn test_s3(endpoint: String, bucket: Option<&str>) -> S3 {
     let credentials = Credentials::new(
@@ -896,6 +905,35 @@ async fn test_object_metadata_actions_request_success() {
     assert_eq!(get.text().await.unwrap(), "data");
 }
 
+#[tokio::test]
+async fn test_actions_send_session_token() {
+    let mut server = Server::new_async().await;
+    let head = server
+        .mock("HEAD", "/bucket/key")
+        .match_header("x-amz-security-token", "session-token")
+        .match_header(
+            "authorization",
+            Matcher::Regex("SignedHeaders=.*x-amz-security-token".to_string()),
+        )
+        .with_status(200)
+        .with_header("etag", "\"head-etag\"")
+        .create_async()
+        .await;
+
+    let credentials = Credentials::new(
+        "minioadmin",
+        &SecretString::new("minioadmin".to_string().into()),
+    )
+    .with_session_token(Some(SecretString::new("session-token".into())));
+    let region = Region::custom("us-east-1", server.url());
+    let s3 = S3::new(&credentials, &region, Some("bucket".to_string()), false);
+
+    let rs = HeadObject::new("key", None).request(&s3).await.unwrap();
+
+    assert_eq!(rs.get("etag"), Some(&"\"head-etag\"".to_string()));
+    head.assert_async().await;
+}
+
 #[tokio::test]
 async fn test_listing_actions_request_success() {
     let mut server = Server::new_async().await;
@@ -1322,3 +1360,798 @@ async fn test_create_multipart_upload_request_success() {
     assert_eq!(created.key, "key");
     assert_eq!(created.upload_id, "upload-id");
 }
+
+#[tokio::test]
+async fn test_copy_object_request_success() {
+    let mut server = Server::new_async().await;
+    let _copy = server
+        .mock("PUT", "/dest/copy/key")
+        .match_header("x-amz-copy-source", "/bucket/dir/a%20b.txt?versionId=v1")
+        .match_header("x-amz-metadata-directive", "REPLACE")
+        .match_header("x-amz-meta-team", "ops")
+        .match_header("x-amz-copy-source-if-match", "\"src-etag\"")
+        .with_status(200)
+        .with_header("x-amz-version-id", "new-version")
+        .with_header("x-amz-copy-source-version-id", "v1")
+        .with_body(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<CopyObjectResult><LastModified>2025-03-13T00:00:00.000Z</LastModified><ETag>"copy-etag"</ETag></CopyObjectResult>"#,
+        )
+        .create_async()
+        .await;
+
+    let s3 = test_s3(server.url(), Some("dest"));
+
+    let mut meta = std::collections::BTreeMap::new();
+    meta.insert("x-amz-meta-team".to_string(), "ops".to_string());
+
+    let output = CopyObject::new(
+        "copy/key",
+        &CopySource::new("bucket", "dir/a b.txt").version_id(Some("v1".to_string())),
+    )
+    .meta(Some(meta))
+    .metadata_directive(Some(MetadataDirective::Replace))
+    .conditions(CopyConditions {
+        if_match: Some("\"src-etag\"".to_string()),
+        ..Default::default()
+    })
+    .request(&s3)
+    .await
+    .unwrap();
+
+    assert_eq!(output.result.e_tag, "\"copy-etag\"");
+    assert_eq!(output.version_id.as_deref(), Some("new-version"));
+    assert_eq!(output.copy_source_version_id.as_deref(), Some("v1"));
+}
+
+#[tokio::test]
+async fn test_copy_object_error_in_200_response() {
+    let mut server = Server::new_async().await;
+    let _copy = server
+        .mock("PUT", "/dest/key")
+        .with_status(200)
+        .with_body(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<Error><Code>InternalError</Code><Message>We encountered an internal error. Please try again.</Message><RequestId>id</RequestId></Error>"#,
+        )
+        .create_async()
+        .await;
+
+    let s3 = test_s3(server.url(), Some("dest"));
+
+    let err = CopyObject::new("key", &CopySource::new("bucket", "key"))
+        .request(&s3)
+        .await
+        .unwrap_err();
+
+    assert_eq!(err.code(), Some("InternalError"));
+}
+
+#[tokio::test]
+async fn test_upload_part_copy_request_success() {
+    let mut server = Server::new_async().await;
+    let _part = server
+        .mock("PUT", "/dest/key")
+        .match_query(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("partNumber".into(), "2".into()),
+            Matcher::UrlEncoded("uploadId".into(), "upload-id".into()),
+        ]))
+        .match_header("x-amz-copy-source", "/bucket/key")
+        .match_header("x-amz-copy-source-range", "bytes=100-199")
+        .with_status(200)
+        .with_body(
+            r#"<CopyPartResult><LastModified>2025-03-13T00:00:00.000Z</LastModified><ETag>"part-etag"</ETag></CopyPartResult>"#,
+        )
+        .create_async()
+        .await;
+
+    let s3 = test_s3(server.url(), Some("dest"));
+
+    let etag = UploadPartCopy::new("key", "upload-id", 2, &CopySource::new("bucket", "key"))
+        .range(100, 100)
+        .request(&s3)
+        .await
+        .unwrap();
+
+    assert_eq!(etag, "\"part-etag\"");
+}
+
+#[tokio::test]
+async fn test_multipart_parts_refresh_expiring_credentials() {
+    let mut server = Server::new_async().await;
+
+    // credentials that are always within the refresh window
+    let expiration = (Utc::now() + chrono::Duration::minutes(1)).to_rfc3339();
+    let credentials = server
+        .mock("GET", "/credentials")
+        .with_status(200)
+        .with_body(format!(
+            r#"{{"AccessKeyId": "ASIAROTATED", "SecretAccessKey": "secret", "Token": "rotated-token", "Expiration": "{expiration}"}}"#
+        ))
+        .expect(3)
+        .create_async()
+        .await;
+    let part = server
+        .mock("PUT", "/dest/key")
+        .match_query(Matcher::Any)
+        .match_header("x-amz-security-token", "rotated-token")
+        .match_header(
+            "authorization",
+            Matcher::Regex("Credential=ASIAROTATED/".to_string()),
+        )
+        .with_status(200)
+        .with_body(
+            r#"<CopyPartResult><LastModified>2025-03-13T00:00:00.000Z</LastModified><ETag>"part-etag"</ETag></CopyPartResult>"#,
+        )
+        .expect(2)
+        .create_async()
+        .await;
+
+    let provider = ContainerProvider::new(&format!("{}/credentials", server.url()));
+    let s3 = S3::new(
+        &Credentials::from_parts("", SecretString::default(), None),
+        &Region::custom("us-east-1", server.url()),
+        Some("dest".to_string()),
+        false,
+    )
+    .with_credentials_provider(Arc::new(provider));
+
+    // initial fetch, as done at startup
+    s3.refresh_credentials().await.unwrap();
+
+    for part_number in 1..=2 {
+        UploadPartCopy::new(
+            "key",
+            "upload-id",
+            part_number,
+            &CopySource::new("bucket", "key"),
+        )
+        .request(&s3)
+        .await
+        .unwrap();
+    }
+
+    credentials.assert_async().await;
+    part.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_cp_small_object_uses_copy_object() {
+    let mut server = Server::new_async().await;
+    let _head = server
+        .mock("HEAD", "/bucket/file.txt")
+        .with_status(200)
+        .with_header("content-length", "4")
+        .with_header("etag", "\"src-etag\"")
+        .create_async()
+        .await;
+    let copy = server
+        .mock("PUT", "/dest/archive/file.txt")
+        .match_header("x-amz-copy-source", "/bucket/file.txt")
+        .with_status(200)
+        .with_body(r#"<CopyObjectResult><ETag>"copy-etag"</ETag></CopyObjectResult>"#)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let s3 = test_s3(server.url(), Some("bucket"));
+
+    let action = CliAction::CopyObject {
+        key: "file.txt".to_string(),
+        version_id: None,
+        dest_bucket: "dest".to_string(),
+        dest_key: "archive/file.txt".to_string(),
+        acl: None,
+        meta: None,
+        conditions: CopyConditions::default(),
+        buf_size: 10_485_760,
+        number: 2,
+        quiet: true,
+    };
+
+    object_copy::handle(&s3, action, GlobalArgs::new())
+        .await
+        .unwrap();
+
+    copy.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_cp_large_object_uses_upload_part_copy() {
+    let mut server = Server::new_async().await;
+    // one byte over the CopyObject limit
+    let _head = server
+        .mock("HEAD", "/bucket/big.iso")
+        .with_status(200)
+        .with_header("content-length", "5368709121")
+        .with_header("etag", "\"src-etag\"")
+        .with_header("content-type", "application/x-iso9660-image")
+        .with_header("x-amz-meta-team", "ops")
+        .create_async()
+        .await;
+    let create = server
+        .mock("POST", "/dest/big.iso")
+        .match_query(Matcher::UrlEncoded("uploads".into(), String::new()))
+        .match_header("content-type", "application/x-iso9660-image")
+        .match_header("x-amz-meta-team", "ops")
+        .with_status(200)
+        .with_body(
+            r"<InitiateMultipartUploadResult><Bucket>dest</Bucket><Key>big.iso</Key><UploadId>upload-id</UploadId></InitiateMultipartUploadResult>",
+        )
+        .expect(1)
+        .create_async()
+        .await;
+    // 1 GiB parts: 5 full parts plus a final 1 byte part
+    let last_part = server
+        .mock("PUT", "/dest/big.iso")
+        .match_query(Matcher::UrlEncoded("partNumber".into(), "6".into()))
+        .match_header("x-amz-copy-source-range", "bytes=5368709120-5368709120")
+        .with_status(200)
+        .with_body(r#"<CopyPartResult><ETag>"part-etag"</ETag></CopyPartResult>"#)
+        .expect(1)
+        .create_async()
+        .await;
+    let parts = server
+        .mock("PUT", "/dest/big.iso")
+        .match_query(Matcher::UrlEncoded("uploadId".into(), "upload-id".into()))
+        .match_header("x-amz-copy-source", "/bucket/big.iso")
+        .match_header("x-amz-copy-source-if-match", "\"src-etag\"")
+        .with_status(200)
+        .with_body(r#"<CopyPartResult><ETag>"part-etag"</ETag></CopyPartResult>"#)
+        .expect(5)
+        .create_async()
+        .await;
+    let complete = server
+        .mock("POST", "/dest/big.iso")
+        .match_query(Matcher::UrlEncoded("uploadId".into(), "upload-id".into()))
+        .match_body(Matcher::Regex("<PartNumber>6</PartNumber>".into()))
+        .with_status(200)
+        .with_body(
+            r#"<CompleteMultipartUploadResult><Location>loc</Location><Bucket>dest</Bucket><Key>big.iso</Key><ETag>"final-6"</ETag></CompleteMultipartUploadResult>"#,
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let s3 = test_s3(server.url(), Some("bucket"));
+
+    let action = CliAction::CopyObject {
+        key: "big.iso".to_string(),
+        version_id: None,
+        dest_bucket: "dest".to_string(),
+        dest_key: "big.iso".to_string(),
+        acl: None,
+        meta: None,
+        conditions: CopyConditions::default(),
+        buf_size: 1_073_741_824,
+        number: 3,
+        quiet: true,
+    };
+
+    object_copy::handle(&s3, action, GlobalArgs::new())
+        .await
+        .unwrap();
+
+    create.assert_async().await;
+    last_part.assert_async().await;
+    parts.assert_async().await;
+    complete.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_cp_large_object_with_meta_keeps_content_type() {
+    let mut server = Server::new_async().await;
+    let _head = server
+        .mock("HEAD", "/bucket/big.iso")
+        .with_status(200)
+        .with_header("content-length", "5368709121")
+        .with_header("etag", "\"src-etag\"")
+        .with_header("content-type", "application/x-iso9660-image")
+        .with_header("x-amz-meta-team", "ops")
+        .create_async()
+        .await;
+    let create = server
+        .mock("POST", "/dest/big.iso")
+        .match_query(Matcher::UrlEncoded("uploads".into(), String::new()))
+        .match_header("content-type", "application/x-iso9660-image")
+        .match_header("x-amz-meta-owner", "alice")
+        .match_header("x-amz-meta-team", Matcher::Missing)
+        .with_status(200)
+        .with_body(
+            r"<InitiateMultipartUploadResult><Bucket>dest</Bucket><Key>big.iso</Key><UploadId>upload-id</UploadId></InitiateMultipartUploadResult>",
+        )
+        .expect(1)
+        .create_async()
+        .await;
+    let _parts = server
+        .mock("PUT", "/dest/big.iso")
+        .match_query(Matcher::UrlEncoded("uploadId".into(), "upload-id".into()))
+        .with_status(200)
+        .with_body(r#"<CopyPartResult><ETag>"part-etag"</ETag></CopyPartResult>"#)
+        .create_async()
+        .await;
+    let _complete = server
+        .mock("POST", "/dest/big.iso")
+        .match_query(Matcher::UrlEncoded("uploadId".into(), "upload-id".into()))
+        .with_status(200)
+        .with_body(
+            r#"<CompleteMultipartUploadResult><Location>loc</Location><Bucket>dest</Bucket><Key>big.iso</Key><ETag>"final-6"</ETag></CompleteMultipartUploadResult>"#,
+        )
+        .create_async()
+        .await;
+
+    let s3 = test_s3(server.url(), Some("bucket"));
+
+    let action = CliAction::CopyObject {
+        key: "big.iso".to_string(),
+        version_id: None,
+        dest_bucket: "dest".to_string(),
+        dest_key: "big.iso".to_string(),
+        acl: None,
+        meta: Some(std::collections::BTreeMap::from([(
+            "x-amz-meta-owner".to_string(),
+            "alice".to_string(),
+        )])),
+        conditions: CopyConditions::default(),
+        buf_size: 1_073_741_824,
+        number: 3,
+        quiet: true,
+    };
+
+    object_copy::handle(&s3, action, GlobalArgs::new())
+        .await
+        .unwrap();
+
+    create.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_cp_retries_throttled_part_copy() {
+    let mut server = Server::new_async().await;
+    let _head = server
+        .mock("HEAD", "/bucket/big.iso")
+        .with_status(200)
+        .with_header("content-length", "5368709121")
+        .with_header("etag", "\"src-etag\"")
+        .create_async()
+        .await;
+    let _create = server
+        .mock("POST", "/dest/big.iso")
+        .match_query(Matcher::UrlEncoded("uploads".into(), String::new()))
+        .with_status(200)
+        .with_body(
+            r"<InitiateMultipartUploadResult><Bucket>dest</Bucket><Key>big.iso</Key><UploadId>upload-id</UploadId></InitiateMultipartUploadResult>",
+        )
+        .create_async()
+        .await;
+    let throttled = server
+        .mock("PUT", "/dest/big.iso")
+        .match_query(Matcher::UrlEncoded("partNumber".into(), "6".into()))
+        .with_status(503)
+        .with_header("Retry-After", "1")
+        .with_body(
+            r"<Error><Code>SlowDown</Code><Message>Please reduce your request rate.</Message><RequestId>1</RequestId></Error>",
+        )
+        .expect(1)
+        .create_async()
+        .await;
+    let parts = server
+        .mock("PUT", "/dest/big.iso")
+        .match_query(Matcher::UrlEncoded("uploadId".into(), "upload-id".into()))
+        .with_status(200)
+        .with_body(r#"<CopyPartResult><ETag>"part-etag"</ETag></CopyPartResult>"#)
+        .expect(6)
+        .create_async()
+        .await;
+    let complete = server
+        .mock("POST", "/dest/big.iso")
+        .match_query(Matcher::UrlEncoded("uploadId".into(), "upload-id".into()))
+        .with_status(200)
+        .with_body(
+            r#"<CompleteMultipartUploadResult><Location>loc</Location><Bucket>dest</Bucket><Key>big.iso</Key><ETag>"final-6"</ETag></CompleteMultipartUploadResult>"#,
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let s3 = test_s3(server.url(), Some("bucket"));
+
+    let action = CliAction::CopyObject {
+        key: "big.iso".to_string(),
+        version_id: None,
+        dest_bucket: "dest".to_string(),
+        dest_key: "big.iso".to_string(),
+        acl: None,
+        meta: None,
+        conditions: CopyConditions::default(),
+        buf_size: 1_073_741_824,
+        number: 3,
+        quiet: true,
+    };
+
+    let mut globals = GlobalArgs::new();
+    globals.retries = 2;
+
+    let started = Instant::now();
+    object_copy::handle(&s3, action, globals).await.unwrap();
+    assert!(started.elapsed() >= Duration::from_secs(1));
+
+    throttled.assert_async().await;
+    parts.assert_async().await;
+    complete.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_create_multipart_upload_sends_tagging_header() {
+    let mut server = Server::new_async().await;
+    let create = server
+        .mock("POST", "/bucket/key")
+        .match_query(Matcher::UrlEncoded("uploads".into(), String::new()))
+        .match_header("x-amz-tagging", "env=prod&team=data%20ops")
+        .with_status(200)
+        .with_body(
+            r"<InitiateMultipartUploadResult><Bucket>bucket</Bucket><Key>key</Key><UploadId>upload-id</UploadId></InitiateMultipartUploadResult>",
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let s3 = test_s3(server.url(), Some("bucket"));
+    let tagging: Tagging = "team=data ops;env=prod".parse().unwrap();
+
+    let created = CreateMultipartUpload::new("key", None, None, None, None)
+        .tagging(Some(&tagging))
+        .request(&s3)
+        .await
+        .unwrap();
+
+    assert_eq!(created.upload_id, "upload-id");
+    create.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_object_tagging_get_put_delete() {
+    let mut server = Server::new_async().await;
+    let get = server
+        .mock("GET", "/bucket/key")
+        .match_query(Matcher::UrlEncoded("tagging".into(), String::new()))
+        .with_status(200)
+        .with_body(
+            r#"<?xml version="1.0" encoding="UTF-8"?><Tagging xmlns="http://s3.amazonaws.com/doc/2006-03-01/"><TagSet><Tag><Key>team</Key><Value>ops</Value></Tag></TagSet></Tagging>"#,
+        )
+        .expect(1)
+        .create_async()
+        .await;
+    let put = server
+        .mock("PUT", "/bucket/key")
+        .match_query(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("tagging".into(), String::new()),
+            Matcher::UrlEncoded("versionId".into(), "v1".into()),
+        ]))
+        .match_header("content-md5", Matcher::Any)
+        .match_body(Matcher::Regex(
+            "<Tag><Key>team</Key><Value>ops</Value></Tag>".into(),
+        ))
+        .with_status(200)
+        .expect(1)
+        .create_async()
+        .await;
+    let delete = server
+        .mock("DELETE", "/bucket/key")
+        .match_query(Matcher::UrlEncoded("tagging".into(), String::new()))
+        .with_status(204)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let s3 = test_s3(server.url(), Some("bucket"));
+    let tagging: Tagging = "team=ops".parse().unwrap();
+
+    PutObjectTagging::new("key", &tagging, Some("v1".to_string()))
+        .request(&s3)
+        .await
+        .unwrap();
+
+    let tags = GetObjectTagging::new("key", None)
+        .request(&s3)
+        .await
+        .unwrap();
+    assert_eq!(tags.tag_set.tags.len(), 1);
+    assert_eq!(tags.tag_set.tags[0].key, "team");
+    assert_eq!(tags.tag_set.tags[0].value, "ops");
+
+    DeleteObjectTagging::new("key", None)
+        .request(&s3)
+        .await
+        .unwrap();
+
+    put.assert_async().await;
+    get.assert_async().await;
+    delete.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_get_object_tagging_error() {
+    let mut server = Server::new_async().await;
+    let _get = server
+        .mock("GET", "/bucket/missing")
+        .match_query(Matcher::UrlEncoded("tagging".into(), String::new()))
+        .with_status(404)
+        .with_body(
+            r"<Error><Code>NoSuchKey</Code><Message>The specified key does not exist.</Message></Error>",
+        )
+        .create_async()
+        .await;
+
+    let s3 = test_s3(server.url(), Some("bucket"));
+
+    let err = GetObjectTagging::new("missing", None)
+        .request(&s3)
+        .await
+        .unwrap_err();
+    assert!(err.is_not_found());
+}
+
+#[tokio::test]
+async fn test_request_time_too_skewed_is_signed_again() {
+    // the server is an hour ahead of the local clock
+    let server_time = Utc::now() + chrono::TimeDelta::hours(1);
+    let date = server_time.to_rfc2822();
+    let skewed = Utc::now().format("%Y%m%dT%H").to_string();
+
+    let mut server = Server::new_async().await;
+    let rejected = server
+        .mock("HEAD", "/bucket/key")
+        .match_header("x-amz-date", Matcher::Regex(format!("^{skewed}")))
+        .with_status(403)
+        .with_header("date", &date)
+        .with_body(
+            r"<Error><Code>RequestTimeTooSkewed</Code><Message>The difference between the request time and the current time is too large.</Message><RequestId>4442587FB7D0A2F9</RequestId></Error>",
+        )
+        .expect(1)
+        .create_async()
+        .await;
+    let accepted = server
+        .mock("HEAD", "/bucket/key")
+        .match_header(
+            "x-amz-date",
+            Matcher::Regex(format!("^{}", server_time.format("%Y%m%dT%H"))),
+        )
+        .with_status(200)
+        .with_header("date", &date)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let s3 = test_s3(server.url(), Some("bucket"));
+
+    let headers = HeadObject::new("key", None).request(&s3).await.unwrap();
+    assert!(!headers.is_empty());
+    assert!(
+        (s3.clock().offset() - chrono::TimeDelta::hours(1)).abs() < chrono::TimeDelta::seconds(5)
+    );
+
+    rejected.assert_async().await;
+    accepted.assert_async().await;
+
+    // the offset is kept for the next requests, clones of the S3 share it
+    let other = s3.with_bucket(Some("other".to_string()));
+    assert_eq!(other.clock().offset(), s3.clock().offset());
+}
+
+#[tokio::test]
+async fn test_request_time_too_skewed_is_retried_once() {
+    // the clock is corrected from the `Date` of the response, which is the
+    // local time here, so the request is rejected again and not retried forever
+    let mut server = Server::new_async().await;
+    let rejected = server
+        .mock("GET", "/bucket")
+        .match_query(Matcher::Any)
+        .with_status(403)
+        .with_body(
+            r"<Error><Code>RequestTimeTooSkewed</Code><Message>The difference between the request time and the current time is too large.</Message><RequestId>4442587FB7D0A2F9</RequestId></Error>",
+        )
+        .expect(2)
+        .create_async()
+        .await;
+
+    let s3 = test_s3(server.url(), Some("bucket"));
+
+    let err = ListObjectsV2::new(None, None, None)
+        .request(&s3)
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Some("RequestTimeTooSkewed"));
+    rejected.assert_async().await;
+}
+
+fn fast_retries(attempts: u32) -> RetryPolicy {
+    RetryPolicy::new(attempts).with_delays(Duration::from_millis(1), Duration::from_millis(5))
+}
+
+#[tokio::test]
+async fn test_actions_retry_slow_down_and_server_errors() {
+    let mut server = Server::new_async().await;
+    let slow_down = server
+        .mock("GET", "/bucket")
+        .match_query(Matcher::Any)
+        .with_status(503)
+        .with_body(
+            r"<Error><Code>SlowDown</Code><Message>Please reduce your request rate.</Message><RequestId>1</RequestId></Error>",
+        )
+        .expect(2)
+        .create_async()
+        .await;
+    let listed = server
+        .mock("GET", "/bucket")
+        .match_query(Matcher::Any)
+        .with_status(200)
+        .with_body(list_objects_page_xml(&[("a.txt", 1)], "", false, None))
+        .expect(1)
+        .create_async()
+        .await;
+    let internal = server
+        .mock("DELETE", "/bucket/key")
+        .with_status(500)
+        .expect(1)
+        .create_async()
+        .await;
+    let deleted = server
+        .mock("DELETE", "/bucket/key")
+        .with_status(204)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let s3 = test_s3(server.url(), Some("bucket")).with_retry_policy(fast_retries(3));
+
+    let rs = ListObjectsV2::new(None, None, None)
+        .request(&s3)
+        .await
+        .unwrap();
+    assert_eq!(rs.contents.len(), 1);
+
+    DeleteObject::new("key").request(&s3).await.unwrap();
+
+    slow_down.assert_async().await;
+    listed.assert_async().await;
+    internal.assert_async().await;
+    deleted.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_actions_retries_exhausted_and_not_retryable() {
+    let mut server = Server::new_async().await;
+    let slow_down = server
+        .mock("HEAD", "/bucket/key")
+        .with_status(503)
+        .expect(3)
+        .create_async()
+        .await;
+    let missing = server
+        .mock("GET", "/bucket/missing")
+        .match_query(Matcher::Any)
+        .with_status(404)
+        .with_body(
+            r"<Error><Code>NoSuchKey</Code><Message>The specified key does not exist.</Message><RequestId>1</RequestId></Error>",
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let s3 = test_s3(server.url(), Some("bucket")).with_retry_policy(fast_retries(3));
+
+    let err = HeadObject::new("key", None).request(&s3).await.unwrap_err();
+    assert_eq!(err.status(), Some(503));
+
+    let err = GetObjectTagging::new("missing", None)
+        .request(&s3)
+        .await
+        .unwrap_err();
+    assert!(err.is_not_found());
+
+    slow_down.assert_async().await;
+    missing.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_actions_return_errors_not_retried() {
+    let mut server = Server::new_async().await;
+    let invalid = server
+        .mock("GET", "/bucket/invalid")
+        .match_query(Matcher::Any)
+        .with_status(400)
+        .with_body(
+            r"<Error><Code>InvalidArgument</Code><Message>Invalid argument.</Message><RequestId>1</RequestId></Error>",
+        )
+        .expect(1)
+        .create_async()
+        .await;
+    let denied = server
+        .mock("GET", "/bucket/denied")
+        .match_query(Matcher::Any)
+        .with_status(403)
+        .with_body(
+            r"<Error><Code>AccessDenied</Code><Message>Access Denied</Message><RequestId>2</RequestId></Error>",
+        )
+        .expect(1)
+        .create_async()
+        .await;
+    let timeout = server
+        .mock("GET", "/bucket/slow")
+        .match_query(Matcher::Any)
+        .with_status(400)
+        .with_body(
+            r"<Error><Code>RequestTimeout</Code><Message>Your socket connection to the server was not read from or written to within the timeout period.</Message><RequestId>3</RequestId></Error>",
+        )
+        .expect(1)
+        .create_async()
+        .await;
+    let tagged = server
+        .mock("GET", "/bucket/slow")
+        .match_query(Matcher::Any)
+        .with_status(200)
+        .with_body(r"<Tagging><TagSet></TagSet></Tagging>")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let s3 = test_s3(server.url(), Some("bucket")).with_retry_policy(fast_retries(3));
+
+    let err = GetObjectTagging::new("invalid", None)
+        .request(&s3)
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), Some(400));
+    assert_eq!(err.code(), Some("InvalidArgument"));
+
+    let err = GetObjectTagging::new("denied", None)
+        .request(&s3)
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), Some(403));
+    assert_eq!(err.code(), Some("AccessDenied"));
+
+    GetObjectTagging::new("slow", None)
+        .request(&s3)
+        .await
+        .unwrap();
+
+    invalid.assert_async().await;
+    denied.assert_async().await;
+    timeout.assert_async().await;
+    tagged.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_actions_retry_honors_retry_after() {
+    let mut server = Server::new_async().await;
+    let throttled = server
+        .mock("GET", "/")
+        .with_status(503)
+        .with_header("Retry-After", "1")
+        .with_body(
+            r"<Error><Code>SlowDown</Code><Message>Please reduce your request rate.</Message><RequestId>1</RequestId></Error>",
+        )
+        .expect(1)
+        .create_async()
+        .await;
+    let listed = server
+        .mock("GET", "/")
+        .with_status(200)
+        .with_body(
+            r#"<?xml version="1.0" encoding="UTF-8"?><ListAllMyBucketsResult><Buckets><Bucket><Name>bucket-one</Name><CreationDate>2025-03-13T00:00:00.000Z</CreationDate></Bucket></Buckets></ListAllMyBucketsResult>"#,
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let s3 = test_s3(server.url(), None).with_retry_policy(fast_retries(2));
+
+    let started = Instant::now();
+    ListBuckets::new(None).request(&s3).await.unwrap();
+    assert!(started.elapsed() >= Duration::from_secs(1));
+
+    throttled.assert_async().await;
+    listed.assert_async().await;
+}
@@ -277,14 +277,14 @@ fn test_binary_data_streaming() {
 
 #[test]
 fn test_compression_buffer_sizing() {
-    use s3m::stream::compress_chunk;
+    use s3m::stream::{compress_chunk, compression::Compression};
 
     // Test that compressed chunks are handled appropriately
     let data = BytesMut::from(&b"Hello, World! ".repeat(1000)[..]);
 
     let rt = tokio::runtime::Runtime::new().expect("Failed to create runtime");
     let compressed = rt
-        .block_on(compress_chunk(data.clone()))
+        .block_on(compress_chunk(data.clone(), Compression::default()))
         .expect("Compression should succeed");
 
     // Compressed data should be smaller than original for repetitive content
@@ -17,6 +17,7 @@ use s3m::{
         actions::{CreateMultipartUpload, UploadPart},
     },
     stream::{
+        compression::Compression,
         db::Db,
         iterator::PartIterator,
         part::Part,
@@ -138,6 +139,7 @@ fn create_resumable_state(home: &Path, id: &str) {
             updated_at: Some(created_at),
             pipe: false,
             compress: false,
+            compression: Compression::default(),
             encrypt: false,
             mode: StreamMode::FileMultipart,
         },
@@ -184,6 +186,7 @@ fn create_valid_resumable_state(home: &Path, id: &str) -> PathBuf {
             updated_at: Some(created_at),
             pipe: false,
             compress: false,
+            compression: Compression::default(),
             encrypt: false,
             mode: StreamMode::FileMultipart,
         },
@@ -265,6 +268,7 @@ async fn create_live_resumable_state(
             updated_at: Some(created_at),
             pipe: false,
             compress: false,
+            compression: Compression::default(),
             encrypt: false,
             mode: StreamMode::FileMultipart,
         },
@@ -357,6 +361,7 @@ async fn create_partially_uploaded_live_resumable_state(
             updated_at: Some(created_at),
             pipe: false,
             compress: false,
+            compression: Compression::default(),
             encrypt: false,
             mode: StreamMode::FileMultipart,
         },
@@ -0,0 +1,190 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unnecessary_wraps
+)]
+
+// The certificates in tests/fixtures/tls were generated with openssl: a CA
+// (key discarded), a server certificate for localhost/127.0.0.1 and a client
+// certificate, both signed by the CA and valid for 100 years.
+
+use s3m::{
+    cli::Config,
+    s3::{ClientConfig, Credentials, Region, S3, actions::HeadObject},
+};
+use secrecy::SecretString;
+use std::{io::Write, path::PathBuf, sync::Arc};
+use tempfile::NamedTempFile;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+use tokio_rustls::{
+    TlsAcceptor,
+    rustls::{
+        self, RootCertStore, ServerConfig,
+        pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject},
+        server::WebPkiClientVerifier,
+    },
+};
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/tls")
+        .join(name)
+}
+
+/// HTTPS server answering `200 OK` to every request, requiring a client
+/// certificate signed by the test CA when `mtls` is set.
+async fn tls_server(mtls: bool) -> String {
+    let certs = CertificateDer::pem_file_iter(fixture("server.pem"))
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let key = PrivateKeyDer::from_pem_file(fixture("server-key.pem")).unwrap();
+
+    let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .unwrap();
+    let config = if mtls {
+        let mut roots = RootCertStore::empty();
+        for ca in CertificateDer::pem_file_iter(fixture("ca.pem")).unwrap() {
+            roots.add(ca.unwrap()).unwrap();
+        }
+        let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+            .build()
+            .unwrap();
+        builder.with_client_cert_verifier(verifier)
+    } else {
+        builder.with_no_client_auth()
+    }
+    .with_single_cert(certs, key)
+    .unwrap();
+
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!(
+        "https://localhost:{}",
+        listener.local_addr().unwrap().port()
+    );
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                // failed handshakes are expected, the client rejects the server
+                // or the server rejects the client
+                let Ok(mut stream) = acceptor.accept(stream).await else {
+                    return;
+                };
+
+                let mut request = Vec::new();
+                let mut buf = [0; 4096];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match stream.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+
+                let _ = stream
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\netag: \"tls\"\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    )
+                    .await;
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+
+    url
+}
+
+fn test_s3(endpoint: String) -> S3 {
+    let credentials = Credentials::new(
+        "minioadmin",
+        &SecretString::new("minioadmin".to_string().into()),
+    );
+    let region = Region::custom("us-east-1", endpoint);
+
+    S3::new(&credentials, &region, Some("bucket".to_string()), false)
+}
+
+async fn head(s3: &S3) -> s3m::s3::error::Result<String> {
+    let headers = HeadObject::new("key", None).request(s3).await?;
+    Ok(headers.get("etag").cloned().unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_tls_ca_bundle() {
+    let url = tls_server(false).await;
+
+    // the test CA is not in the system roots
+    assert!(head(&test_s3(url.clone())).await.is_err());
+
+    let config = ClientConfig {
+        ca_bundle: Some(fixture("ca.pem")),
+        ..ClientConfig::default()
+    };
+    let s3 = test_s3(url).with_client_config(&config).unwrap();
+    assert_eq!(head(&s3).await.unwrap(), "\"tls\"");
+}
+
+#[tokio::test]
+async fn test_tls_insecure_skip_verify() {
+    let url = tls_server(false).await;
+
+    let config = ClientConfig {
+        insecure_skip_verify: true,
+        ..ClientConfig::default()
+    };
+    let s3 = test_s3(url).with_client_config(&config).unwrap();
+    assert_eq!(head(&s3).await.unwrap(), "\"tls\"");
+}
+
+#[tokio::test]
+async fn test_tls_client_certificate() {
+    let url = tls_server(true).await;
+
+    // no client certificate, rejected by the server
+    let config = ClientConfig {
+        ca_bundle: Some(fixture("ca.pem")),
+        ..ClientConfig::default()
+    };
+    let s3 = test_s3(url.clone()).with_client_config(&config).unwrap();
+    assert!(head(&s3).await.is_err());
+
+    let config = ClientConfig {
+        ca_bundle: Some(fixture("ca.pem")),
+        client_cert: Some(fixture("client.pem")),
+        client_key: Some(fixture("client-key.pem")),
+        ..ClientConfig::default()
+    };
+    let s3 = test_s3(url).with_client_config(&config).unwrap();
+    assert_eq!(head(&s3).await.unwrap(), "\"tls\"");
+}
+
+#[tokio::test]
+async fn test_tls_host_config() {
+    let url = tls_server(true).await;
+
+    let yaml = format!(
+        "---\nhosts:\n  ceph:\n    endpoint: {url}\n    access_key: XXX\n    secret_key: YYY\n    ca_bundle: {}\n    client_cert: {}\n    client_key: {}\n",
+        fixture("ca.pem").display(),
+        fixture("client.pem").display(),
+        fixture("client-key.pem").display(),
+    );
+
+    let mut config_file = NamedTempFile::new().unwrap();
+    config_file.write_all(yaml.as_bytes()).unwrap();
+    let config = Config::new(config_file.path().to_path_buf()).unwrap();
+
+    let host = config.get_host("ceph").unwrap();
+    assert_eq!(host.client.ca_bundle, Some(fixture("ca.pem")));
+
+    let s3 = host.get_s3(Some("bucket".to_string()), false).unwrap();
+    assert_eq!(head(&s3).await.unwrap(), "\"tls\"");
+}